@@ -0,0 +1,345 @@
+use std::{collections::BTreeSet, fs, path::Path, process::Command};
+
+use cargo_metadata::Metadata;
+use indexmap::{IndexMap, IndexSet};
+
+use crate::{
+    error::AppError,
+    metadata::Workspace,
+    reporting::Reporter,
+    test_runner::Runners,
+    testing::{
+        filter::TestFilter,
+        plan::{RunMode, TestCrates, TestPlan},
+        result::{TestOutcome, TestResults},
+    },
+    vcs::{ChangedFile, FileType, Vcs},
+};
+
+pub mod store;
+
+pub use store::{HttpStore, LocalStore, ResultStore};
+
+#[cfg(test)]
+mod store_tests;
+#[cfg(test)]
+mod tests;
+
+/// Inputs that affect the outcome of every crate's tests
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedInputs {
+    /// Output of `rustc -vV`
+    pub toolchain: String,
+    /// Content hashes of workspace-wide files such as `Cargo.lock`
+    pub files: Vec<(String, Option<String>)>,
+    pub mode: RunMode,
+    /// Arguments given on the command line
+    pub runner_args: Vec<String>,
+    /// Environment variables that change how crates are compiled, such as `RUSTFLAGS`
+    pub env: Vec<(String, String)>,
+    /// Crates whose doc-tests are run separately
+    pub doc_test_crates: IndexSet<String>,
+}
+
+impl SharedInputs {
+    /// Files outside of crate directories that can change the result of any crate
+    const WORKSPACE_FILES: &[&str] = &["Cargo.lock", "Cargo.toml", ".cargo/config.toml"];
+
+    /// Variables that only locate files or configure cargo's output, and so would keep
+    /// results from being shared between machines without changing them
+    const IGNORED_ENV: &[&str] = &[
+        "CARGO_HOME",
+        "CARGO_TARGET_DIR",
+        "CARGO_BUILD_TARGET_DIR",
+        "CARGO_TERM_",
+        "CARGO_LOG",
+        "CARGO_HTTP_",
+        "CARGO_NET_",
+        "CARGO_REGISTRY",
+        "CARGO_REGISTRIES_",
+        "CARGO_MAKEFLAGS",
+    ];
+
+    pub fn collect(
+        workspace_root: &Path,
+        mode: RunMode,
+        runner_args: &[String],
+        doc_test_crates: &IndexSet<String>,
+    ) -> Result<Self, AppError> {
+        let output = Command::new("rustc")
+            .arg("-vV")
+            .current_dir(workspace_root)
+            .output()
+            .map_err(|e| AppError::CommandFailed {
+                command: "rustc -vV".to_string(),
+                reason: e.to_string(),
+            })?;
+
+        let files = Self::WORKSPACE_FILES
+            .iter()
+            .map(|file| (file.to_string(), hash_file(&workspace_root.join(file))))
+            .collect();
+
+        Ok(SharedInputs {
+            toolchain: String::from_utf8_lossy(&output.stdout).trim().to_string(),
+            files,
+            mode,
+            runner_args: runner_args.to_vec(),
+            env: Self::build_env(std::env::vars()),
+            doc_test_crates: doc_test_crates.clone(),
+        })
+    }
+
+    /// Select the `RUSTFLAGS`, `RUSTDOCFLAGS` and `CARGO_*` variables that can change the
+    /// result of a crate, sorted by name
+    pub fn build_env(vars: impl Iterator<Item = (String, String)>) -> Vec<(String, String)> {
+        let mut env: Vec<_> = vars
+            .filter(|(name, _)| {
+                name == "RUSTFLAGS"
+                    || name == "RUSTDOCFLAGS"
+                    || (name.starts_with("CARGO_")
+                        && !Self::IGNORED_ENV
+                            .iter()
+                            .any(|ignored| name.starts_with(ignored)))
+            })
+            .collect();
+        env.sort();
+        env
+    }
+}
+
+/// Computes a fingerprint for each crate from the content of the crate and its workspace dependencies
+pub struct Fingerprinter<'a> {
+    vcs: &'a dyn Vcs,
+    workspace: &'a Workspace,
+    runners: &'a Runners,
+    shared: SharedInputs,
+    /// Uncommitted changes, which are not reflected in tree IDs
+    uncommitted: &'a [ChangedFile],
+}
+
+impl<'a> Fingerprinter<'a> {
+    pub fn new(
+        vcs: &'a dyn Vcs,
+        workspace: &'a Workspace,
+        runners: &'a Runners,
+        shared: SharedInputs,
+        uncommitted: &'a [ChangedFile],
+    ) -> Self {
+        Fingerprinter {
+            vcs,
+            workspace,
+            runners,
+            shared,
+            uncommitted,
+        }
+    }
+
+    /// Fingerprint a crate from the tree IDs of its directory and those of its workspace
+    /// dependencies at the current commit, along with the content of any uncommitted files
+    ///
+    /// Returns `None` when the content cannot be identified, such as for crates whose
+    /// directory is not committed or contains untracked directories.
+    pub fn fingerprint(&self, crate_name: &str) -> Result<Option<String>, AppError> {
+        let mut inputs = Vec::new();
+
+        for name in workspace_dependencies(crate_name, &self.workspace.metadata) {
+            let Some(crate_info) = self.workspace.crates.get(&name) else {
+                continue;
+            };
+
+            let relative = self.relative(&crate_info.path);
+            let Some(tree_id) = self.vcs.get_tree_id(&self.workspace.root, relative)? else {
+                return Ok(None);
+            };
+            inputs.push(format!("crate {} {}", name, tree_id));
+
+            for file in self.uncommitted {
+                for path in file.old_path.iter().chain([&file.current_path]) {
+                    if !path.starts_with(&crate_info.path) {
+                        continue;
+                    }
+                    if file.file_type == FileType::Directory {
+                        return Ok(None);
+                    }
+                    inputs.push(format!(
+                        "dirty {} {}",
+                        self.relative(path).display(),
+                        hash_file(path).as_deref().unwrap_or("-")
+                    ));
+                }
+            }
+
+            // Files outside the crate directory are not covered by its tree ID
+            for path in self.workspace.file_dependencies.for_crate(&name) {
+                inputs.push(format!(
+                    "file {} {}",
+                    self.relative(path).display(),
+                    hash_file(path).as_deref().unwrap_or("-")
+                ));
+            }
+        }
+
+        for (file, hash) in &self.shared.files {
+            inputs.push(format!("file {} {}", file, hash.as_deref().unwrap_or("-")));
+        }
+        inputs.push(format!("toolchain {}", self.shared.toolchain));
+        for (name, value) in &self.shared.env {
+            inputs.push(format!("env {}={}", name, value));
+        }
+
+        // The command the crate is run with, including its configured runner settings
+        inputs.push(format!("mode {}", self.shared.mode.verb()));
+        inputs.push(format!("runner {}", self.runners.runner(crate_name).name()));
+        inputs.push(format!(
+            "args {} {}",
+            self.runners.args(crate_name).join(" "),
+            self.shared.runner_args.join(" ")
+        ));
+        for (name, value) in self.runners.env(crate_name) {
+            inputs.push(format!("crate env {}={}", name, value));
+        }
+        inputs.push(format!(
+            "doc-tests {}",
+            self.shared.doc_test_crates.contains(crate_name)
+        ));
+
+        Ok(Some(hash_bytes(inputs.join("\n").as_bytes())))
+    }
+
+    /// Paths are hashed relative to the workspace root, so fingerprints do not depend on
+    /// where the workspace is checked out
+    fn relative<'p>(&self, path: &'p Path) -> &'p Path {
+        path.strip_prefix(&self.workspace.root).unwrap_or(path)
+    }
+}
+
+/// Fingerprint the crates in a discovered plan, skipping those with a passing result in
+/// the local store or, failing that, the shared store
+///
+/// Crates given explicitly are always tested, as are crates limited to some of their
+/// tests, since a passing subset says nothing about the rest. Hits from the shared store
+/// are copied to the local store, and a shared store that cannot be reached is reported
+/// and no longer consulted.
+pub fn apply_cache(
+    test_plan: &mut TestPlan,
+    fingerprinter: &Fingerprinter,
+    local: &dyn ResultStore,
+    mut shared: Option<&dyn ResultStore>,
+    reporter: &mut dyn Reporter,
+) -> Result<(), AppError> {
+    if matches!(test_plan.crates, TestCrates::Manual(_)) {
+        return Ok(());
+    }
+
+    let selected: Vec<String> = test_plan
+        .get_selected_crates()
+        .into_iter()
+        .filter(|name| {
+            !matches!(
+                test_plan.test_filters.get(*name),
+                Some(TestFilter::Targets { .. })
+            )
+        })
+        .cloned()
+        .collect();
+
+    for crate_name in selected {
+        let Some(fingerprint) = fingerprinter.fingerprint(&crate_name)? else {
+            continue;
+        };
+
+        if local.contains(&fingerprint)? {
+            test_plan.cached.insert(crate_name);
+            continue;
+        }
+
+        let shared_hit = match shared.map(|store| store.contains(&fingerprint)) {
+            Some(Ok(hit)) => hit,
+            Some(Err(e)) => {
+                reporter.note(&format!("ignoring the shared result store, {}", e));
+                shared = None;
+                false
+            }
+            None => false,
+        };
+
+        if shared_hit {
+            if let Err(e) = local.insert(&fingerprint, &crate_name) {
+                reporter.note(&e.to_string());
+            }
+            test_plan.cached.insert(crate_name.clone());
+            test_plan.shared_cached.insert(crate_name);
+        } else {
+            test_plan.fingerprints.insert(crate_name, fingerprint);
+        }
+    }
+
+    Ok(())
+}
+
+/// Find a crate and the workspace crates it depends on, directly or transitively,
+/// through normal, dev or build dependencies
+pub fn workspace_dependencies(crate_name: &str, metadata: &Metadata) -> BTreeSet<String> {
+    let members: IndexMap<_, _> = metadata
+        .workspace_packages()
+        .into_iter()
+        .map(|package| (package.name.as_str(), package))
+        .collect();
+
+    let mut found = BTreeSet::new();
+    let mut pending = vec![crate_name.to_string()];
+
+    while let Some(name) = pending.pop() {
+        let Some(package) = members.get(name.as_str()) else {
+            continue;
+        };
+        if !found.insert(name) {
+            continue;
+        }
+
+        pending.extend(
+            package
+                .dependencies
+                .iter()
+                .filter(|dependency| members.contains_key(dependency.name.as_str()))
+                .map(|dependency| dependency.name.clone()),
+        );
+    }
+
+    found
+}
+
+/// Hash bytes the way git hashes blobs
+fn hash_bytes(bytes: &[u8]) -> String {
+    gix::objs::compute_hash(gix::hash::Kind::Sha1, gix::objs::Kind::Blob, bytes)
+        .map(|id| id.to_string())
+        .unwrap_or_default()
+}
+
+fn hash_file(path: &Path) -> Option<String> {
+    fs::read(path).ok().map(|contents| hash_bytes(&contents))
+}
+
+/// Add the fingerprints of crates that passed to each store, so later runs skip them
+///
+/// Crates that only passed when retried are tested again next time. Failing to record a
+/// result is reported without failing the run.
+pub fn record_passing(
+    results: &TestResults,
+    fingerprints: &IndexMap<String, String>,
+    stores: &[Box<dyn ResultStore>],
+    reporter: &mut dyn Reporter,
+) {
+    for result in results.with_outcome(TestOutcome::Passed) {
+        let Some(fingerprint) = fingerprints.get(&result.crate_name) else {
+            continue;
+        };
+
+        for store in stores {
+            if let Err(e) = store.insert(fingerprint, &result.crate_name) {
+                reporter.error(&e.to_string());
+            }
+        }
+    }
+}
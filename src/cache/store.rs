@@ -0,0 +1,117 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::error::AppError;
+
+/// Storage for the fingerprints of crates whose tests passed
+pub trait ResultStore {
+    /// Check whether a crate with the given fingerprint has passed before
+    fn contains(&self, fingerprint: &str) -> Result<bool, AppError>;
+
+    /// Record that the crate with the given fingerprint passed
+    fn insert(&self, fingerprint: &str, crate_name: &str) -> Result<(), AppError>;
+}
+
+/// Fingerprints stored as files in a directory, such as the target directory or a
+/// directory shared between CI runners
+pub struct LocalStore {
+    dir: PathBuf,
+}
+
+impl LocalStore {
+    pub fn new(dir: PathBuf) -> Self {
+        LocalStore { dir }
+    }
+
+    /// The store kept under the workspace's target directory
+    pub fn in_target_dir(target_dir: &Path) -> Self {
+        LocalStore::new(target_dir.join("test-changed").join("cache"))
+    }
+}
+
+impl ResultStore for LocalStore {
+    fn contains(&self, fingerprint: &str) -> Result<bool, AppError> {
+        Ok(self.dir.join(fingerprint).is_file())
+    }
+
+    fn insert(&self, fingerprint: &str, crate_name: &str) -> Result<(), AppError> {
+        let path = self.dir.join(fingerprint);
+        let write_failed = |e: std::io::Error| AppError::WriteFailed {
+            path: path.display().to_string(),
+            reason: e.to_string(),
+        };
+
+        fs::create_dir_all(&self.dir).map_err(write_failed)?;
+        fs::write(&path, crate_name).map_err(write_failed)
+    }
+}
+
+/// Fingerprints stored on an HTTP server as `{base_url}/{fingerprint}`
+///
+/// A `GET` answering `200` is a hit and any other status is a miss. Passing crates are
+/// recorded with a `PUT` whose body is the crate name.
+pub struct HttpStore {
+    base_url: String,
+    agent: ureq::Agent,
+}
+
+impl HttpStore {
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    pub fn new(base_url: &str) -> Self {
+        let agent = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .timeout_global(Some(Self::TIMEOUT))
+            .build()
+            .into();
+
+        HttpStore {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            agent,
+        }
+    }
+
+    fn url(&self, fingerprint: &str) -> String {
+        format!("{}/{}", self.base_url, fingerprint)
+    }
+}
+
+impl ResultStore for HttpStore {
+    fn contains(&self, fingerprint: &str) -> Result<bool, AppError> {
+        let url = self.url(fingerprint);
+        let response = self
+            .agent
+            .get(&url)
+            .call()
+            .map_err(|e| AppError::RemoteStoreFailed {
+                url: url.clone(),
+                reason: e.to_string(),
+            })?;
+
+        Ok(response.status() == 200)
+    }
+
+    fn insert(&self, fingerprint: &str, crate_name: &str) -> Result<(), AppError> {
+        let url = self.url(fingerprint);
+        let response =
+            self.agent
+                .put(&url)
+                .send(crate_name)
+                .map_err(|e| AppError::RemoteStoreFailed {
+                    url: url.clone(),
+                    reason: e.to_string(),
+                })?;
+
+        if !response.status().is_success() {
+            return Err(AppError::RemoteStoreFailed {
+                url,
+                reason: format!("server responded with {}", response.status()),
+            });
+        }
+
+        Ok(())
+    }
+}
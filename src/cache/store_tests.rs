@@ -0,0 +1,94 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use tiny_http::{Method, Response, Server};
+
+use super::store::{HttpStore, LocalStore, ResultStore};
+use crate::error::AppError;
+
+/// Start a key/value server that answers GET and PUT like a shared result store,
+/// returning its base URL
+fn start_server() -> String {
+    let server = Server::http("127.0.0.1:0").unwrap();
+    let port = server.server_addr().to_ip().unwrap().port();
+    let entries = Arc::new(Mutex::new(HashMap::<String, String>::new()));
+
+    thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let key = request.url().to_string();
+            let response = match request.method() {
+                Method::Get => match entries.lock().unwrap().get(&key) {
+                    Some(value) => Response::from_string(value.clone()),
+                    None => Response::from_string("").with_status_code(404),
+                },
+                Method::Put => {
+                    let mut body = String::new();
+                    request.as_reader().read_to_string(&mut body).unwrap();
+                    entries.lock().unwrap().insert(key, body);
+                    Response::from_string("").with_status_code(201)
+                }
+                _ => Response::from_string("").with_status_code(405),
+            };
+            let _ = request.respond(response);
+        }
+    });
+
+    format!("http://127.0.0.1:{}/cache/", port)
+}
+
+#[test]
+fn test_local_store() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let store = LocalStore::in_target_dir(dir.path());
+
+    assert!(!store.contains("abc").unwrap());
+
+    store.insert("abc", "app").unwrap();
+
+    assert!(store.contains("abc").unwrap());
+    assert!(!store.contains("def").unwrap());
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("test-changed/cache/abc")).unwrap(),
+        "app"
+    );
+}
+
+#[test]
+fn test_http_store() {
+    let store = HttpStore::new(&start_server());
+
+    assert!(!store.contains("abc").unwrap());
+
+    store.insert("abc", "app").unwrap();
+
+    assert!(store.contains("abc").unwrap());
+    assert!(!store.contains("def").unwrap());
+}
+
+#[test]
+fn test_http_store_shared_between_clients() {
+    let url = start_server();
+
+    HttpStore::new(&url).insert("abc", "app").unwrap();
+
+    assert!(HttpStore::new(&url).contains("abc").unwrap());
+}
+
+#[test]
+fn test_http_store_unreachable() {
+    // Bind and drop a listener to find a port nothing is listening on
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let store = HttpStore::new(&format!("http://127.0.0.1:{}", port));
+
+    assert!(matches!(
+        store.contains("abc"),
+        Err(AppError::RemoteStoreFailed { .. })
+    ));
+}
@@ -0,0 +1,466 @@
+use std::{cell::RefCell, collections::HashMap, path::PathBuf};
+
+use cargo_metadata::semver::{Version, VersionReq};
+use cargo_metadata::{
+    DependencyBuilder, DependencyKind, MetadataBuilder, PackageBuilder, PackageId,
+};
+
+use super::*;
+use crate::metadata::{self, CrateMappings, FileDependencies, IgnorePatterns};
+use crate::quarantine::Quarantine;
+use crate::reporting::console::ConsoleReporter;
+use crate::test_runner::{RunnerOverride, TestRunnerType};
+use crate::testing::plan::{DiscoveredTestCrate, DiscoveryType, RunMode};
+use crate::testing::result::TestResult;
+use crate::testing::schedule::Schedule;
+use crate::vcs::ChangeType;
+
+/// A repository whose tree IDs are set by the test
+#[derive(Default)]
+struct FakeVcs {
+    trees: RefCell<HashMap<PathBuf, String>>,
+}
+
+impl FakeVcs {
+    fn set_tree(&self, path: &str, id: &str) {
+        self.trees
+            .borrow_mut()
+            .insert(PathBuf::from(path), id.to_string());
+    }
+}
+
+impl Vcs for FakeVcs {
+    fn get_workspace_root(&self, path: &Path) -> Result<PathBuf, AppError> {
+        Ok(path.to_path_buf())
+    }
+
+    fn get_uncommitted_changes(&self, _: &Path) -> Result<Vec<ChangedFile>, AppError> {
+        Ok(Vec::new())
+    }
+
+    fn get_changes_between(
+        &self,
+        _: &Path,
+        _: &str,
+        _: Option<&str>,
+    ) -> Result<Vec<ChangedFile>, AppError> {
+        Ok(Vec::new())
+    }
+
+    fn get_head_commit(&self, _: &Path) -> Result<Option<String>, AppError> {
+        Ok(None)
+    }
+
+    fn get_tree_id(&self, _: &Path, path: &Path) -> Result<Option<String>, AppError> {
+        Ok(self.trees.borrow().get(path).cloned())
+    }
+
+    fn filter_ignored(&self, _: &Path, paths: Vec<PathBuf>) -> Result<Vec<PathBuf>, AppError> {
+        Ok(paths)
+    }
+}
+
+fn package(name: &str, dependencies: &[&str]) -> cargo_metadata::Package {
+    let dependencies: Vec<_> = dependencies
+        .iter()
+        .map(|dependency| {
+            DependencyBuilder::default()
+                .name(dependency.to_string())
+                .kind(DependencyKind::Normal)
+                .req(VersionReq::STAR)
+                .optional(false)
+                .uses_default_features(true)
+                .source(None)
+                .target(None)
+                .features(vec![])
+                .rename(None)
+                .registry(None)
+                .path(Some(format!("/workspace/{}", dependency).into()))
+                .build()
+                .unwrap()
+        })
+        .collect();
+
+    PackageBuilder::new(
+        name,
+        Version::new(1, 0, 0),
+        PackageId {
+            repr: name.to_string(),
+        },
+        format!("/workspace/{}/Cargo.toml", name),
+    )
+    .dependencies(dependencies)
+    .build()
+    .unwrap()
+}
+
+/// `app` depends on `core` through `util`, and `other` depends on nothing
+fn workspace() -> Workspace {
+    let packages = vec![
+        package("core", &["serde"]),
+        package("util", &["core"]),
+        package("app", &["util"]),
+        package("other", &[]),
+    ];
+    let members: Vec<_> = packages.iter().map(|p| p.id.clone()).collect();
+
+    let metadata = MetadataBuilder::default()
+        .packages(packages)
+        .workspace_root("/workspace")
+        .target_directory("/workspace/target")
+        .workspace_members(members)
+        .workspace_default_members(cargo_metadata::WorkspaceDefaultMembers::default())
+        .workspace_metadata(serde_json::Value::Null)
+        .resolve(None)
+        .version(1usize)
+        .build()
+        .unwrap();
+
+    Workspace {
+        root: PathBuf::from("/workspace"),
+        crates: metadata::get_workspace_crates(&metadata).unwrap(),
+        metadata,
+        dep_info: Vec::new(),
+        file_dependencies: FileDependencies::default(),
+        mappings: CrateMappings::default(),
+        ignore_patterns: IgnorePatterns::default(),
+        quarantine: Quarantine::default(),
+        custom_runner: None,
+        runner_overrides: IndexMap::new(),
+        clippy_args: Vec::new(),
+    }
+}
+
+fn shared(runner_args: &[&str]) -> SharedInputs {
+    SharedInputs {
+        toolchain: "rustc 1.85.0".to_string(),
+        files: vec![("Cargo.lock".to_string(), Some("abc".to_string()))],
+        mode: RunMode::Test,
+        runner_args: runner_args.iter().map(|arg| arg.to_string()).collect(),
+        env: Vec::new(),
+        doc_test_crates: IndexSet::new(),
+    }
+}
+
+fn cargo_runners(workspace: &Workspace) -> Runners {
+    Runners::new(&TestRunnerType::Cargo, workspace).unwrap()
+}
+
+fn fake_vcs() -> FakeVcs {
+    let vcs = FakeVcs::default();
+    for name in ["core", "util", "app", "other"] {
+        vcs.set_tree(name, &format!("{}-1", name));
+    }
+    vcs
+}
+
+#[test]
+fn test_workspace_dependencies() {
+    let workspace = workspace();
+
+    assert_eq!(
+        workspace_dependencies("app", &workspace.metadata),
+        BTreeSet::from(["app".to_string(), "core".to_string(), "util".to_string()])
+    );
+    assert_eq!(
+        workspace_dependencies("other", &workspace.metadata),
+        BTreeSet::from(["other".to_string()])
+    );
+}
+
+#[test]
+fn test_fingerprint_follows_dependency_trees() {
+    let workspace = workspace();
+    let runners = cargo_runners(&workspace);
+    let vcs = fake_vcs();
+    let fingerprinter = Fingerprinter::new(&vcs, &workspace, &runners, shared(&[]), &[]);
+
+    let app = fingerprinter.fingerprint("app").unwrap().unwrap();
+    let other = fingerprinter.fingerprint("other").unwrap().unwrap();
+    assert_eq!(fingerprinter.fingerprint("app").unwrap(), Some(app.clone()));
+
+    vcs.set_tree("core", "core-2");
+
+    assert_ne!(fingerprinter.fingerprint("app").unwrap(), Some(app));
+    assert_eq!(fingerprinter.fingerprint("other").unwrap(), Some(other));
+}
+
+#[test]
+fn test_fingerprint_includes_runner_args() {
+    let workspace = workspace();
+    let runners = cargo_runners(&workspace);
+    let vcs = fake_vcs();
+
+    let default = Fingerprinter::new(&vcs, &workspace, &runners, shared(&[]), &[]);
+    let release = Fingerprinter::new(&vcs, &workspace, &runners, shared(&["--release"]), &[]);
+
+    assert_ne!(
+        default.fingerprint("app").unwrap(),
+        release.fingerprint("app").unwrap()
+    );
+}
+
+#[test]
+fn test_fingerprint_includes_run_inputs() {
+    let workspace = workspace();
+    let runners = cargo_runners(&workspace);
+    let vcs = fake_vcs();
+    let fingerprint = |workspace: &Workspace, runners: &Runners, shared: SharedInputs| {
+        Fingerprinter::new(&vcs, workspace, runners, shared, &[])
+            .fingerprint("app")
+            .unwrap()
+            .unwrap()
+    };
+    let default = fingerprint(&workspace, &runners, shared(&[]));
+
+    let mut check = shared(&[]);
+    check.mode = RunMode::Check;
+    assert_ne!(fingerprint(&workspace, &runners, check), default);
+
+    let mut doc_tests = shared(&[]);
+    doc_tests.doc_test_crates = IndexSet::from(["app".to_string()]);
+    assert_ne!(fingerprint(&workspace, &runners, doc_tests), default);
+
+    let mut rustflags = shared(&[]);
+    rustflags.env = vec![("RUSTFLAGS".to_string(), "-C debug-assertions".to_string())];
+    assert_ne!(fingerprint(&workspace, &runners, rustflags), default);
+
+    let mut overridden = self::workspace();
+    overridden.runner_overrides = IndexMap::from([(
+        "app".to_string(),
+        RunnerOverride {
+            runner: None,
+            args: vec!["--features".to_string(), "slow".to_string()],
+            env: IndexMap::new(),
+        },
+    )]);
+    let overridden_runners = cargo_runners(&overridden);
+    assert_ne!(
+        fingerprint(&overridden, &overridden_runners, shared(&[])),
+        default
+    );
+
+    overridden.runner_overrides["app"].args = Vec::new();
+    overridden.runner_overrides["app"].env =
+        IndexMap::from([("DATABASE_URL".to_string(), "sqlite::memory:".to_string())]);
+    let overridden_runners = cargo_runners(&overridden);
+    assert_ne!(
+        fingerprint(&overridden, &overridden_runners, shared(&[])),
+        default
+    );
+}
+
+#[test]
+fn test_build_env() {
+    let vars = [
+        ("RUSTFLAGS", "-D warnings"),
+        ("PATH", "/usr/bin"),
+        ("CARGO_PROFILE_DEV_OPT_LEVEL", "1"),
+        ("CARGO_HOME", "/home/ci/.cargo"),
+        ("CARGO_TERM_COLOR", "always"),
+        ("CARGO_INCREMENTAL", "0"),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value.to_string()));
+
+    assert_eq!(
+        SharedInputs::build_env(vars),
+        [
+            ("CARGO_INCREMENTAL".to_string(), "0".to_string()),
+            ("CARGO_PROFILE_DEV_OPT_LEVEL".to_string(), "1".to_string()),
+            ("RUSTFLAGS".to_string(), "-D warnings".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_fingerprint_uncommitted_changes() {
+    let workspace = workspace();
+    let runners = cargo_runners(&workspace);
+    let vcs = fake_vcs();
+    let clean = Fingerprinter::new(&vcs, &workspace, &runners, shared(&[]), &[])
+        .fingerprint("app")
+        .unwrap();
+
+    let removed = [ChangedFile {
+        current_path: PathBuf::from("/workspace/core/src/old.rs"),
+        old_path: None,
+        file_type: FileType::File,
+        change_type: ChangeType::Removed,
+    }];
+    let dirty = Fingerprinter::new(&vcs, &workspace, &runners, shared(&[]), &removed)
+        .fingerprint("app")
+        .unwrap();
+
+    assert!(dirty.is_some());
+    assert_ne!(dirty, clean);
+
+    let untracked_dir = [ChangedFile {
+        current_path: PathBuf::from("/workspace/util/fixtures"),
+        old_path: None,
+        file_type: FileType::Directory,
+        change_type: ChangeType::Added,
+    }];
+    let fingerprinter = Fingerprinter::new(&vcs, &workspace, &runners, shared(&[]), &untracked_dir);
+
+    assert_eq!(fingerprinter.fingerprint("app").unwrap(), None);
+    assert!(fingerprinter.fingerprint("other").unwrap().is_some());
+}
+
+#[test]
+fn test_fingerprint_untracked_crate() {
+    let workspace = workspace();
+    let runners = cargo_runners(&workspace);
+    let vcs = FakeVcs::default();
+    vcs.set_tree("other", "other-1");
+    let fingerprinter = Fingerprinter::new(&vcs, &workspace, &runners, shared(&[]), &[]);
+
+    assert_eq!(fingerprinter.fingerprint("app").unwrap(), None);
+    assert!(fingerprinter.fingerprint("other").unwrap().is_some());
+}
+
+fn discovered_plan(crates: &[&str]) -> TestPlan {
+    TestPlan {
+        workspace_root: PathBuf::from("/workspace"),
+        crates: TestCrates::Discovered(
+            crates
+                .iter()
+                .map(|name| DiscoveredTestCrate {
+                    name: name.to_string(),
+                    discovery_type: DiscoveryType::Modified,
+                })
+                .collect(),
+        ),
+        mode: RunMode::Test,
+        with_dependents: false,
+        fail_fast: true,
+        retries: 0,
+        verbose: false,
+        test_runner_args: Vec::new(),
+        test_filters: IndexMap::new(),
+        cached: Default::default(),
+        shared_cached: Default::default(),
+        fingerprints: IndexMap::new(),
+        quarantine: Quarantine::default(),
+        schedule: Schedule::default(),
+        crate_stats: IndexMap::new(),
+        doc_test_crates: Default::default(),
+        shard: None,
+    }
+}
+
+#[test]
+fn test_apply_cache_with_shared_store() {
+    let workspace = workspace();
+    let runners = cargo_runners(&workspace);
+    let vcs = fake_vcs();
+    let fingerprinter = Fingerprinter::new(&vcs, &workspace, &runners, shared(&[]), &[]);
+    let local_dir = tempfile::TempDir::new().unwrap();
+    let shared_dir = tempfile::TempDir::new().unwrap();
+    let local = LocalStore::new(local_dir.path().to_path_buf());
+    let shared_store = LocalStore::new(shared_dir.path().to_path_buf());
+
+    let core = fingerprinter.fingerprint("core").unwrap().unwrap();
+    let util = fingerprinter.fingerprint("util").unwrap().unwrap();
+    local.insert(&core, "core").unwrap();
+    shared_store.insert(&util, "util").unwrap();
+
+    let mut plan = discovered_plan(&["core", "util", "app"]);
+    let mut reporter = ConsoleReporter::new(Vec::new(), false);
+    apply_cache(
+        &mut plan,
+        &fingerprinter,
+        &local,
+        Some(&shared_store),
+        &mut reporter,
+    )
+    .unwrap();
+
+    assert_eq!(plan.get_crates_to_test(), vec!["app"]);
+    assert_eq!(plan.cached.iter().collect::<Vec<_>>(), vec!["core", "util"]);
+    assert_eq!(plan.shared_cached.iter().collect::<Vec<_>>(), vec!["util"]);
+    assert!(plan.fingerprints.contains_key("app"));
+
+    // Shared hits are copied to the local store
+    assert!(local.contains(&util).unwrap());
+}
+
+/// A store that cannot be reached
+struct UnavailableStore;
+
+impl ResultStore for UnavailableStore {
+    fn contains(&self, _: &str) -> Result<bool, AppError> {
+        Err(AppError::RemoteStoreFailed {
+            url: "http://cache".to_string(),
+            reason: "connection refused".to_string(),
+        })
+    }
+
+    fn insert(&self, _: &str, _: &str) -> Result<(), AppError> {
+        unreachable!()
+    }
+}
+
+#[test]
+fn test_apply_cache_with_unavailable_shared_store() {
+    let workspace = workspace();
+    let runners = cargo_runners(&workspace);
+    let vcs = fake_vcs();
+    let fingerprinter = Fingerprinter::new(&vcs, &workspace, &runners, shared(&[]), &[]);
+    let local_dir = tempfile::TempDir::new().unwrap();
+    let local = LocalStore::new(local_dir.path().to_path_buf());
+
+    let mut plan = discovered_plan(&["core", "util"]);
+    let mut output = Vec::new();
+    let mut reporter = ConsoleReporter::new(&mut output, false);
+    apply_cache(
+        &mut plan,
+        &fingerprinter,
+        &local,
+        Some(&UnavailableStore),
+        &mut reporter,
+    )
+    .unwrap();
+
+    assert_eq!(plan.get_crates_to_test(), vec!["core", "util"]);
+    assert!(plan.shared_cached.is_empty());
+
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(
+        output.matches("ignoring the shared result store").count(),
+        1
+    );
+}
+
+#[test]
+fn test_record_passing() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let stores: Vec<Box<dyn ResultStore>> =
+        vec![Box::new(LocalStore::new(dir.path().to_path_buf()))];
+    let fingerprints = IndexMap::from([
+        ("core".to_string(), "core-print".to_string()),
+        ("util".to_string(), "util-print".to_string()),
+        ("app".to_string(), "app-print".to_string()),
+    ]);
+    let result = |name: &str, outcome: TestOutcome| TestResult {
+        crate_name: name.to_string(),
+        outcome,
+        output: String::new(),
+        duration: std::time::Duration::from_millis(10),
+        test_cases: Vec::new(),
+        attempts: 1,
+        sub_results: Vec::new(),
+        diagnostics: Vec::new(),
+    };
+    let mut results = TestResults::new();
+    results.add_result(result("core", TestOutcome::Passed));
+    results.add_result(result("util", TestOutcome::Flaky));
+    results.add_result(result("app", TestOutcome::Failed));
+
+    let mut reporter = ConsoleReporter::new(Vec::new(), false);
+    record_passing(&results, &fingerprints, &stores, &mut reporter);
+
+    assert!(stores[0].contains("core-print").unwrap());
+    assert!(!stores[0].contains("util-print").unwrap());
+    assert!(!stores[0].contains("app-print").unwrap());
+}
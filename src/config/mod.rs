@@ -0,0 +1,72 @@
+use cargo_metadata::Metadata;
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+use crate::{error::AppError, test_runner::TestRunnerType};
+
+#[cfg(test)]
+mod tests;
+
+/// The key under `[workspace.metadata]` that holds the configuration
+const METADATA_KEY: &str = "test-changed";
+
+/// Configuration read from `[workspace.metadata.test-changed]` in the workspace manifest
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    /// Glob patterns, relative to the workspace root, for changed files that should never
+    /// select a crate
+    pub ignore: Vec<String>,
+    /// Path of the quarantine file, relative to the workspace root
+    pub quarantine: Option<String>,
+    /// Arguments passed to clippy with `--mode clippy`, such as `["--", "-D", "warnings"]`
+    pub clippy_args: Vec<String>,
+    /// Command used by `-r custom`
+    pub custom_runner: Option<CustomRunnerConfig>,
+    /// Per-crate configuration, keyed by crate name
+    pub crates: IndexMap<String, CrateConfig>,
+}
+
+/// Configuration for a single crate
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct CrateConfig {
+    /// Glob patterns, relative to the workspace root, for files outside the crate's
+    /// directory whose changes should also select the crate
+    pub triggered_by: Vec<String>,
+    /// Runner used for the crate instead of the one given with `-r`
+    pub runner: Option<TestRunnerType>,
+    /// Arguments passed to the test runner for the crate, before those given on the command
+    /// line
+    pub args: Vec<String>,
+    /// Environment variables set when testing the crate
+    pub env: IndexMap<String, String>,
+}
+
+/// A test runner given as a command template
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct CustomRunnerConfig {
+    /// Command run for each crate, split into arguments like a POSIX shell would, so quotes
+    /// and backslashes group words, with `{crate}`, `{manifest_path}` and `{crate_dir}`
+    /// replaced in each argument
+    pub command: String,
+    /// Command that succeeds when the runner is installed, split like `command`
+    pub check: Option<String>,
+    /// Shown when the check command fails
+    pub install: Option<String>,
+}
+
+impl Config {
+    /// Load the configuration from the workspace metadata, falling back to the defaults
+    pub fn from_metadata(metadata: &Metadata) -> Result<Self, AppError> {
+        match metadata.workspace_metadata.get(METADATA_KEY) {
+            Some(value) => {
+                serde_json::from_value(value.clone()).map_err(|e| AppError::InvalidConfig {
+                    reason: e.to_string(),
+                })
+            }
+            None => Ok(Config::default()),
+        }
+    }
+}
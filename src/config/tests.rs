@@ -0,0 +1,134 @@
+use super::*;
+
+use cargo_metadata::MetadataBuilder;
+
+fn create_metadata(workspace_metadata: serde_json::Value) -> Metadata {
+    MetadataBuilder::default()
+        .packages(vec![])
+        .workspace_root("/workspace")
+        .target_directory("/workspace/target")
+        .workspace_members(vec![])
+        .workspace_default_members(cargo_metadata::WorkspaceDefaultMembers::default())
+        .workspace_metadata(workspace_metadata)
+        .resolve(None)
+        .version(1usize)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_config_defaults_without_metadata() {
+    let config = Config::from_metadata(&create_metadata(serde_json::Value::Null)).unwrap();
+    assert!(config.ignore.is_empty());
+    assert!(config.crates.is_empty());
+    assert!(config.quarantine.is_none());
+    assert!(config.custom_runner.is_none());
+}
+
+#[test]
+fn test_config_ignore() {
+    let metadata = create_metadata(serde_json::json!({
+        "test-changed": { "ignore": ["**/*.md"] }
+    }));
+
+    let config = Config::from_metadata(&metadata).unwrap();
+
+    assert_eq!(config.ignore, vec!["**/*.md".to_string()]);
+}
+
+#[test]
+fn test_config_quarantine() {
+    let metadata = create_metadata(serde_json::json!({
+        "test-changed": { "quarantine": "ci/quarantine.txt" }
+    }));
+
+    let config = Config::from_metadata(&metadata).unwrap();
+
+    assert_eq!(config.quarantine.as_deref(), Some("ci/quarantine.txt"));
+}
+
+#[test]
+fn test_config_clippy_args() {
+    let metadata = create_metadata(serde_json::json!({
+        "test-changed": { "clippy-args": ["--", "-D", "warnings"] }
+    }));
+
+    let config = Config::from_metadata(&metadata).unwrap();
+
+    assert_eq!(config.clippy_args, ["--", "-D", "warnings"]);
+}
+
+#[test]
+fn test_config_triggered_by() {
+    let metadata = create_metadata(serde_json::json!({
+        "test-changed": {
+            "crates": {
+                "api": { "triggered-by": ["proto/**", "schemas/*.json"] }
+            }
+        }
+    }));
+
+    let config = Config::from_metadata(&metadata).unwrap();
+
+    assert_eq!(
+        config.crates["api"].triggered_by,
+        vec!["proto/**".to_string(), "schemas/*.json".to_string()]
+    );
+}
+
+#[test]
+fn test_config_rejects_unknown_fields() {
+    let metadata = create_metadata(serde_json::json!({
+        "test-changed": { "crates": { "api": { "trigered-by": [] } } }
+    }));
+
+    assert!(matches!(
+        Config::from_metadata(&metadata),
+        Err(AppError::InvalidConfig { .. })
+    ));
+}
+
+#[test]
+fn test_config_custom_runner() {
+    let metadata = create_metadata(serde_json::json!({
+        "test-changed": {
+            "custom-runner": {
+                "command": "cargo miri test -p {crate}",
+                "check": "cargo miri --version",
+                "install": "rustup +nightly component add miri"
+            }
+        }
+    }));
+
+    let config = Config::from_metadata(&metadata).unwrap();
+    let runner = config.custom_runner.unwrap();
+
+    assert_eq!(runner.command, "cargo miri test -p {crate}");
+    assert_eq!(runner.check.as_deref(), Some("cargo miri --version"));
+    assert_eq!(
+        runner.install.as_deref(),
+        Some("rustup +nightly component add miri")
+    );
+}
+
+#[test]
+fn test_config_crate_runner_overrides() {
+    let metadata = create_metadata(serde_json::json!({
+        "test-changed": {
+            "crates": {
+                "db": {
+                    "runner": "nextest",
+                    "args": ["--profile", "serial"],
+                    "env": { "RUST_LOG": "debug" }
+                }
+            }
+        }
+    }));
+
+    let config = Config::from_metadata(&metadata).unwrap();
+    let db = &config.crates["db"];
+
+    assert_eq!(db.runner, Some(TestRunnerType::Nextest));
+    assert_eq!(db.args, vec!["--profile".to_string(), "serial".to_string()]);
+    assert_eq!(db.env["RUST_LOG"], "debug");
+}
@@ -10,6 +10,14 @@ pub enum AppError {
         runner_name: String,
         installation_tip: String,
     },
+    #[error("test runner '{runner_name}' {version} is too old for {feature}, which requires {required_version} or later")]
+    TestRunnerTooOld {
+        runner_name: String,
+        version: String,
+        required_version: String,
+        feature: String,
+        upgrade_tip: String,
+    },
     #[error("test failed")]
     TestsFailed { failed_crates: Vec<String> },
     #[error("failed to discover git repository: {reason}")]
@@ -24,6 +32,16 @@ pub enum AppError {
     UnknownCrate { crate_name: String },
     #[error("invalid arguments: {reason}")]
     InvalidArguments { reason: String },
+    #[error("invalid configuration: {reason}")]
+    InvalidConfig { reason: String },
+    #[error("failed to write '{path}': {reason}")]
+    WriteFailed { path: String, reason: String },
+    #[error("result store request to '{url}' failed: {reason}")]
+    RemoteStoreFailed { url: String, reason: String },
+    #[error("test run cancelled")]
+    RunCancelled,
+    #[error("failed to watch for changes: {reason}")]
+    WatchFailed { reason: String },
     #[error("{0}")]
     Other(anyhow::Error),
 }
@@ -33,6 +51,7 @@ impl AppError {
     pub fn exit_code(&self) -> i32 {
         match self {
             AppError::TestRunnerNotInstalled { .. } => 10,
+            AppError::TestRunnerTooOld { .. } => 140,
             AppError::TestsFailed { .. } => 20,
             AppError::GitDiscoveryFailed { .. } => 30,
             AppError::MetadataFailed { .. } => 40,
@@ -40,6 +59,11 @@ impl AppError {
             AppError::CommandFailed { .. } => 60,
             AppError::UnknownCrate { .. } => 70,
             AppError::InvalidArguments { .. } => 80,
+            AppError::InvalidConfig { .. } => 90,
+            AppError::WriteFailed { .. } => 100,
+            AppError::RemoteStoreFailed { .. } => 110,
+            AppError::RunCancelled => 120,
+            AppError::WatchFailed { .. } => 130,
             AppError::Other(_) => 1,
         }
     }
@@ -54,10 +78,27 @@ impl AppError {
                 reporter.error(&format!("test runner '{}' is not installed", runner_name));
                 reporter.tip(installation_tip);
             }
+            AppError::TestRunnerTooOld {
+                runner_name,
+                version,
+                required_version,
+                feature,
+                upgrade_tip,
+            } => {
+                reporter.error(&format!(
+                    "test runner '{}' {} is too old for {}, which requires {} or later",
+                    runner_name,
+                    version.bold().yellow(),
+                    feature,
+                    required_version.bold()
+                ));
+                reporter.tip(upgrade_tip);
+            }
             AppError::TestsFailed { failed_crates } => {
                 let rerun_command = format!("-c {}", failed_crates.join(","));
                 reporter.error(&format!(
-                    "test failed, to rerun pass `{}`",
+                    "test failed, to rerun pass `{}` or `{}`",
+                    "--rerun-failed".bold().yellow(),
                     rerun_command.bold().yellow(),
                 ));
             }
@@ -93,6 +134,32 @@ impl AppError {
             AppError::InvalidArguments { reason } => {
                 reporter.error(&format!("invalid arguments: {}", reason.bold().yellow()));
             }
+            AppError::InvalidConfig { reason } => {
+                reporter.error(&format!(
+                    "invalid configuration: {}",
+                    reason.bold().yellow()
+                ));
+            }
+            AppError::WriteFailed { path, reason } => {
+                reporter.error(&format!(
+                    "failed to write '{}': {}",
+                    path.bold().yellow(),
+                    reason.bold()
+                ));
+            }
+            AppError::RemoteStoreFailed { url, reason } => {
+                reporter.error(&format!(
+                    "result store request to '{}' failed: {}",
+                    url.bold().yellow(),
+                    reason.bold()
+                ));
+            }
+            AppError::RunCancelled => {
+                reporter.error("test run cancelled");
+            }
+            AppError::WatchFailed { reason } => {
+                reporter.error(&format!("failed to watch for changes: {}", reason.bold()));
+            }
             AppError::Other(err) => {
                 reporter.error(&format!("{}", err));
             }
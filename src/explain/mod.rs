@@ -0,0 +1,180 @@
+use std::path::PathBuf;
+
+use indexmap::IndexSet;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    error::AppError,
+    metadata::{self, ChangeOrigin, Workspace},
+    vcs::ChangedFile,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Why a crate is or is not part of the test plan
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Explanation {
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+    pub in_plan: bool,
+    /// Changed files that selected the crate directly
+    pub changes: Vec<CrateChange>,
+    /// Chain of crates from a changed crate to this one, if it is a dependent
+    pub dependency_path: Option<Vec<String>>,
+    /// Why the crate was left out of the plan
+    pub exclusion: Option<Exclusion>,
+}
+
+/// A changed file that selected a crate
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CrateChange {
+    pub path: PathBuf,
+    #[serde(flatten)]
+    pub origin: ChangeOrigin,
+}
+
+/// A changed file that was disregarded because of an ignore pattern
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct IgnoredChange {
+    pub path: PathBuf,
+    pub pattern: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum Exclusion {
+    /// None of the changed files affect the crate
+    NotAffected,
+    /// The crate depends on a changed crate, but dependents were not requested
+    DependentsNotIncluded,
+    /// Every changed file that would have selected the crate, directly or through one of its
+    /// dependencies, was ignored
+    Ignored {
+        files: Vec<IgnoredChange>,
+        /// Chain of crates from the crate changed by the ignored files to this one, if they
+        /// changed one of its dependencies
+        #[serde(skip_serializing_if = "Option::is_none")]
+        dependency_path: Option<Vec<String>>,
+    },
+}
+
+/// Explain why a crate is or is not part of the test plan
+pub fn explain_crate(
+    crate_name: &str,
+    workspace: &Workspace,
+    changed_files: &[ChangedFile],
+    ignored_files: &[(ChangedFile, String)],
+    with_dependents: bool,
+) -> Result<Explanation, AppError> {
+    metadata::verify_crates_exist(&workspace.metadata, &[crate_name.to_string()])?;
+
+    let relative = |path: &std::path::Path| {
+        path.strip_prefix(&workspace.root)
+            .unwrap_or(path)
+            .to_path_buf()
+    };
+
+    let changes = metadata::find_changes_for_crate(
+        crate_name,
+        changed_files,
+        &workspace.crates,
+        &workspace.file_dependencies,
+        &workspace.mappings,
+    )
+    .into_iter()
+    .map(|(path, origin)| CrateChange {
+        path: relative(path),
+        origin,
+    })
+    .collect::<Vec<_>>();
+
+    if !changes.is_empty() {
+        return Ok(Explanation {
+            crate_name: crate_name.to_string(),
+            in_plan: true,
+            changes,
+            dependency_path: None,
+            exclusion: None,
+        });
+    }
+
+    let changed_crates = metadata::find_changed_crates(
+        changed_files,
+        &workspace.crates,
+        &workspace.file_dependencies,
+        &workspace.mappings,
+    )?;
+    let changed_crate_names: IndexSet<&String> = changed_crates.keys().copied().collect();
+    let dependency_path =
+        metadata::find_dependency_path(crate_name, &changed_crate_names, &workspace.metadata);
+
+    if dependency_path.is_some() {
+        return Ok(Explanation {
+            crate_name: crate_name.to_string(),
+            in_plan: with_dependents,
+            changes,
+            dependency_path,
+            exclusion: (!with_dependents).then_some(Exclusion::DependentsNotIncluded),
+        });
+    }
+
+    let ignored_changes_for = |crate_name: &str| {
+        ignored_files
+            .iter()
+            .filter(|(change, _)| {
+                !metadata::find_changes_for_crate(
+                    crate_name,
+                    std::slice::from_ref(change),
+                    &workspace.crates,
+                    &workspace.file_dependencies,
+                    &workspace.mappings,
+                )
+                .is_empty()
+            })
+            .map(|(change, pattern)| IgnoredChange {
+                path: relative(&change.current_path),
+                pattern: pattern.clone(),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let ignored = ignored_changes_for(crate_name);
+    let exclusion = if !ignored.is_empty() {
+        Exclusion::Ignored {
+            files: ignored,
+            dependency_path: None,
+        }
+    } else {
+        // The crate may depend on a crate that only the ignored files changed
+        let ignored_changes = ignored_files
+            .iter()
+            .map(|(change, _)| change.clone())
+            .collect::<Vec<_>>();
+        let ignored_crates = metadata::find_changed_crates(
+            &ignored_changes,
+            &workspace.crates,
+            &workspace.file_dependencies,
+            &workspace.mappings,
+        )?;
+        let ignored_crate_names: IndexSet<&String> = ignored_crates.keys().copied().collect();
+
+        match metadata::find_dependency_path(crate_name, &ignored_crate_names, &workspace.metadata)
+        {
+            Some(path) => Exclusion::Ignored {
+                files: ignored_changes_for(&path[0]),
+                dependency_path: Some(path),
+            },
+            None => Exclusion::NotAffected,
+        }
+    };
+
+    Ok(Explanation {
+        crate_name: crate_name.to_string(),
+        in_plan: false,
+        changes,
+        dependency_path: None,
+        exclusion: Some(exclusion),
+    })
+}
@@ -0,0 +1,206 @@
+use super::*;
+
+use std::path::Path;
+
+use indexmap::IndexMap;
+
+use cargo_metadata::semver::Version;
+use cargo_metadata::{DependencyBuilder, Metadata, MetadataBuilder, PackageBuilder, PackageId};
+
+use crate::config::Config;
+use crate::metadata::{CrateMappings, FileDependencies, IgnorePatterns};
+use crate::quarantine::Quarantine;
+use crate::vcs::{ChangeType, FileType};
+
+fn create_package(name: &str, dependencies: &[&str]) -> cargo_metadata::Package {
+    let dependencies = dependencies
+        .iter()
+        .map(|dep| {
+            DependencyBuilder::default()
+                .name(*dep)
+                .kind(cargo_metadata::DependencyKind::Normal)
+                .req(cargo_metadata::semver::VersionReq::STAR)
+                .optional(false)
+                .uses_default_features(true)
+                .source(None)
+                .target(None)
+                .features(vec![])
+                .rename(None)
+                .registry(None)
+                .path(None)
+                .build()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    PackageBuilder::new(
+        name,
+        Version::new(1, 0, 0),
+        PackageId {
+            repr: name.to_string(),
+        },
+        format!("/workspace/{}/Cargo.toml", name),
+    )
+    .dependencies(dependencies)
+    .build()
+    .unwrap()
+}
+
+fn create_workspace(config: &Config) -> Workspace {
+    let metadata: Metadata = MetadataBuilder::default()
+        .packages(vec![
+            create_package("core", &[]),
+            create_package("api", &["core"]),
+            create_package("docs", &[]),
+        ])
+        .workspace_root("/workspace")
+        .target_directory("/workspace/target")
+        .workspace_members(vec![])
+        .workspace_default_members(cargo_metadata::WorkspaceDefaultMembers::default())
+        .workspace_metadata(serde_json::Value::Null)
+        .resolve(None)
+        .version(1usize)
+        .build()
+        .unwrap();
+
+    let root = Path::new("/workspace");
+
+    Workspace {
+        root: root.to_path_buf(),
+        crates: metadata::get_workspace_crates(&metadata).unwrap(),
+        mappings: CrateMappings::from_config(config, &metadata, root).unwrap(),
+        ignore_patterns: IgnorePatterns::from_config(config, root).unwrap(),
+        metadata,
+        dep_info: Vec::new(),
+        file_dependencies: FileDependencies::default(),
+        quarantine: Quarantine::default(),
+        custom_runner: None,
+        runner_overrides: IndexMap::new(),
+        clippy_args: Vec::new(),
+    }
+}
+
+fn changed_file(path: &str) -> ChangedFile {
+    ChangedFile {
+        current_path: PathBuf::from(path),
+        old_path: None,
+        file_type: FileType::File,
+        change_type: ChangeType::Modified,
+    }
+}
+
+#[test]
+fn test_explain_modified_crate() {
+    let workspace = create_workspace(&Config::default());
+    let changed_files = vec![changed_file("/workspace/core/src/lib.rs")];
+
+    let explanation = explain_crate("core", &workspace, &changed_files, &[], false).unwrap();
+
+    assert!(explanation.in_plan);
+    assert_eq!(explanation.changes.len(), 1);
+    assert_eq!(
+        explanation.changes[0].path,
+        PathBuf::from("core/src/lib.rs")
+    );
+    assert_eq!(explanation.changes[0].origin, ChangeOrigin::Directory);
+    assert!(explanation.exclusion.is_none());
+}
+
+#[test]
+fn test_explain_dependent_crate() {
+    let workspace = create_workspace(&Config::default());
+    let changed_files = vec![changed_file("/workspace/core/src/lib.rs")];
+
+    let excluded = explain_crate("api", &workspace, &changed_files, &[], false).unwrap();
+    assert!(!excluded.in_plan);
+    assert_eq!(
+        excluded.dependency_path,
+        Some(vec!["core".to_string(), "api".to_string()])
+    );
+    assert!(matches!(
+        excluded.exclusion,
+        Some(Exclusion::DependentsNotIncluded)
+    ));
+
+    let included = explain_crate("api", &workspace, &changed_files, &[], true).unwrap();
+    assert!(included.in_plan);
+    assert!(included.exclusion.is_none());
+}
+
+#[test]
+fn test_explain_unaffected_and_ignored_crates() {
+    let config = Config {
+        ignore: vec!["**/*.md".to_string()],
+        ..Default::default()
+    };
+    let workspace = create_workspace(&config);
+    let (changed_files, ignored_files) = workspace.ignore_patterns.partition(vec![
+        changed_file("/workspace/core/src/lib.rs"),
+        changed_file("/workspace/docs/README.md"),
+    ]);
+
+    let ignored = explain_crate("docs", &workspace, &changed_files, &ignored_files, true).unwrap();
+    assert!(!ignored.in_plan);
+    match ignored.exclusion {
+        Some(Exclusion::Ignored {
+            files,
+            dependency_path: None,
+        }) => {
+            assert_eq!(files.len(), 1);
+            assert_eq!(files[0].path, PathBuf::from("docs/README.md"));
+            assert_eq!(files[0].pattern, "**/*.md");
+        }
+        other => panic!("Expected Ignored exclusion, got: {:?}", other),
+    }
+
+    let (changed_files, ignored_files) = workspace
+        .ignore_patterns
+        .partition(vec![changed_file("/workspace/core/src/lib.rs")]);
+    let unaffected =
+        explain_crate("docs", &workspace, &changed_files, &ignored_files, true).unwrap();
+    assert!(!unaffected.in_plan);
+    assert!(matches!(unaffected.exclusion, Some(Exclusion::NotAffected)));
+}
+
+#[test]
+fn test_explain_dependent_of_ignored_changes() {
+    let config = Config {
+        ignore: vec!["**/*.md".to_string()],
+        ..Default::default()
+    };
+    let workspace = create_workspace(&config);
+    let (changed_files, ignored_files) = workspace
+        .ignore_patterns
+        .partition(vec![changed_file("/workspace/core/README.md")]);
+
+    let explanation =
+        explain_crate("api", &workspace, &changed_files, &ignored_files, true).unwrap();
+
+    assert!(!explanation.in_plan);
+    assert!(explanation.dependency_path.is_none());
+    match explanation.exclusion {
+        Some(Exclusion::Ignored {
+            files,
+            dependency_path,
+        }) => {
+            assert_eq!(files.len(), 1);
+            assert_eq!(files[0].path, PathBuf::from("core/README.md"));
+            assert_eq!(files[0].pattern, "**/*.md");
+            assert_eq!(
+                dependency_path,
+                Some(vec!["core".to_string(), "api".to_string()])
+            );
+        }
+        other => panic!("Expected Ignored exclusion, got: {:?}", other),
+    }
+}
+
+#[test]
+fn test_explain_unknown_crate() {
+    let workspace = create_workspace(&Config::default());
+
+    assert!(matches!(
+        explain_crate("missing", &workspace, &[], &[], false),
+        Err(AppError::UnknownCrate { .. })
+    ));
+}
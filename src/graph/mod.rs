@@ -0,0 +1,234 @@
+use std::fmt::Write;
+
+use cargo_metadata::{DependencyKind, Metadata};
+use clap::ValueEnum;
+use indexmap::{IndexMap, IndexSet};
+use serde::Serialize;
+
+use crate::{
+    error::AppError,
+    testing::plan::{DiscoveredTestCrate, DiscoveryType},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Output format for the impact graph
+#[derive(ValueEnum, Debug, Clone, Default)]
+pub enum GraphFormat {
+    /// Graphviz DOT (default)
+    #[default]
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+    /// JSON adjacency list
+    Json,
+}
+
+/// The kind of a dependency edge between two crates
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    Normal,
+    Dev,
+    Build,
+}
+
+/// A crate in the affected subgraph of the workspace
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphNode {
+    pub name: String,
+    pub discovery_type: DiscoveryType,
+}
+
+/// A dependency of one affected crate on another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphEdge {
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+    pub kinds: Vec<EdgeKind>,
+}
+
+/// The changed crates and their dependents, with the dependency edges between them
+#[derive(Debug, Serialize)]
+pub struct ImpactGraph {
+    pub nodes: Vec<GraphNode>,
+    /// Dependencies of each crate, keyed by the dependent crate
+    pub adjacency: IndexMap<String, Vec<GraphEdge>>,
+}
+
+/// Build the affected subgraph from discovered crates and the resolved dependency graph
+pub fn build_impact_graph(
+    crates: &IndexSet<DiscoveredTestCrate>,
+    resolved: &Metadata,
+) -> Result<ImpactGraph, AppError> {
+    let resolve = resolved
+        .resolve
+        .as_ref()
+        .ok_or_else(|| AppError::MetadataFailed {
+            reason: "dependency resolution is missing from cargo metadata".to_string(),
+        })?;
+
+    // A crate can be both changed and a dependent, in which case it is reported as changed
+    let mut nodes: IndexMap<&String, &DiscoveryType> = IndexMap::new();
+    for test_crate in crates {
+        nodes
+            .entry(&test_crate.name)
+            .or_insert(&test_crate.discovery_type);
+    }
+
+    let package_name = |id: &cargo_metadata::PackageId| {
+        resolved
+            .packages
+            .iter()
+            .find(|p| p.id == *id)
+            .map(|p| &p.name)
+    };
+
+    let mut adjacency = IndexMap::new();
+    for node in &resolve.nodes {
+        let Some(name) = package_name(&node.id).filter(|name| nodes.contains_key(name)) else {
+            continue;
+        };
+
+        let edges = node
+            .deps
+            .iter()
+            .filter_map(|dep| {
+                let dep_name = package_name(&dep.pkg).filter(|n| nodes.contains_key(n))?;
+                let mut kinds = Vec::new();
+                for info in &dep.dep_kinds {
+                    let kind = match info.kind {
+                        DependencyKind::Development => EdgeKind::Dev,
+                        DependencyKind::Build => EdgeKind::Build,
+                        _ => EdgeKind::Normal,
+                    };
+                    if !kinds.contains(&kind) {
+                        kinds.push(kind);
+                    }
+                }
+                Some(GraphEdge {
+                    crate_name: dep_name.clone(),
+                    kinds,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        adjacency.insert(name.clone(), edges);
+    }
+
+    Ok(ImpactGraph {
+        nodes: nodes
+            .into_iter()
+            .map(|(name, discovery_type)| GraphNode {
+                name: name.clone(),
+                discovery_type: discovery_type.clone(),
+            })
+            .collect(),
+        adjacency,
+    })
+}
+
+impl ImpactGraph {
+    /// Render the graph in the given format
+    pub fn render(&self, format: &GraphFormat) -> Result<String, AppError> {
+        match format {
+            GraphFormat::Dot => Ok(self.to_dot()),
+            GraphFormat::Mermaid => Ok(self.to_mermaid()),
+            GraphFormat::Json => {
+                serde_json::to_string_pretty(self).map_err(|e| AppError::Other(anyhow::anyhow!(e)))
+            }
+        }
+    }
+
+    fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph impact {\n    rankdir=LR;\n");
+
+        for node in &self.nodes {
+            let color = match node.discovery_type {
+                DiscoveryType::Modified => "gold",
+                DiscoveryType::Mapped { .. } => "lightblue",
+                DiscoveryType::Dependent => "lightcoral",
+            };
+            let _ = writeln!(
+                dot,
+                "    \"{}\" [style=filled, fillcolor={}];",
+                node.name, color
+            );
+        }
+
+        for (name, edges) in &self.adjacency {
+            for edge in edges {
+                let _ = writeln!(
+                    dot,
+                    "    \"{}\" -> \"{}\" [label=\"{}\"];",
+                    name,
+                    edge.crate_name,
+                    edge.label()
+                );
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn to_mermaid(&self) -> String {
+        let mut mermaid = String::from("graph LR\n");
+        // Crate names can clash once normalized (`a-b` and `a_b`) or be mermaid keywords (`end`),
+        // so nodes are identified by position and labelled with the name
+        let ids: IndexMap<&str, String> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.name.as_str(), format!("n{}", index)))
+            .collect();
+        let id = |name: &str| ids[name].as_str();
+
+        for node in &self.nodes {
+            let class = match node.discovery_type {
+                DiscoveryType::Modified => "changed",
+                DiscoveryType::Mapped { .. } => "mapped",
+                DiscoveryType::Dependent => "dependent",
+            };
+            let _ = writeln!(
+                mermaid,
+                "    {}[\"{}\"]:::{}",
+                id(&node.name),
+                node.name,
+                class
+            );
+        }
+
+        for (name, edges) in &self.adjacency {
+            for edge in edges {
+                let _ = writeln!(
+                    mermaid,
+                    "    {} -->|{}| {}",
+                    id(name),
+                    edge.label(),
+                    id(&edge.crate_name)
+                );
+            }
+        }
+
+        mermaid.push_str("    classDef changed fill:#ffd700\n");
+        mermaid.push_str("    classDef mapped fill:#add8e6\n");
+        mermaid.push_str("    classDef dependent fill:#f08080\n");
+        mermaid
+    }
+}
+
+impl GraphEdge {
+    fn label(&self) -> String {
+        self.kinds
+            .iter()
+            .map(|kind| match kind {
+                EdgeKind::Normal => "normal",
+                EdgeKind::Dev => "dev",
+                EdgeKind::Build => "build",
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
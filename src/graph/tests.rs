@@ -0,0 +1,206 @@
+use super::*;
+
+use cargo_metadata::semver::Version;
+use cargo_metadata::{
+    DepKindInfoBuilder, MetadataBuilder, NodeBuilder, NodeDepBuilder, PackageBuilder, PackageId,
+    ResolveBuilder,
+};
+
+fn package_id(name: &str) -> PackageId {
+    PackageId {
+        repr: name.to_string(),
+    }
+}
+
+fn create_node(name: &str, deps: &[(&str, &[DependencyKind])]) -> cargo_metadata::Node {
+    let deps = deps
+        .iter()
+        .map(|(dep, kinds)| {
+            NodeDepBuilder::default()
+                .name(dep.replace('-', "_"))
+                .pkg(package_id(dep))
+                .dep_kinds(
+                    kinds
+                        .iter()
+                        .map(|kind| {
+                            DepKindInfoBuilder::default()
+                                .kind(*kind)
+                                .target(None)
+                                .build()
+                                .unwrap()
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .build()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    NodeBuilder::default()
+        .id(package_id(name))
+        .deps(deps)
+        .dependencies(vec![])
+        .features(vec![])
+        .build()
+        .unwrap()
+}
+
+fn create_resolved_metadata() -> Metadata {
+    let packages = ["core", "api", "cli", "docs"]
+        .into_iter()
+        .map(|name| {
+            PackageBuilder::new(
+                name,
+                Version::new(1, 0, 0),
+                package_id(name),
+                format!("/workspace/{}/Cargo.toml", name),
+            )
+            .build()
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let resolve = ResolveBuilder::default()
+        .nodes(vec![
+            create_node("core", &[]),
+            create_node(
+                "api",
+                &[(
+                    "core",
+                    &[DependencyKind::Normal, DependencyKind::Development],
+                )],
+            ),
+            create_node(
+                "cli",
+                &[
+                    ("api", &[DependencyKind::Normal]),
+                    ("docs", &[DependencyKind::Build]),
+                ],
+            ),
+            create_node("docs", &[]),
+        ])
+        .root(None)
+        .build()
+        .unwrap();
+
+    MetadataBuilder::default()
+        .packages(packages)
+        .workspace_root("/workspace")
+        .target_directory("/workspace/target")
+        .workspace_members(vec![])
+        .workspace_default_members(cargo_metadata::WorkspaceDefaultMembers::default())
+        .workspace_metadata(serde_json::Value::Null)
+        .resolve(Some(resolve))
+        .version(1usize)
+        .build()
+        .unwrap()
+}
+
+fn create_crates() -> IndexSet<DiscoveredTestCrate> {
+    IndexSet::from([
+        DiscoveredTestCrate {
+            name: "core".to_string(),
+            discovery_type: DiscoveryType::Modified,
+        },
+        DiscoveredTestCrate {
+            name: "api".to_string(),
+            discovery_type: DiscoveryType::Dependent,
+        },
+        DiscoveredTestCrate {
+            name: "core".to_string(),
+            discovery_type: DiscoveryType::Dependent,
+        },
+    ])
+}
+
+#[test]
+fn test_build_impact_graph() {
+    let graph = build_impact_graph(&create_crates(), &create_resolved_metadata()).unwrap();
+
+    assert_eq!(
+        graph.nodes,
+        vec![
+            GraphNode {
+                name: "core".to_string(),
+                discovery_type: DiscoveryType::Modified,
+            },
+            GraphNode {
+                name: "api".to_string(),
+                discovery_type: DiscoveryType::Dependent,
+            },
+        ]
+    );
+
+    // Crates outside of the affected set are left out, along with their edges
+    assert_eq!(graph.adjacency.len(), 2);
+    assert!(graph.adjacency["core"].is_empty());
+    assert_eq!(
+        graph.adjacency["api"],
+        vec![GraphEdge {
+            crate_name: "core".to_string(),
+            kinds: vec![EdgeKind::Normal, EdgeKind::Dev],
+        }]
+    );
+}
+
+#[test]
+fn test_build_impact_graph_requires_resolve() {
+    let mut metadata = create_resolved_metadata();
+    metadata.resolve = None;
+
+    assert!(matches!(
+        build_impact_graph(&create_crates(), &metadata),
+        Err(AppError::MetadataFailed { .. })
+    ));
+}
+
+#[test]
+fn test_render_formats() {
+    let graph = build_impact_graph(&create_crates(), &create_resolved_metadata()).unwrap();
+
+    let dot = graph.render(&GraphFormat::Dot).unwrap();
+    assert!(dot.starts_with("digraph impact {"));
+    assert!(dot.contains("\"core\" [style=filled, fillcolor=gold];"));
+    assert!(dot.contains("\"api\" -> \"core\" [label=\"normal, dev\"];"));
+
+    let mermaid = graph.render(&GraphFormat::Mermaid).unwrap();
+    assert!(mermaid.starts_with("graph LR\n"));
+    assert!(mermaid.contains("n1[\"api\"]:::dependent"));
+    assert!(mermaid.contains("n1 -->|normal, dev| n0"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&graph.render(&GraphFormat::Json).unwrap()).unwrap();
+    assert_eq!(
+        json["adjacency"]["api"],
+        serde_json::json!([{ "crate": "core", "kinds": ["normal", "dev"] }])
+    );
+    assert_eq!(
+        json["nodes"][0],
+        serde_json::json!({ "name": "core", "discovery_type": "modified" })
+    );
+}
+
+#[test]
+fn test_mermaid_ids_do_not_clash() {
+    let node = |name: &str| GraphNode {
+        name: name.to_string(),
+        discovery_type: DiscoveryType::Modified,
+    };
+    let graph = ImpactGraph {
+        nodes: vec![node("a-b"), node("a_b"), node("end")],
+        adjacency: IndexMap::from([(
+            "end".to_string(),
+            vec![GraphEdge {
+                crate_name: "a-b".to_string(),
+                kinds: vec![EdgeKind::Normal],
+            }],
+        )]),
+    };
+
+    let mermaid = graph.render(&GraphFormat::Mermaid).unwrap();
+
+    assert!(mermaid.contains("    n0[\"a-b\"]:::changed\n"));
+    assert!(mermaid.contains("    n1[\"a_b\"]:::changed\n"));
+    assert!(mermaid.contains("    n2[\"end\"]:::changed\n"));
+    assert!(mermaid.contains("    n2 -->|normal| n0\n"));
+}
@@ -0,0 +1,7 @@
+/// 64-bit FNV-1a, which unlike the standard library's hasher is stable across releases and
+/// platforms, for values that are compared between runs
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
@@ -0,0 +1,239 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::AppError,
+    quarantine::QuarantineOutcome,
+    test_runner::Runners,
+    testing::{
+        plan::{RunMode, TestPlan},
+        result::{TestOutcome, TestResults},
+    },
+    vcs::ChangedFile,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Number of runs kept in the history file, older runs are dropped
+pub const HISTORY_LIMIT: usize = 50;
+
+/// The outcome of a single crate in a recorded run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CrateOutcome {
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+    pub success: bool,
+    /// Whether the crate only passed when retried
+    #[serde(default)]
+    pub flaky: bool,
+    pub duration_ms: u64,
+    /// Quarantined entries covered by the crate's tests and whether they passed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub quarantined: Vec<QuarantineOutcome>,
+}
+
+/// Why a crate selected by the plan of a recorded run was not tested
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// The crate passed before with the same fingerprint
+    Cached,
+    /// The run stopped at an earlier failure before reaching the crate
+    FailFast,
+}
+
+/// A crate selected by the plan of a recorded run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PlannedCrate {
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+    /// Name of the runner the crate was tested with
+    pub runner: String,
+    /// Why the crate was not tested, if it was not
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<SkipReason>,
+}
+
+/// A test run, as persisted in the history file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RunRecord {
+    /// Milliseconds since the Unix epoch
+    pub started_at: u64,
+    /// Commit checked out when the run started
+    pub head: Option<String>,
+    pub changed_files: Vec<PathBuf>,
+    /// What was done with each crate
+    #[serde(default)]
+    pub mode: RunMode,
+    /// Crates selected by the plan, including those that were not tested
+    #[serde(default)]
+    pub planned: Vec<PlannedCrate>,
+    /// Outcomes of the crates that were tested, in the order they were tested
+    pub crates: Vec<CrateOutcome>,
+    pub duration_ms: u64,
+}
+
+impl RunRecord {
+    /// Record the plan and results of a finished run
+    pub fn new(
+        started_at: SystemTime,
+        head: Option<String>,
+        changed_files: &[ChangedFile],
+        test_plan: &TestPlan,
+        runners: &Runners,
+        results: &TestResults,
+    ) -> Self {
+        RunRecord {
+            started_at: started_at
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_millis() as u64)
+                .unwrap_or_default(),
+            head,
+            changed_files: changed_files
+                .iter()
+                .map(|file| file.current_path.clone())
+                .collect(),
+            mode: test_plan.mode,
+            planned: test_plan
+                .get_selected_crates()
+                .into_iter()
+                .map(|crate_name| PlannedCrate {
+                    crate_name: crate_name.clone(),
+                    runner: runners.runner(crate_name).name().to_string(),
+                    skipped: if test_plan.cached.contains(crate_name) {
+                        Some(SkipReason::Cached)
+                    } else if results
+                        .results
+                        .iter()
+                        .any(|result| &result.crate_name == crate_name)
+                    {
+                        None
+                    } else {
+                        Some(SkipReason::FailFast)
+                    },
+                })
+                .collect(),
+            crates: results
+                .results
+                .iter()
+                .map(|result| CrateOutcome {
+                    crate_name: result.crate_name.clone(),
+                    success: result.success(),
+                    flaky: result.outcome == TestOutcome::Flaky,
+                    duration_ms: result.duration.as_millis() as u64,
+                    quarantined: test_plan.quarantine.outcomes(result),
+                })
+                .collect(),
+            duration_ms: results.duration.as_millis() as u64,
+        }
+    }
+
+    pub fn success(&self) -> bool {
+        self.crates.iter().all(|outcome| outcome.success)
+    }
+
+    pub fn failed_crates(&self) -> Vec<String> {
+        self.crates
+            .iter()
+            .filter(|outcome| !outcome.success)
+            .map(|outcome| outcome.crate_name.clone())
+            .collect()
+    }
+
+    /// Crates selected by the plan that were not tested, with the reason
+    pub fn skipped_crates(&self) -> Vec<(&str, SkipReason)> {
+        self.planned
+            .iter()
+            .filter_map(|planned| Some((planned.crate_name.as_str(), planned.skipped?)))
+            .collect()
+    }
+
+    /// Names of the runners used by the run, in the order of the plan
+    pub fn runners(&self) -> Vec<&str> {
+        let mut runners: Vec<&str> = Vec::new();
+        for planned in &self.planned {
+            if !runners.contains(&planned.runner.as_str()) {
+                runners.push(&planned.runner);
+            }
+        }
+        runners
+    }
+}
+
+impl SkipReason {
+    pub fn description(&self) -> &'static str {
+        match self {
+            SkipReason::Cached => "cached",
+            SkipReason::FailFast => "fail-fast",
+        }
+    }
+}
+
+/// Recent runs, oldest first
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    runs: Vec<RunRecord>,
+}
+
+impl History {
+    /// Location of the history file within the cargo target directory
+    pub fn path(target_dir: &Path) -> PathBuf {
+        target_dir.join("test-changed").join("history.json")
+    }
+
+    /// Load the history file, starting afresh if it is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), AppError> {
+        let write_failed = |reason: String| AppError::WriteFailed {
+            path: path.display().to_string(),
+            reason,
+        };
+
+        let json = serde_json::to_string_pretty(self).map_err(|e| write_failed(e.to_string()))?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e: io::Error| write_failed(e.to_string()))?;
+        }
+
+        fs::write(path, json).map_err(|e| write_failed(e.to_string()))
+    }
+
+    /// Add a run to the history file, starting it if needed
+    pub fn append(path: &Path, run: RunRecord) -> Result<(), AppError> {
+        let mut history = History::load(path);
+        history.record(run);
+        history.save(path)
+    }
+
+    /// Add a run, dropping the oldest runs beyond the limit
+    pub fn record(&mut self, run: RunRecord) {
+        self.runs.push(run);
+
+        if self.runs.len() > HISTORY_LIMIT {
+            let excess = self.runs.len() - HISTORY_LIMIT;
+            self.runs.drain(..excess);
+        }
+    }
+
+    pub fn last_run(&self) -> Option<&RunRecord> {
+        self.runs.last()
+    }
+
+    /// The most recent runs, newest first
+    pub fn recent(&self, limit: usize) -> Vec<RunRecord> {
+        self.runs.iter().rev().take(limit).cloned().collect()
+    }
+}
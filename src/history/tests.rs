@@ -0,0 +1,237 @@
+use std::path::PathBuf;
+
+use cargo_metadata::MetadataBuilder;
+use indexmap::{IndexMap, IndexSet};
+
+use super::*;
+use crate::metadata::{self, CrateMappings, FileDependencies, IgnorePatterns, Workspace};
+use crate::quarantine::Quarantine;
+use crate::test_runner::TestRunnerType;
+use crate::testing::{
+    plan::{ManualTestCrate, TestCrates},
+    result::TestResult,
+    schedule::Schedule,
+};
+use crate::vcs::{ChangeType, FileType};
+
+fn run(started_at: u64, outcomes: &[(&str, bool)]) -> RunRecord {
+    RunRecord {
+        started_at,
+        head: Some("0123456789abcdef0123456789abcdef01234567".to_string()),
+        changed_files: vec![PathBuf::from("crates/a/src/lib.rs")],
+        mode: RunMode::Test,
+        planned: Vec::new(),
+        crates: outcomes
+            .iter()
+            .map(|(name, success)| CrateOutcome {
+                crate_name: name.to_string(),
+                success: *success,
+                flaky: false,
+                duration_ms: 100,
+                quarantined: Vec::new(),
+            })
+            .collect(),
+        duration_ms: 100 * outcomes.len() as u64,
+    }
+}
+
+#[test]
+fn test_failed_crates() {
+    let record = run(1, &[("a", true), ("b", false), ("c", false)]);
+
+    assert!(!record.success());
+    assert_eq!(record.failed_crates(), vec!["b", "c"]);
+    assert!(run(1, &[("a", true)]).success());
+}
+
+#[test]
+fn test_save_and_load() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = History::path(dir.path());
+
+    let mut history = History::default();
+    history.record(run(1, &[("a", true)]));
+    history.record(run(2, &[("a", false)]));
+    history.save(&path).unwrap();
+
+    let loaded = History::load(&path);
+
+    assert_eq!(loaded.last_run(), Some(&run(2, &[("a", false)])));
+    assert_eq!(
+        loaded
+            .recent(10)
+            .iter()
+            .map(|r| r.started_at)
+            .collect::<Vec<_>>(),
+        vec![2, 1]
+    );
+}
+
+#[test]
+fn test_load_missing_or_invalid_file() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = History::path(dir.path());
+
+    assert_eq!(History::load(&path).last_run(), None);
+
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, "not json").unwrap();
+
+    assert_eq!(History::load(&path).last_run(), None);
+}
+
+#[test]
+fn test_record_drops_oldest_runs() {
+    let mut history = History::default();
+    for started_at in 0..(HISTORY_LIMIT as u64 + 5) {
+        history.record(run(started_at, &[("a", true)]));
+    }
+
+    let recent = history.recent(usize::MAX);
+
+    assert_eq!(recent.len(), HISTORY_LIMIT);
+    assert_eq!(recent.last().unwrap().started_at, 5);
+}
+
+fn workspace() -> Workspace {
+    let metadata = MetadataBuilder::default()
+        .packages(vec![])
+        .workspace_root("/workspace")
+        .target_directory("/workspace/target")
+        .workspace_members(vec![])
+        .workspace_default_members(cargo_metadata::WorkspaceDefaultMembers::default())
+        .workspace_metadata(serde_json::Value::Null)
+        .resolve(None)
+        .version(1usize)
+        .build()
+        .unwrap();
+
+    Workspace {
+        root: PathBuf::from("/workspace"),
+        crates: metadata::get_workspace_crates(&metadata).unwrap(),
+        metadata,
+        dep_info: Vec::new(),
+        file_dependencies: FileDependencies::default(),
+        mappings: CrateMappings::default(),
+        ignore_patterns: IgnorePatterns::default(),
+        quarantine: Quarantine::default(),
+        custom_runner: None,
+        runner_overrides: IndexMap::new(),
+        clippy_args: Vec::new(),
+    }
+}
+
+fn plan(crates: &[&str], cached: &[&str]) -> TestPlan {
+    TestPlan {
+        workspace_root: PathBuf::from("/workspace"),
+        crates: TestCrates::Manual(
+            crates
+                .iter()
+                .map(|name| ManualTestCrate {
+                    name: name.to_string(),
+                })
+                .collect(),
+        ),
+        mode: RunMode::Test,
+        with_dependents: false,
+        fail_fast: true,
+        retries: 0,
+        verbose: false,
+        test_runner_args: Vec::new(),
+        test_filters: IndexMap::new(),
+        cached: cached.iter().map(|name| name.to_string()).collect(),
+        shared_cached: IndexSet::new(),
+        fingerprints: IndexMap::new(),
+        quarantine: Quarantine::default(),
+        schedule: Schedule::default(),
+        crate_stats: IndexMap::new(),
+        doc_test_crates: IndexSet::new(),
+        shard: None,
+    }
+}
+
+fn result(name: &str, outcome: TestOutcome) -> TestResult {
+    TestResult {
+        crate_name: name.to_string(),
+        outcome,
+        output: String::new(),
+        duration: std::time::Duration::from_millis(100),
+        test_cases: Vec::new(),
+        attempts: 1,
+        sub_results: Vec::new(),
+        diagnostics: Vec::new(),
+    }
+}
+
+#[test]
+fn test_record_results() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = History::path(dir.path());
+    let mut results = TestResults::new();
+    results.add_result(result("b", TestOutcome::Failed));
+    results.add_result(result("a", TestOutcome::Passed));
+    results.duration = std::time::Duration::from_millis(200);
+    let changed_files = [ChangedFile {
+        old_path: None,
+        current_path: PathBuf::from("crates/a/src/lib.rs"),
+        file_type: FileType::File,
+        change_type: ChangeType::Modified,
+    }];
+    let runners = Runners::new(&TestRunnerType::Cargo, &workspace()).unwrap();
+
+    let record = RunRecord::new(
+        UNIX_EPOCH + std::time::Duration::from_millis(1),
+        Some("0123456789abcdef0123456789abcdef01234567".to_string()),
+        &changed_files,
+        &plan(&["a", "b"], &[]),
+        &runners,
+        &results,
+    );
+    History::append(&path, record).unwrap();
+
+    // Crates are recorded in the order they were tested
+    let mut expected = run(1, &[("b", false), ("a", true)]);
+    expected.planned = ["a", "b"]
+        .into_iter()
+        .map(|name| PlannedCrate {
+            crate_name: name.to_string(),
+            runner: "cargo".to_string(),
+            skipped: None,
+        })
+        .collect();
+    assert_eq!(History::load(&path).last_run(), Some(&expected));
+}
+
+#[test]
+fn test_record_skipped_crates() {
+    let mut results = TestResults::new();
+    results.add_result(result("b", TestOutcome::Failed));
+    let runners = Runners::new(&TestRunnerType::Nextest, &workspace()).unwrap();
+
+    let record = RunRecord::new(
+        UNIX_EPOCH,
+        None,
+        &[],
+        &plan(&["a", "b", "c"], &["a"]),
+        &runners,
+        &results,
+    );
+
+    assert_eq!(record.mode, RunMode::Test);
+    assert_eq!(record.runners(), vec!["nextest"]);
+    assert_eq!(
+        record.skipped_crates(),
+        vec![("a", SkipReason::Cached), ("c", SkipReason::FailFast)]
+    );
+    assert_eq!(record.failed_crates(), vec!["b"]);
+}
+
+#[test]
+fn test_load_run_without_plan() {
+    let json = r#"{"started_at":1,"head":null,"changed_files":[],"crates":[],"duration_ms":0}"#;
+
+    let record: RunRecord = serde_json::from_str(json).unwrap();
+
+    assert_eq!(record.mode, RunMode::Test);
+    assert!(record.planned.is_empty());
+}
@@ -1,25 +1,49 @@
 use std::{
-    io::{stderr, stdout},
-    path::Path,
+    io::{stderr, stdout, IsTerminal},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
 };
 
 use anyhow::Result;
-use indexmap::IndexSet;
-use test_runner::TestRunnerType;
+use indexmap::{IndexMap, IndexSet};
+use test_runner::{Runners, TestRunnerType};
 
 use clap::{Parser, ValueEnum};
 use error::AppError;
 
+mod cache;
+mod config;
 mod error;
+mod explain;
+mod graph;
+mod hash;
+mod history;
 mod metadata;
+mod quarantine;
 mod reporting;
 mod test_runner;
 mod testing;
 mod vcs;
-
-use reporting::Reporter;
-use testing::plan::{DiscoveredTestCrate, DiscoveryType, ManualTestCrate, TestCrates, TestPlan};
-use vcs::VcsType;
+mod watch;
+
+use cache::{Fingerprinter, HttpStore, LocalStore, ResultStore, SharedInputs};
+use graph::GraphFormat;
+use history::{History, RunRecord, HISTORY_LIMIT};
+use metadata::Workspace;
+use quarantine::Quarantine;
+use reporting::{
+    annotations::AnnotationFormat, multi::MultiReporter, ReportFormat, ReportOptions, ReportTarget,
+    Reporter,
+};
+use testing::{
+    cancel::CancelToken,
+    plan::{DiscoveredTestCrate, DiscoveryType, ManualTestCrate, RunMode, TestCrates, TestPlan},
+    schedule::{CrateStats, Schedule},
+    shard::{self, Shard, ShardStrategy},
+};
+use vcs::{ChangedFile, Vcs, VcsType};
+use watch::{FileWatcher, WatchFilter, WatchStatus};
 
 /// Configuration for the changed tests subcommand
 #[derive(Parser)]
@@ -35,9 +59,156 @@ enum CargoCli {
 #[derive(clap::Args)]
 #[command(
     version,
-    about = "Run tests only for crates that have been modified in the current workspace"
+    about = "Run tests only for crates that have been modified in the current workspace",
+    args_conflicts_with_subcommands = true
 )]
 struct TestChangedArgs {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    detection: DetectionArgs,
+
+    /// Include tests for crates dependent on the changed crates in the test run
+    #[arg(short = 'd', long)]
+    with_dependents: bool,
+
+    /// Specify a custom test runner
+    #[arg(short = 'r', value_enum, default_value_t)]
+    test_runner: TestRunnerType,
+
+    /// Compile or lint the selected crates instead of running their tests
+    #[arg(long, value_enum, default_value_t)]
+    mode: RunMode,
+
+    /// Granularity used to select tests within changed crates
+    #[arg(long, value_enum, default_value_t, value_name = "LEVEL")]
+    precision: Precision,
+
+    /// Skip running tests, only print the crates that would be tested
+    #[arg(short = 'n', long)]
+    dry_run: bool,
+
+    /// Display full output while running tests
+    #[arg(short = 'v', long)]
+    verbose: bool,
+
+    /// Run tests for all crates regardless of failure
+    #[arg(short = 'k', long)]
+    no_fail_fast: bool,
+
+    /// Retry failing crates up to N times, reporting those that pass on a retry as flaky
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    retries: usize,
+
+    /// Also run doc-tests with `cargo test --doc` when the test runner skips them, as nextest does
+    #[arg(long)]
+    doc_tests: bool,
+
+    /// Order in which crates are tested, using the durations and failures of previous runs
+    #[arg(long, value_enum, default_value_t, value_name = "STRATEGY")]
+    schedule: Schedule,
+
+    /// Only test this job's share of the selected crates, given as <INDEX>/<COUNT> such as 1/4
+    #[arg(long, value_name = "INDEX/COUNT")]
+    shard: Option<Shard>,
+
+    /// How selected crates are split across shards
+    #[arg(
+        long,
+        value_enum,
+        default_value_t,
+        value_name = "STRATEGY",
+        requires = "shard"
+    )]
+    shard_by: ShardStrategy,
+
+    /// JSON file mapping crate names to durations in milliseconds, shared by every shard job,
+    /// used to balance shards with `--shard-by duration`. Without it, crates are assigned by hash
+    #[arg(long, value_name = "FILE", requires = "shard")]
+    shard_timings: Option<PathBuf>,
+
+    /// Print which crates each shard tests instead of running tests
+    #[arg(long, requires = "shard")]
+    print_shards: bool,
+
+    /// Test every selected crate, even those that passed before with identical content
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Also look up and record passing results in a directory shared between CI runners
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["no_cache", "cache_url"])]
+    cache_dir: Option<PathBuf>,
+
+    /// Also look up and record passing results on an HTTP server, using GET and PUT on `<URL>/<fingerprint>`
+    #[arg(long, value_name = "URL", conflicts_with = "no_cache")]
+    cache_url: Option<String>,
+
+    /// Specify a set of crates to run tests for, typically for re-running failed tests
+    #[arg(short = 'c', long, value_delimiter = ',')]
+    crates: Vec<String>,
+
+    /// Re-run the crates that failed in the previous run
+    #[arg(long, conflicts_with = "crates")]
+    rerun_failed: bool,
+
+    /// Output in JSON format for machine consumption
+    #[arg(short = 'j', long)]
+    json: bool,
+
+    /// Write a JUnit XML report to the given path, same as `--report junit=PATH`
+    #[arg(long, value_name = "PATH", value_parser = junit_target)]
+    junit: Option<ReportTarget>,
+
+    /// Also write a report in the given format (console, json, junit or markdown) to a file, may be repeated
+    #[arg(long, value_name = "FORMAT=PATH")]
+    report: Vec<ReportTarget>,
+
+    /// Write a Markdown summary to the given path, or append it to $GITHUB_STEP_SUMMARY if no path is given
+    #[arg(long, value_name = "PATH", num_args = 0..=1, require_equals = true)]
+    markdown: Option<Option<PathBuf>>,
+
+    /// Annotate failing tests for a CI system, detected from the environment if no format is given
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "auto"
+    )]
+    annotations: Option<AnnotationFormat>,
+
+    /// Path of the GitLab code quality report written with --annotations=gitlab
+    #[arg(long, value_name = "PATH", default_value = reporting::annotations::DEFAULT_CODE_QUALITY_REPORT)]
+    code_quality_report: PathBuf,
+
+    /// Maximum number of bytes of failure output to include for each crate in a Markdown summary
+    #[arg(long, value_name = "BYTES", default_value_t = reporting::markdown::DEFAULT_MAX_FAILURE_OUTPUT)]
+    max_failure_output: usize,
+
+    /// Additional arguments to pass to the test runner
+    #[arg(last = true)]
+    test_runner_args: Vec<String>,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Explain why a crate is or is not part of the test plan
+    Explain(ExplainArgs),
+    /// Export the graph of changed crates and their dependents
+    Graph(GraphArgs),
+    /// Print the JSON Schema describing the events written by --json
+    JsonSchema,
+    /// List recent test runs
+    History(HistoryArgs),
+    /// Watch the workspace and test the crates affected by each change
+    Watch(WatchArgs),
+}
+
+/// Options controlling how changed crates are detected
+#[derive(clap::Args)]
+struct DetectionArgs {
     /// Compare changes between VCS references instead of uncommitted changes
     #[arg(
         long,
@@ -55,7 +226,39 @@ struct TestChangedArgs {
     /// Ending reference point (defaults to current state when using --changes)
     #[arg(long, requires = "from")]
     to: Option<String>,
+}
+
+#[derive(clap::Args)]
+struct ExplainArgs {
+    /// Name of the crate to explain
+    #[arg(value_name = "CRATE")]
+    crate_name: String,
+
+    #[command(flatten)]
+    detection: DetectionArgs,
+
+    /// Include tests for crates dependent on the changed crates in the test run
+    #[arg(short = 'd', long)]
+    with_dependents: bool,
+
+    /// Output in JSON format for machine consumption
+    #[arg(short = 'j', long)]
+    json: bool,
+}
+
+#[derive(clap::Args)]
+struct HistoryArgs {
+    /// Number of runs to list
+    #[arg(short = 'n', long, default_value_t = 10)]
+    limit: usize,
+
+    /// Output in JSON format for machine consumption
+    #[arg(short = 'j', long)]
+    json: bool,
+}
 
+#[derive(clap::Args)]
+struct WatchArgs {
     /// Specify a custom test runner
     #[arg(short = 'r', value_enum, default_value_t)]
     test_runner: TestRunnerType,
@@ -64,21 +267,25 @@ struct TestChangedArgs {
     #[arg(short = 'd', long)]
     with_dependents: bool,
 
-    /// Skip running tests, only print the crates that would be tested
-    #[arg(short = 'n', long)]
-    dry_run: bool,
+    /// Compile or lint the affected crates instead of running their tests
+    #[arg(long, value_enum, default_value_t)]
+    mode: RunMode,
+
+    /// Time to wait for edits to settle before testing, in milliseconds
+    #[arg(long, value_name = "MS", default_value_t = watch::DEFAULT_DEBOUNCE_MS)]
+    debounce: u64,
 
     /// Display full output while running tests
     #[arg(short = 'v', long)]
     verbose: bool,
 
-    /// Run tests for all crates regardless of failure
+    /// Run tests for all affected crates regardless of failure
     #[arg(short = 'k', long)]
     no_fail_fast: bool,
 
-    /// Specify a set of crates to run tests for, typically for re-running failed tests
-    #[arg(short = 'c', long, value_delimiter = ',')]
-    crates: Vec<String>,
+    /// Also run doc-tests with `cargo test --doc` when the test runner skips them, as nextest does
+    #[arg(long)]
+    doc_tests: bool,
 
     /// Output in JSON format for machine consumption
     #[arg(short = 'j', long)]
@@ -98,6 +305,29 @@ enum ChangeDetectionMode {
     Refs,
 }
 
+#[derive(ValueEnum, Clone, Debug, Default, PartialEq, Eq)]
+enum Precision {
+    /// Run every test in changed crates (default)
+    #[default]
+    Crate,
+    /// Only run test modules whose source files changed, using rustc dep-info files
+    Module,
+}
+
+#[derive(clap::Args)]
+struct GraphArgs {
+    #[command(flatten)]
+    detection: DetectionArgs,
+
+    /// Output format of the graph
+    #[arg(long, value_enum, default_value_t)]
+    format: GraphFormat,
+
+    /// Write the graph to a file instead of stdout
+    #[arg(short = 'o', long, value_name = "PATH")]
+    output: Option<PathBuf>,
+}
+
 fn main() {
     match run() {
         Ok(_) => (),
@@ -112,22 +342,98 @@ fn main() {
 fn run() -> Result<(), AppError> {
     let CargoCli::TestChanged(args) = CargoCli::parse();
 
-    // Create a reporter
-    let mut reporter = if args.json {
-        Box::new(reporting::json::JsonReporter::new(stdout())) as Box<dyn Reporter>
+    match args.command {
+        Some(Command::Explain(explain_args)) => explain(explain_args),
+        Some(Command::Graph(graph_args)) => graph(graph_args),
+        Some(Command::JsonSchema) => json_schema(),
+        Some(Command::History(history_args)) => history(history_args),
+        Some(Command::Watch(watch_args)) => watch(watch_args),
+        None => test(args),
+    }
+}
+
+fn create_reporter(json: bool, verbose: bool) -> Box<dyn Reporter> {
+    if json {
+        Box::new(reporting::json::JsonReporter::new(stdout()))
     } else {
-        Box::new(reporting::console::ConsoleReporter::new(
-            stdout(),
-            args.verbose,
-        )) as Box<dyn Reporter>
-    };
+        Box::new(
+            reporting::console::ConsoleReporter::new(stdout(), verbose)
+                .with_terminal(stdout().is_terminal()),
+        )
+    }
+}
 
-    // Get workspace and repository information
-    let vcs = VcsType::Git.create();
-    let workspace_root = vcs.get_workspace_root(Path::new("."))?;
+/// Create the terminal reporter along with any additional file reporters
+fn create_reporters(
+    json: bool,
+    verbose: bool,
+    targets: &[ReportTarget],
+    options: &ReportOptions,
+) -> Result<Box<dyn Reporter>, AppError> {
+    let primary = create_reporter(json, verbose);
+    if targets.is_empty() {
+        return Ok(primary);
+    }
+
+    let mut reporters = vec![primary];
+    for target in targets {
+        reporters.push(target.create(options)?);
+    }
+
+    Ok(Box::new(MultiReporter::new(reporters)))
+}
 
-    let changed_files = match &args.changes {
-        ChangeDetectionMode::Working => vcs.get_uncommitted_changes(&workspace_root)?,
+/// Create the reporter that annotates failing tests for a CI system
+///
+/// GitHub workflow commands are printed to stderr when stdout is reserved for JSON events.
+fn create_annotator(
+    format: AnnotationFormat,
+    json: bool,
+    code_quality_report: &Path,
+) -> Box<dyn Reporter> {
+    match format {
+        AnnotationFormat::Gitlab => Box::new(reporting::annotations::GitlabReporter::new(
+            code_quality_report.to_path_buf(),
+        )),
+        _ if json => Box::new(reporting::annotations::GithubReporter::new(stderr())),
+        _ => Box::new(reporting::annotations::GithubReporter::new(stdout())),
+    }
+}
+
+/// Parse `--junit PATH` the same way as `--report junit=PATH`
+fn junit_target(path: &str) -> Result<ReportTarget, String> {
+    format!("junit={}", path).parse()
+}
+
+/// Write the Markdown summary to the given path, or append it to the GitHub step summary
+fn markdown_target(path: Option<PathBuf>) -> Result<ReportTarget, AppError> {
+    if let Some(path) = path {
+        return Ok(ReportTarget {
+            format: ReportFormat::Markdown,
+            path,
+            append: false,
+        });
+    }
+
+    match std::env::var_os("GITHUB_STEP_SUMMARY") {
+        Some(path) if !path.is_empty() => Ok(ReportTarget {
+            format: ReportFormat::Markdown,
+            path: PathBuf::from(path),
+            append: true,
+        }),
+        _ => Err(AppError::InvalidArguments {
+            reason: "--markdown requires a path when GITHUB_STEP_SUMMARY is not set".to_string(),
+        }),
+    }
+}
+
+fn get_changed_files(
+    vcs: &dyn Vcs,
+    workspace_root: &Path,
+    args: &DetectionArgs,
+) -> Result<Vec<ChangedFile>, AppError> {
+    match &args.changes {
+        ChangeDetectionMode::Working => vcs.get_uncommitted_changes(workspace_root),
         ChangeDetectionMode::Refs => {
             let from_ref = args
                 .from
@@ -136,61 +442,475 @@ fn run() -> Result<(), AppError> {
                     reason: "--from is required when using --changes=refs".to_string(),
                 })?;
 
-            vcs.get_changes_between(&workspace_root, from_ref, args.to.as_deref())?
+            vcs.get_changes_between(workspace_root, from_ref, args.to.as_deref())
         }
+    }
+}
+
+/// Find the changed crates and the crates that depend on them
+fn discover_crates(
+    workspace: &Workspace,
+    changed_files: &[ChangedFile],
+) -> Result<IndexSet<DiscoveredTestCrate>, AppError> {
+    let changed_crates = metadata::find_changed_crates(
+        changed_files,
+        &workspace.crates,
+        &workspace.file_dependencies,
+        &workspace.mappings,
+    )?;
+
+    let mut crates_to_test = IndexSet::new();
+
+    crates_to_test.extend(
+        changed_crates
+            .iter()
+            .map(|(name, discovery_type)| DiscoveredTestCrate {
+                name: name.to_string(),
+                discovery_type: discovery_type.clone(),
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    let changed_crate_names = changed_crates.keys().copied().collect();
+    crates_to_test.extend(
+        metadata::find_dependent_crates(&changed_crate_names, &workspace.metadata)?
+            .into_iter()
+            .map(|name| DiscoveredTestCrate {
+                name: name.to_string(),
+                discovery_type: DiscoveryType::Dependent,
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    Ok(crates_to_test)
+}
+
+fn test(args: TestChangedArgs) -> Result<(), AppError> {
+    let mut report_targets = args.report.clone();
+    report_targets.extend(args.junit.clone());
+    if let Some(path) = &args.markdown {
+        report_targets.push(markdown_target(path.clone())?);
+    }
+    let options = ReportOptions {
+        max_failure_output: args.max_failure_output,
     };
+    let mut reporter = create_reporters(args.json, args.verbose, &report_targets, &options)?;
+    if let Some(annotator) = args
+        .annotations
+        .and_then(AnnotationFormat::resolve)
+        .map(|format| create_annotator(format, args.json, &args.code_quality_report))
+    {
+        reporter = Box::new(MultiReporter::new(vec![reporter, annotator]));
+    }
 
-    reporter.changed_files(changed_files.as_slice(), &workspace_root);
+    // Get workspace and repository information
+    let vcs = VcsType::Git.create();
+    let workspace_root = vcs.get_workspace_root(Path::new("."))?;
+    let changed_files = get_changed_files(vcs.as_ref(), &workspace_root, &args.detection)?;
 
-    let metadata = metadata::get_workspace_metadata(&workspace_root)?;
-    let crates = metadata::get_workspace_crates(&metadata)?;
+    let workspace = Workspace::load(&workspace_root)?;
+    let (changed_files, _) = workspace.ignore_patterns.partition(changed_files);
 
-    // Identify which crates need testing
-    let crates = if args.crates.is_empty() {
-        let changed_crates = metadata::find_changed_crates(&changed_files, &crates)?;
+    let history_path = History::path(workspace.metadata.target_directory.as_std_path());
+    let history = History::load(&history_path);
+    let recent_runs = history.recent(HISTORY_LIMIT);
+    // Quarantined tests only matter when tests are run
+    let mut quarantine = if args.mode == RunMode::Test {
+        workspace.quarantine.clone()
+    } else {
+        Quarantine::default()
+    };
+    if !quarantine.is_empty() {
+        quarantine = quarantine.with_history(&recent_runs);
+    }
+    let crate_stats = CrateStats::from_history(&recent_runs);
+
+    let mut crates = args.crates;
+    if args.rerun_failed {
+        let Some(last_run) = history.last_run().cloned() else {
+            reporter.note("no previous run recorded, nothing to rerun");
+            return Ok(());
+        };
+
+        crates = last_run.failed_crates();
+        if crates.is_empty() {
+            reporter.note("the previous run had no failures, nothing to rerun");
+            return Ok(());
+        }
+    }
 
-        let mut crates_to_test = IndexSet::new();
+    let started_at = SystemTime::now();
+    let head = vcs.get_head_commit(&workspace_root).ok().flatten();
 
-        crates_to_test.extend(
-            changed_crates
-                .iter()
-                .map(|name| DiscoveredTestCrate {
-                    name: name.to_string(),
-                    discovery_type: DiscoveryType::Modified,
-                })
-                .collect::<Vec<_>>(),
-        );
+    reporter.changed_files(changed_files.as_slice(), &workspace_root);
 
-        crates_to_test.extend(
-            metadata::find_dependent_crates(&changed_crates, &metadata)?
-                .into_iter()
-                .map(|name| DiscoveredTestCrate {
-                    name: name.to_string(),
-                    discovery_type: DiscoveryType::Dependent,
-                })
-                .collect::<Vec<_>>(),
-        );
+    // Identify which crates need testing
+    let mut test_filters = IndexMap::new();
+    let crates = if crates.is_empty() {
+        if args.precision == Precision::Module {
+            test_filters = metadata::find_test_filters(
+                &changed_files,
+                &workspace.crates,
+                &workspace.file_dependencies,
+                &workspace.metadata,
+                &workspace.dep_info,
+            );
+        }
 
-        TestCrates::Discovered(crates_to_test)
+        TestCrates::Discovered(discover_crates(&workspace, &changed_files)?)
     } else {
-        metadata::verify_crates_exist(&metadata, args.crates.as_slice())?;
+        metadata::verify_crates_exist(&workspace.metadata, crates.as_slice())?;
         TestCrates::Manual(IndexSet::from_iter(
-            args.crates.into_iter().map(|name| ManualTestCrate { name }),
+            crates.into_iter().map(|name| ManualTestCrate { name }),
         ))
     };
 
     // Get the appropriate test runner
-    let runner = args.test_runner.create();
+    let runners = Runners::for_mode(args.mode, &args.test_runner, &workspace)?;
 
     // Execute the tests
-    let test_plan = TestPlan {
+    let mut test_plan = TestPlan {
         workspace_root,
         crates,
+        mode: args.mode,
         with_dependents: args.with_dependents,
         fail_fast: !args.no_fail_fast,
+        retries: if args.mode == RunMode::Test {
+            args.retries
+        } else {
+            0
+        },
         verbose: args.verbose,
         test_runner_args: args.test_runner_args,
+        test_filters,
+        cached: IndexSet::new(),
+        shared_cached: IndexSet::new(),
+        fingerprints: IndexMap::new(),
+        quarantine,
+        schedule: args.schedule,
+        crate_stats,
+        doc_test_crates: if args.doc_tests {
+            metadata::find_doc_test_crates(&workspace.metadata)
+        } else {
+            IndexSet::new()
+        },
+        shard: None,
+    };
+
+    // Every job assigns the crates of the whole plan, before cached crates are skipped
+    if let Some(shard) = args.shard {
+        // Local histories differ between jobs, so only timings shared by every job are used
+        let (strategy, timings) = match (args.shard_by, &args.shard_timings) {
+            (ShardStrategy::Duration, Some(path)) => {
+                (ShardStrategy::Duration, shard::load_timings(path)?)
+            }
+            (ShardStrategy::Duration, None) => {
+                reporter.note("no --shard-timings given, assigning crates to shards by hash");
+                (ShardStrategy::Hash, IndexMap::new())
+            }
+            (strategy, _) => (strategy, IndexMap::new()),
+        };
+        let shards = strategy.assign(&test_plan.get_all_selected_crates(), shard.count, &timings);
+
+        if args.print_shards {
+            reporter.shards(&shards, shard.index);
+            return Ok(());
+        }
+
+        let total = test_plan.get_all_selected_crates().len();
+        test_plan.shard = shards.into_iter().nth(shard.index - 1);
+        reporter.note(&format!(
+            "testing shard {}, {} of {} selected crates",
+            shard,
+            test_plan.get_selected_crates().len(),
+            total
+        ));
+    }
+
+    let mut stores: Vec<Box<dyn ResultStore>> = Vec::new();
+    if !args.no_cache {
+        let shared = SharedInputs::collect(
+            &test_plan.workspace_root,
+            test_plan.mode,
+            &test_plan.test_runner_args,
+            &test_plan.doc_test_crates,
+        )?;
+        let uncommitted = vcs.get_uncommitted_changes(&test_plan.workspace_root)?;
+        let fingerprinter =
+            Fingerprinter::new(vcs.as_ref(), &workspace, &runners, shared, &uncommitted);
+        let local = LocalStore::in_target_dir(workspace.metadata.target_directory.as_std_path());
+        let shared: Option<Box<dyn ResultStore>> = match (args.cache_dir, &args.cache_url) {
+            (Some(dir), _) => Some(Box::new(LocalStore::new(dir))),
+            (None, Some(url)) => Some(Box::new(HttpStore::new(url))),
+            (None, None) => None,
+        };
+        cache::apply_cache(
+            &mut test_plan,
+            &fingerprinter,
+            &local,
+            shared.as_deref(),
+            reporter.as_mut(),
+        )?;
+
+        stores.push(Box::new(local));
+        stores.extend(shared);
+    }
+
+    let Some(results) =
+        testing::run_tests(&test_plan, &runners, args.dry_run, reporter.as_mut(), None)?
+    else {
+        return reporting::finish(reporter.as_mut());
+    };
+
+    cache::record_passing(
+        &results,
+        &test_plan.fingerprints,
+        &stores,
+        reporter.as_mut(),
+    );
+
+    // Only test runs are recorded, since compiling a crate says nothing about its tests
+    if args.mode == RunMode::Test {
+        let run = RunRecord::new(
+            started_at,
+            head,
+            &changed_files,
+            &test_plan,
+            &runners,
+            &results,
+        );
+        if let Err(e) = History::append(&history_path, run) {
+            reporter.error(&e.to_string());
+        }
+    }
+
+    reporting::finish(reporter.as_mut())?;
+    results.ensure_success()
+}
+
+fn explain(args: ExplainArgs) -> Result<(), AppError> {
+    let mut reporter = create_reporter(args.json, false);
+
+    let vcs = VcsType::Git.create();
+    let workspace_root = vcs.get_workspace_root(Path::new("."))?;
+    let changed_files = get_changed_files(vcs.as_ref(), &workspace_root, &args.detection)?;
+
+    let workspace = Workspace::load(&workspace_root)?;
+    let (changed_files, ignored_files) = workspace.ignore_patterns.partition(changed_files);
+
+    let explanation = explain::explain_crate(
+        &args.crate_name,
+        &workspace,
+        &changed_files,
+        &ignored_files,
+        args.with_dependents,
+    )?;
+
+    reporter.explain(&explanation);
+
+    Ok(())
+}
+
+fn graph(args: GraphArgs) -> Result<(), AppError> {
+    let vcs = VcsType::Git.create();
+    let workspace_root = vcs.get_workspace_root(Path::new("."))?;
+    let changed_files = get_changed_files(vcs.as_ref(), &workspace_root, &args.detection)?;
+
+    let workspace = Workspace::load(&workspace_root)?;
+    let (changed_files, _) = workspace.ignore_patterns.partition(changed_files);
+
+    let crates = discover_crates(&workspace, &changed_files)?;
+    let resolved = metadata::get_resolved_metadata(&workspace_root)?;
+    let rendered = graph::build_impact_graph(&crates, &resolved)?.render(&args.format)?;
+
+    match &args.output {
+        Some(path) => std::fs::write(path, rendered).map_err(|e| AppError::WriteFailed {
+            path: path.display().to_string(),
+            reason: e.to_string(),
+        }),
+        None => {
+            print!("{}", rendered);
+            Ok(())
+        }
+    }
+}
+
+fn json_schema() -> Result<(), AppError> {
+    let schema = serde_json::to_string_pretty(&reporting::events::json_schema())
+        .map_err(|e| AppError::Other(anyhow::anyhow!(e)))?;
+
+    println!("{}", schema);
+
+    Ok(())
+}
+
+fn history(args: HistoryArgs) -> Result<(), AppError> {
+    let mut reporter = create_reporter(args.json, false);
+
+    let vcs = VcsType::Git.create();
+    let workspace_root = vcs.get_workspace_root(Path::new("."))?;
+    let metadata = metadata::get_workspace_metadata(&workspace_root)?;
+
+    let history = History::load(&History::path(metadata.target_directory.as_std_path()));
+    reporter.history(&history.recent(args.limit));
+
+    Ok(())
+}
+
+fn watch(args: WatchArgs) -> Result<(), AppError> {
+    let mut reporter = create_reporter(args.json, args.verbose);
+
+    let vcs = VcsType::Git.create();
+    let workspace_root = vcs.get_workspace_root(Path::new("."))?;
+    let mut workspace = Workspace::load(&workspace_root)?;
+    let mut runners = Runners::for_mode(args.mode, &args.test_runner, &workspace)?;
+
+    // Changes cancel whichever run is in progress, as does Ctrl-C before exiting
+    let current_run = Arc::new(Mutex::new(CancelToken::default()));
+    let cancel_current = {
+        let current_run = Arc::clone(&current_run);
+        move || {
+            if let Ok(token) = current_run.lock() {
+                token.cancel();
+            }
+        }
     };
+    ctrlc::set_handler({
+        let cancel_current = cancel_current.clone();
+        move || {
+            cancel_current();
+            std::process::exit(130);
+        }
+    })
+    .map_err(|e| AppError::WatchFailed {
+        reason: e.to_string(),
+    })?;
+
+    let ignored_dirs = vec![
+        workspace
+            .metadata
+            .target_directory
+            .clone()
+            .into_std_path_buf(),
+        workspace_root.join(".git"),
+    ];
+    let filter = WatchFilter::new(
+        &workspace_root,
+        ignored_dirs,
+        VcsType::Git,
+        workspace.ignore_patterns.clone(),
+    );
+    let watcher = FileWatcher::new(
+        &workspace_root,
+        filter.clone(),
+        Duration::from_millis(args.debounce),
+        cancel_current,
+    )?;
+
+    // Paths changed since the last run that completed
+    let mut pending = IndexSet::new();
+    let mut last_run_passed = None;
+
+    loop {
+        reporter.watch_status(&WatchStatus::Watching { last_run_passed });
+        pending.extend(watcher.wait()?);
+
+        // A manifest change can add crates or dependencies, or break the workspace until the
+        // next save
+        if pending.iter().any(|path| path.ends_with("Cargo.toml")) {
+            match Workspace::load(&workspace_root).and_then(|loaded| {
+                Ok((
+                    Runners::for_mode(args.mode, &args.test_runner, &loaded)?,
+                    loaded,
+                ))
+            }) {
+                Ok((created, loaded)) => {
+                    filter.set_ignore_patterns(loaded.ignore_patterns.clone());
+                    runners = created;
+                    workspace = loaded;
+                }
+                Err(e) => {
+                    reporter.error(&e.to_string());
+                    continue;
+                }
+            }
+        }
 
-    testing::run_tests(test_plan, runner.as_ref(), args.dry_run, reporter.as_mut())
+        let (changed_files, _) = workspace
+            .ignore_patterns
+            .partition(watch::changed_files(&pending));
+        if changed_files.is_empty() {
+            pending.clear();
+            continue;
+        }
+        let crates = match discover_crates(&workspace, &changed_files) {
+            Ok(crates) => crates,
+            Err(e) => {
+                reporter.error(&e.to_string());
+                continue;
+            }
+        };
+
+        let test_plan = TestPlan {
+            workspace_root: workspace_root.clone(),
+            crates: TestCrates::Discovered(crates),
+            mode: args.mode,
+            with_dependents: args.with_dependents,
+            fail_fast: !args.no_fail_fast,
+            retries: 0,
+            verbose: args.verbose,
+            test_runner_args: args.test_runner_args.clone(),
+            test_filters: IndexMap::new(),
+            cached: IndexSet::new(),
+            shared_cached: IndexSet::new(),
+            fingerprints: IndexMap::new(),
+            quarantine: if args.mode == RunMode::Test {
+                workspace.quarantine.clone()
+            } else {
+                Quarantine::default()
+            },
+            schedule: Schedule::default(),
+            crate_stats: IndexMap::new(),
+            doc_test_crates: if args.doc_tests {
+                metadata::find_doc_test_crates(&workspace.metadata)
+            } else {
+                IndexSet::new()
+            },
+            shard: None,
+        };
+
+        reporter.watch_status(&WatchStatus::Changed {
+            files: changed_files
+                .iter()
+                .map(|file| file.current_path.clone())
+                .collect(),
+            crates: test_plan
+                .get_crates_to_test()
+                .into_iter()
+                .cloned()
+                .collect(),
+        });
+
+        let token = CancelToken::default();
+        if let Ok(mut current) = current_run.lock() {
+            *current = token.clone();
+        }
+
+        match testing::run_tests(&test_plan, &runners, false, reporter.as_mut(), Some(&token)) {
+            Ok(results) => {
+                last_run_passed = Some(results.is_none_or(|results| !results.has_failures()))
+            }
+            Err(AppError::RunCancelled) => {
+                reporter.watch_status(&WatchStatus::Cancelled);
+                continue;
+            }
+            Err(e @ AppError::TestRunnerNotInstalled { .. }) => return Err(e),
+            Err(e) => {
+                reporter.error(&e.to_string());
+                last_run_passed = Some(false);
+            }
+        }
+
+        pending.clear();
+    }
 }
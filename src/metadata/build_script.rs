@@ -0,0 +1,58 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::Crates;
+
+/// Load the paths watched via `rerun-if-changed` by the build scripts of workspace crates
+///
+/// Build script output lives in `build/<package>-<hash>/output`, and watched paths are
+/// relative to the package directory.
+pub fn load_rerun_if_changed(target_dir: &Path, crates: &Crates) -> Vec<(String, PathBuf)> {
+    let mut watched = Vec::new();
+
+    for profile_dir in super::find_profile_dirs(target_dir) {
+        let Ok(entries) = fs::read_dir(profile_dir.join("build")) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let build_dir = entry.path();
+            let Some(crate_info) = build_dir
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.rsplit_once('-'))
+                .and_then(|(package, _hash)| crates.0.iter().find(|c| c.name == package))
+            else {
+                continue;
+            };
+
+            let Ok(output) = fs::read_to_string(build_dir.join("output")) else {
+                continue;
+            };
+
+            for path in parse_rerun_if_changed(&output) {
+                let path = super::normalize_path(&crate_info.path.join(path));
+                let entry = (crate_info.name.clone(), path);
+                if !watched.contains(&entry) {
+                    watched.push(entry);
+                }
+            }
+        }
+    }
+
+    watched
+}
+
+/// Parse `rerun-if-changed` instructions from build script output
+pub fn parse_rerun_if_changed(output: &str) -> Vec<PathBuf> {
+    output
+        .lines()
+        .filter_map(|line| {
+            line.strip_prefix("cargo::rerun-if-changed=")
+                .or_else(|| line.strip_prefix("cargo:rerun-if-changed="))
+        })
+        .map(PathBuf::from)
+        .collect()
+}
@@ -0,0 +1,57 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use crate::metadata::build_script::{load_rerun_if_changed, parse_rerun_if_changed};
+use crate::metadata::{CrateInfo, Crates};
+
+#[test]
+fn test_parse_rerun_if_changed() {
+    let output = "\
+cargo:rerun-if-changed=build.rs
+cargo::rerun-if-changed=../proto/service.proto
+cargo:rustc-cfg=has_proto
+cargo:rerun-if-env-changed=PROTOC
+";
+
+    assert_eq!(
+        parse_rerun_if_changed(output),
+        vec![
+            PathBuf::from("build.rs"),
+            PathBuf::from("../proto/service.proto")
+        ]
+    );
+}
+
+#[test]
+fn test_load_rerun_if_changed() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let target_dir = temp_dir.path();
+
+    let profile_dir = target_dir.join("debug");
+    std::fs::create_dir_all(profile_dir.join("deps"))?;
+
+    let run_dir = profile_dir.join("build").join("my-crate-0123456789abcdef");
+    let compile_dir = profile_dir.join("build").join("my-crate-fedcba9876543210");
+    let other_dir = profile_dir.join("build").join("serde-0123456789abcdef");
+    std::fs::create_dir_all(&run_dir)?;
+    std::fs::create_dir_all(&compile_dir)?;
+    std::fs::create_dir_all(&other_dir)?;
+
+    std::fs::write(run_dir.join("output"), "cargo:rerun-if-changed=../proto\n")?;
+    std::fs::write(
+        other_dir.join("output"),
+        "cargo:rerun-if-changed=build.rs\n",
+    )?;
+
+    let crates = Crates(HashSet::from([CrateInfo {
+        name: "my-crate".to_string(),
+        path: PathBuf::from("/workspace/my-crate"),
+    }]));
+
+    assert_eq!(
+        load_rerun_if_changed(target_dir, &crates),
+        vec![("my-crate".to_string(), PathBuf::from("/workspace/proto"))]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The source files a single compilation unit was built from, as recorded by rustc
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepInfo {
+    pub sources: Vec<PathBuf>,
+}
+
+impl DepInfo {
+    pub fn contains(&self, path: &Path) -> bool {
+        self.sources.iter().any(|source| source == path)
+    }
+}
+
+/// Load every dep-info file cargo has written for the workspace
+///
+/// Relative paths inside dep-info files are resolved against the workspace root,
+/// which is the directory rustc is invoked from for workspace members.
+pub fn load_dep_info(target_dir: &Path, workspace_root: &Path) -> Vec<DepInfo> {
+    let mut dep_info = Vec::new();
+
+    for profile_dir in super::find_profile_dirs(target_dir) {
+        let Ok(entries) = fs::read_dir(profile_dir.join("deps")) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "d") {
+                if let Ok(contents) = fs::read_to_string(&path) {
+                    dep_info.push(parse_dep_info(&contents, workspace_root));
+                }
+            }
+        }
+    }
+
+    dep_info
+}
+
+/// Parse the makefile-style contents of a dep-info file
+pub fn parse_dep_info(contents: &str, base: &Path) -> DepInfo {
+    let mut sources = Vec::new();
+
+    for line in contents.lines() {
+        if line.starts_with('#') {
+            continue;
+        }
+
+        let Some(separator) = find_rule_separator(line) else {
+            continue;
+        };
+
+        for dependency in split_escaped(&line[separator + 1..]) {
+            let path = super::normalize_path(&base.join(dependency));
+            if !sources.contains(&path) {
+                sources.push(path);
+            }
+        }
+    }
+
+    DepInfo { sources }
+}
+
+/// Find the colon separating a rule's target from its dependencies
+fn find_rule_separator(line: &str) -> Option<usize> {
+    let bytes = line.as_bytes();
+
+    (0..bytes.len()).find(|&i| {
+        bytes[i] == b':'
+            && (i == 0 || bytes[i - 1] != b'\\')
+            && bytes.get(i + 1).is_none_or(|next| *next == b' ')
+    })
+}
+
+/// Split a list of whitespace-separated paths, honouring backslash-escaped spaces
+fn split_escaped(list: &str) -> Vec<String> {
+    let mut paths = Vec::new();
+    let mut current = String::new();
+    let mut chars = list.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&' ') => {
+                current.push(' ');
+                chars.next();
+            }
+            ' ' | '\t' => {
+                if !current.is_empty() {
+                    paths.push(std::mem::take(&mut current));
+                }
+            }
+            _ => current.push(c),
+        }
+    }
+
+    if !current.is_empty() {
+        paths.push(current);
+    }
+
+    paths
+}
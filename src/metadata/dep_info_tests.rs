@@ -0,0 +1,82 @@
+use std::path::{Path, PathBuf};
+
+use crate::metadata::dep_info::{load_dep_info, parse_dep_info};
+
+#[test]
+fn test_parse_dep_info_relative_paths() {
+    let contents = "\
+/workspace/target/debug/deps/crate1-abc.d: crate1/src/lib.rs crate1/src/foo.rs
+
+/workspace/target/debug/deps/libcrate1-abc.rlib: crate1/src/lib.rs crate1/src/foo.rs
+
+crate1/src/lib.rs:
+crate1/src/foo.rs:
+
+# env-dep:CARGO_PKG_NAME=crate1
+";
+
+    let dep_info = parse_dep_info(contents, Path::new("/workspace"));
+
+    assert_eq!(
+        dep_info.sources,
+        vec![
+            PathBuf::from("/workspace/crate1/src/lib.rs"),
+            PathBuf::from("/workspace/crate1/src/foo.rs"),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_dep_info_escaped_spaces() {
+    let contents = "/out/lib.rlib: /my\\ dir/src/lib.rs /my\\ dir/src/a\\ b.rs\n";
+
+    let dep_info = parse_dep_info(contents, Path::new("/workspace"));
+
+    assert_eq!(
+        dep_info.sources,
+        vec![
+            PathBuf::from("/my dir/src/lib.rs"),
+            PathBuf::from("/my dir/src/a b.rs"),
+        ]
+    );
+    assert!(dep_info.contains(Path::new("/my dir/src/a b.rs")));
+}
+
+#[test]
+fn test_load_dep_info_scans_profiles() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let target_dir = temp_dir.path();
+
+    let debug_deps = target_dir.join("debug").join("deps");
+    let triple_deps = target_dir
+        .join("x86_64-unknown-linux-gnu")
+        .join("release")
+        .join("deps");
+    std::fs::create_dir_all(&debug_deps)?;
+    std::fs::create_dir_all(&triple_deps)?;
+
+    std::fs::write(debug_deps.join("a-1.d"), "out: a/src/lib.rs\n")?;
+    std::fs::write(debug_deps.join("a-1.rlib"), "")?;
+    std::fs::write(triple_deps.join("b-2.d"), "out: b/src/lib.rs\n")?;
+
+    let dep_info = load_dep_info(target_dir, Path::new("/workspace"));
+
+    assert_eq!(dep_info.len(), 2);
+    assert!(dep_info
+        .iter()
+        .any(|d| d.contains(Path::new("/workspace/a/src/lib.rs"))));
+    assert!(dep_info
+        .iter()
+        .any(|d| d.contains(Path::new("/workspace/b/src/lib.rs"))));
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_dep_info_normalizes_parent_components() {
+    let contents = "out: crate1/src/lib.rs crate1/src/../../fixtures/data.json\n";
+
+    let dep_info = parse_dep_info(contents, Path::new("/workspace"));
+
+    assert!(dep_info.contains(Path::new("/workspace/fixtures/data.json")));
+}
@@ -4,11 +4,32 @@ use std::{
 };
 
 use anyhow::Result;
-use cargo_metadata::{CargoOpt, Metadata, MetadataCommand};
-use indexmap::IndexSet;
+use cargo_metadata::{CargoOpt, Metadata, MetadataCommand, Package, Target, TargetKind};
+use indexmap::{IndexMap, IndexSet};
+use schemars::JsonSchema;
+use serde::Serialize;
 
-use crate::{error::AppError, vcs::ChangedFile};
+use crate::{
+    config::Config,
+    error::AppError,
+    quarantine::Quarantine,
+    test_runner::{CustomRunner, RunnerOverride},
+    testing::{
+        filter::{FallbackReason, TargetFilter, TestFilter, TestTarget},
+        plan::DiscoveryType,
+    },
+    vcs::ChangedFile,
+};
+
+pub mod build_script;
+pub mod dep_info;
+
+use dep_info::DepInfo;
 
+#[cfg(test)]
+mod build_script_tests;
+#[cfg(test)]
+mod dep_info_tests;
 #[cfg(test)]
 mod tests;
 
@@ -16,6 +37,100 @@ mod tests;
 #[derive(Debug)]
 pub struct Crates(HashSet<CrateInfo>);
 
+/// Files outside of a crate's directory that the crate depends on, such as fixtures
+/// pulled in with `include_str!` or paths watched by its build script
+#[derive(Debug, Default)]
+pub struct FileDependencies(Vec<(String, PathBuf)>);
+
+/// Glob patterns from the configuration that select crates in addition to their directory
+#[derive(Debug, Default)]
+pub struct CrateMappings {
+    workspace_root: PathBuf,
+    mappings: Vec<CrateMapping>,
+}
+
+#[derive(Debug)]
+struct CrateMapping {
+    crate_name: String,
+    pattern: String,
+    matcher: globset::GlobMatcher,
+}
+
+/// Glob patterns from the configuration for changed files that should be disregarded
+#[derive(Debug, Default, Clone)]
+pub struct IgnorePatterns {
+    workspace_root: PathBuf,
+    patterns: Vec<(String, globset::GlobMatcher)>,
+}
+
+/// How a changed file selected a crate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(tag = "via", content = "pattern", rename_all = "snake_case")]
+pub enum ChangeOrigin {
+    /// The file lives in the crate's directory
+    Directory,
+    /// The crate depends on the file through dep-info or its build script
+    FileDependency,
+    /// The file matches one of the crate's `triggered-by` patterns
+    Pattern(String),
+}
+
+/// Workspace information shared by every command
+pub struct Workspace {
+    pub root: PathBuf,
+    pub metadata: Metadata,
+    pub crates: Crates,
+    pub dep_info: Vec<DepInfo>,
+    pub file_dependencies: FileDependencies,
+    pub mappings: CrateMappings,
+    pub ignore_patterns: IgnorePatterns,
+    pub quarantine: Quarantine,
+    /// Runner used by `-r custom`, if one is configured
+    pub custom_runner: Option<CustomRunner>,
+    /// Runner settings configured for individual crates
+    pub runner_overrides: IndexMap<String, RunnerOverride>,
+    /// Arguments passed to clippy when linting
+    pub clippy_args: Vec<String>,
+}
+
+impl Workspace {
+    /// Load metadata, configuration and previous build information for a workspace
+    pub fn load(root: &Path) -> Result<Self, AppError> {
+        let metadata = get_workspace_metadata(root)?;
+        let crates = get_workspace_crates(&metadata)?;
+        let config = Config::from_metadata(&metadata)?;
+        let dep_info = dep_info::load_dep_info(metadata.target_directory.as_std_path(), root);
+        let file_dependencies = get_file_dependencies(&metadata, &crates, &dep_info);
+        let mappings = CrateMappings::from_config(&config, &metadata, root)?;
+        let ignore_patterns = IgnorePatterns::from_config(&config, root)?;
+        let quarantine = Quarantine::from_config(&config, root)?;
+        let custom_runner = CustomRunner::from_config(config.custom_runner.as_ref(), &crates)?;
+        let runner_overrides = RunnerOverride::from_config(&config, &metadata)?;
+
+        Ok(Workspace {
+            root: root.to_path_buf(),
+            metadata,
+            crates,
+            dep_info,
+            file_dependencies,
+            mappings,
+            ignore_patterns,
+            quarantine,
+            custom_runner,
+            runner_overrides,
+            clippy_args: config.clippy_args,
+        })
+    }
+}
+
+/// Compile a glob pattern where `*` does not match path separators
+fn compile_glob(pattern: &str) -> Result<globset::GlobMatcher, globset::Error> {
+    Ok(globset::GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()?
+        .compile_matcher())
+}
+
 /// Represents a single crate in a workspace
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct CrateInfo {
@@ -23,6 +138,32 @@ pub struct CrateInfo {
     pub path: PathBuf,
 }
 
+impl Crates {
+    pub fn get(&self, crate_name: &str) -> Option<&CrateInfo> {
+        self.0.iter().find(|c| c.name == crate_name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CrateInfo> {
+        self.0.iter()
+    }
+}
+
+impl FromIterator<CrateInfo> for Crates {
+    fn from_iter<I: IntoIterator<Item = CrateInfo>>(iter: I) -> Self {
+        Crates(iter.into_iter().collect())
+    }
+}
+
+impl FileDependencies {
+    /// Files outside the crate's directory that the given crate depends on
+    pub fn for_crate<'a>(&'a self, crate_name: &'a str) -> impl Iterator<Item = &'a Path> {
+        self.0
+            .iter()
+            .filter(move |(name, _)| name == crate_name)
+            .map(|(_, path)| path.as_path())
+    }
+}
+
 /// Get workspace metadata using cargo metadata
 pub fn get_workspace_metadata(workspace_root: &Path) -> Result<Metadata, AppError> {
     let metadata = MetadataCommand::new()
@@ -37,6 +178,17 @@ pub fn get_workspace_metadata(workspace_root: &Path) -> Result<Metadata, AppErro
     Ok(metadata)
 }
 
+/// Get workspace metadata including the resolved dependency graph
+pub fn get_resolved_metadata(workspace_root: &Path) -> Result<Metadata, AppError> {
+    MetadataCommand::new()
+        .manifest_path(workspace_root.join("Cargo.toml"))
+        .features(CargoOpt::AllFeatures)
+        .exec()
+        .map_err(|e| AppError::MetadataFailed {
+            reason: e.to_string(),
+        })
+}
+
 /// Get workspace crates using cargo metadata
 pub fn get_workspace_crates(metadata: &Metadata) -> Result<Crates, AppError> {
     let mut crates = HashSet::new();
@@ -58,6 +210,208 @@ pub fn get_workspace_crates(metadata: &Metadata) -> Result<Crates, AppError> {
     Ok(Crates(crates))
 }
 
+impl CrateMappings {
+    /// Compile the `triggered-by` patterns of every configured crate
+    pub fn from_config(
+        config: &Config,
+        metadata: &Metadata,
+        workspace_root: &Path,
+    ) -> Result<Self, AppError> {
+        let mut mappings = Vec::new();
+
+        for (crate_name, crate_config) in &config.crates {
+            if crate_config.triggered_by.is_empty() {
+                continue;
+            }
+
+            verify_crates_exist(metadata, std::slice::from_ref(crate_name))?;
+
+            for pattern in &crate_config.triggered_by {
+                let matcher = compile_glob(pattern).map_err(|e| AppError::InvalidConfig {
+                    reason: format!("invalid pattern for crate '{}': {}", crate_name, e),
+                })?;
+
+                mappings.push(CrateMapping {
+                    crate_name: crate_name.clone(),
+                    pattern: pattern.clone(),
+                    matcher,
+                });
+            }
+        }
+
+        Ok(CrateMappings {
+            workspace_root: workspace_root.to_path_buf(),
+            mappings,
+        })
+    }
+
+    /// Find the crates mapped to a file path, along with the pattern that matched
+    fn find_crates_for_file(&self, file_path: &Path) -> Vec<&CrateMapping> {
+        let Ok(relative_path) = file_path.strip_prefix(&self.workspace_root) else {
+            return Vec::new();
+        };
+
+        self.mappings
+            .iter()
+            .filter(|mapping| mapping.matcher.is_match(relative_path))
+            .collect()
+    }
+}
+
+impl IgnorePatterns {
+    /// Compile the workspace-wide `ignore` patterns
+    pub fn from_config(config: &Config, workspace_root: &Path) -> Result<Self, AppError> {
+        let patterns = config
+            .ignore
+            .iter()
+            .map(|pattern| {
+                compile_glob(pattern)
+                    .map(|matcher| (pattern.clone(), matcher))
+                    .map_err(|e| AppError::InvalidConfig {
+                        reason: format!("invalid ignore pattern: {}", e),
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(IgnorePatterns {
+            workspace_root: workspace_root.to_path_buf(),
+            patterns,
+        })
+    }
+
+    /// Find the first pattern that ignores a file path
+    fn find_pattern_for_file(&self, file_path: &Path) -> Option<&str> {
+        let relative_path = file_path.strip_prefix(&self.workspace_root).ok()?;
+
+        self.patterns
+            .iter()
+            .find(|(_, matcher)| matcher.is_match(relative_path))
+            .map(|(pattern, _)| pattern.as_str())
+    }
+
+    /// Whether a file path is matched by one of the patterns
+    pub fn is_ignored(&self, file_path: &Path) -> bool {
+        self.find_pattern_for_file(file_path).is_some()
+    }
+
+    /// Split changed files into those to consider and those ignored, along with the
+    /// pattern that ignored them
+    pub fn partition(
+        &self,
+        changed_files: Vec<ChangedFile>,
+    ) -> (Vec<ChangedFile>, Vec<(ChangedFile, String)>) {
+        let mut kept = Vec::new();
+        let mut ignored = Vec::new();
+
+        for change in changed_files {
+            match self.find_pattern_for_file(&change.current_path) {
+                Some(pattern) => {
+                    let pattern = pattern.to_string();
+                    ignored.push((change, pattern));
+                }
+                None => kept.push(change),
+            }
+        }
+
+        (kept, ignored)
+    }
+}
+
+/// Find the profile directories (such as `debug` and `release`) cargo has created,
+/// including those nested under a target triple
+fn find_profile_dirs(target_dir: &Path) -> Vec<PathBuf> {
+    let mut profile_dirs = Vec::new();
+    let mut candidates = vec![target_dir.to_path_buf()];
+
+    for _ in 0..2 {
+        let mut next = Vec::new();
+        for dir in candidates {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if !path.is_dir() {
+                    continue;
+                }
+                if path.join("deps").is_dir() {
+                    profile_dirs.push(path);
+                } else {
+                    next.push(path);
+                }
+            }
+        }
+        candidates = next;
+    }
+
+    profile_dirs
+}
+
+/// Lexically resolve `.` and `..` components without touching the filesystem
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            _ => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// Collect the files outside of each crate's directory that the crate depends on
+pub fn get_file_dependencies(
+    metadata: &Metadata,
+    crates: &Crates,
+    dep_info: &[DepInfo],
+) -> FileDependencies {
+    let target_dir = metadata.target_directory.as_std_path();
+    let mut dependencies = Vec::new();
+
+    for package in &metadata.packages {
+        let Some(crate_info) = crates.0.iter().find(|c| c.name == package.name) else {
+            continue;
+        };
+
+        let units = dep_info.iter().filter(|unit| {
+            package
+                .targets
+                .iter()
+                .any(|target| unit.contains(target.src_path.as_std_path()))
+        });
+
+        for source in units.flat_map(|unit| unit.sources.iter()) {
+            if source.starts_with(&crate_info.path) || source.starts_with(target_dir) {
+                continue;
+            }
+
+            let entry = (crate_info.name.clone(), source.clone());
+            if !dependencies.contains(&entry) {
+                dependencies.push(entry);
+            }
+        }
+    }
+
+    for entry in build_script::load_rerun_if_changed(target_dir, crates) {
+        let inside_crate = crates
+            .0
+            .iter()
+            .any(|c| c.name == entry.0 && entry.1.starts_with(&c.path));
+        if !inside_crate && !dependencies.contains(&entry) {
+            dependencies.push(entry);
+        }
+    }
+
+    FileDependencies(dependencies)
+}
+
 /// Find the crate name for a given file path
 fn find_crate_for_file<'a>(file_path: &Path, crates: &'a Crates) -> Option<&'a CrateInfo> {
     let mut best_match: Option<&CrateInfo> = None;
@@ -76,27 +430,292 @@ fn find_crate_for_file<'a>(file_path: &Path, crates: &'a Crates) -> Option<&'a C
     best_match
 }
 
+/// Find every crate affected by a change to the given file path, either because the file
+/// lives in the crate's directory or because the crate depends on it
+fn find_crates_for_file<'a>(
+    file_path: &Path,
+    crates: &'a Crates,
+    file_dependencies: &'a FileDependencies,
+) -> Vec<&'a String> {
+    let mut affected = Vec::new();
+
+    if let Some(crate_info) = find_crate_for_file(file_path, crates) {
+        affected.push(&crate_info.name);
+    }
+
+    for (crate_name, path) in &file_dependencies.0 {
+        if file_path.starts_with(path) && !affected.contains(&crate_name) {
+            affected.push(crate_name);
+        }
+    }
+
+    affected
+}
+
 /// Find crates that have changed based on file paths
+///
+/// Crates selected through their directory or file dependencies are reported as modified,
+/// while crates only selected through a configured pattern are reported as mapped.
 pub fn find_changed_crates<'a>(
     changed_files: &[ChangedFile],
     crates: &'a Crates,
-) -> Result<IndexSet<&'a String>, AppError> {
-    let mut changed_crates = IndexSet::new();
+    file_dependencies: &'a FileDependencies,
+    mappings: &'a CrateMappings,
+) -> Result<IndexMap<&'a String, DiscoveryType>, AppError> {
+    let mut changed_crates = IndexMap::new();
+    let mut mapped_crates = IndexMap::new();
 
     for change in changed_files {
-        if let Some(crate_info) = find_crate_for_file(&change.current_path, crates) {
-            changed_crates.insert(&crate_info.name);
-        }
-        if let Some(old_path) = &change.old_path {
-            if let Some(crate_info) = find_crate_for_file(old_path, crates) {
-                changed_crates.insert(&crate_info.name);
+        let paths = std::iter::once(&change.current_path).chain(change.old_path.as_ref());
+        for path in paths {
+            for crate_name in find_crates_for_file(path, crates, file_dependencies) {
+                changed_crates.insert(crate_name, DiscoveryType::Modified);
+            }
+            for mapping in mappings.find_crates_for_file(path) {
+                mapped_crates
+                    .entry(&mapping.crate_name)
+                    .or_insert_with(|| DiscoveryType::Mapped {
+                        pattern: mapping.pattern.clone(),
+                    });
             }
         }
     }
 
+    for (crate_name, discovery_type) in mapped_crates {
+        changed_crates.entry(crate_name).or_insert(discovery_type);
+    }
+
     Ok(changed_crates)
 }
 
+/// Find the changed files that select a given crate, along with how each selected it
+pub fn find_changes_for_crate<'a>(
+    crate_name: &str,
+    changed_files: &'a [ChangedFile],
+    crates: &Crates,
+    file_dependencies: &FileDependencies,
+    mappings: &CrateMappings,
+) -> Vec<(&'a Path, ChangeOrigin)> {
+    let mut changes = Vec::new();
+
+    for change in changed_files {
+        let paths = std::iter::once(&change.current_path).chain(change.old_path.as_ref());
+        for path in paths {
+            let origin = if find_crate_for_file(path, crates).is_some_and(|c| c.name == crate_name)
+            {
+                Some(ChangeOrigin::Directory)
+            } else if file_dependencies
+                .0
+                .iter()
+                .any(|(name, dependency)| name == crate_name && path.starts_with(dependency))
+            {
+                Some(ChangeOrigin::FileDependency)
+            } else {
+                mappings
+                    .find_crates_for_file(path)
+                    .into_iter()
+                    .find(|mapping| mapping.crate_name == crate_name)
+                    .map(|mapping| ChangeOrigin::Pattern(mapping.pattern.clone()))
+            };
+
+            if let Some(origin) = origin {
+                changes.push((path.as_path(), origin));
+            }
+        }
+    }
+
+    changes
+}
+
+/// Find the tests to run for each changed crate based on which of its source files changed
+pub fn find_test_filters(
+    changed_files: &[ChangedFile],
+    crates: &Crates,
+    file_dependencies: &FileDependencies,
+    metadata: &Metadata,
+    dep_info: &[DepInfo],
+) -> IndexMap<String, TestFilter> {
+    let mut files_by_crate: IndexMap<&String, Vec<&Path>> = IndexMap::new();
+
+    for change in changed_files {
+        let paths =
+            std::iter::once(change.current_path.as_path()).chain(change.old_path.as_deref());
+        for path in paths {
+            for crate_name in find_crates_for_file(path, crates, file_dependencies) {
+                files_by_crate.entry(crate_name).or_default().push(path);
+            }
+        }
+    }
+
+    files_by_crate
+        .into_iter()
+        .filter_map(|(name, files)| {
+            let package = metadata.packages.iter().find(|p| p.name == *name)?;
+            let filter = if dep_info.is_empty() {
+                TestFilter::Crate {
+                    reason: FallbackReason::NoDepInfo,
+                }
+            } else {
+                find_test_filter(&files, package, dep_info)
+            };
+            Some((name.clone(), filter))
+        })
+        .collect()
+}
+
+/// Find the tests to run for a single crate given its changed files
+fn find_test_filter(files: &[&Path], package: &Package, dep_info: &[DepInfo]) -> TestFilter {
+    let mut filters: IndexMap<TestTarget, TargetFilter> = IndexMap::new();
+
+    for file in files {
+        let relative = package
+            .manifest_path
+            .parent()
+            .and_then(|dir| file.strip_prefix(dir).ok())
+            .unwrap_or(file)
+            .to_path_buf();
+
+        if *file == package.manifest_path.as_std_path() {
+            return TestFilter::Crate {
+                reason: FallbackReason::ManifestChanged(relative),
+            };
+        }
+
+        if file.extension().is_none_or(|ext| ext != "rs") {
+            return TestFilter::Crate {
+                reason: FallbackReason::NonSourceFile(relative),
+            };
+        }
+
+        let targets = find_targets_for_file(file, package, dep_info);
+        if targets.is_empty() {
+            return TestFilter::Crate {
+                reason: FallbackReason::UnknownSourceFile(relative),
+            };
+        }
+
+        for target in targets {
+            let test_target = if target.is_kind(TargetKind::CustomBuild) {
+                return TestFilter::Crate {
+                    reason: FallbackReason::BuildScriptChanged(relative),
+                };
+            } else if target.is_kind(TargetKind::Test) {
+                TestTarget::Test(target.name.clone())
+            } else if target.is_kind(TargetKind::Bin) {
+                TestTarget::Bin(target.name.clone())
+            } else if target.is_kind(TargetKind::Example) || target.is_kind(TargetKind::Bench) {
+                return TestFilter::Crate {
+                    reason: FallbackReason::UntestedTargetChanged(relative),
+                };
+            } else {
+                TestTarget::Lib
+            };
+
+            let modules = match test_target {
+                TestTarget::Test(_) => None,
+                TestTarget::Lib if *file == target.src_path.as_std_path() => {
+                    return TestFilter::Crate {
+                        reason: FallbackReason::CrateRootChanged(relative),
+                    };
+                }
+                _ => module_path_for_file(file, target).map(|module| IndexSet::from([module])),
+            };
+
+            let filter = TargetFilter {
+                target: test_target.clone(),
+                modules,
+            };
+
+            match filters.get_mut(&test_target) {
+                Some(existing) => existing.merge(filter),
+                None => {
+                    filters.insert(test_target, filter);
+                }
+            }
+        }
+    }
+
+    // Integration tests, binaries and doc-tests link the library, but their dep-info does
+    // not list its sources, so they are run in full whenever the library changes
+    if filters.contains_key(&TestTarget::Lib) {
+        for target in &package.targets {
+            let test_target = if target.is_kind(TargetKind::Test) {
+                TestTarget::Test(target.name.clone())
+            } else if target.is_kind(TargetKind::Bin) {
+                TestTarget::Bin(target.name.clone())
+            } else if target.is_kind(TargetKind::Lib) && target.doctest {
+                TestTarget::Doc
+            } else {
+                continue;
+            };
+
+            let filter = TargetFilter {
+                target: test_target.clone(),
+                modules: None,
+            };
+            match filters.get_mut(&test_target) {
+                Some(existing) => existing.merge(filter),
+                None => {
+                    filters.insert(test_target, filter);
+                }
+            }
+        }
+    }
+
+    TestFilter::Targets {
+        targets: filters.into_values().collect(),
+    }
+}
+
+/// Find the targets of a package whose compilation units include the given file
+fn find_targets_for_file<'a>(
+    file: &Path,
+    package: &'a Package,
+    dep_info: &[DepInfo],
+) -> Vec<&'a Target> {
+    let mut targets: Vec<&Target> = Vec::new();
+
+    for unit in dep_info.iter().filter(|unit| unit.contains(file)) {
+        for target in &package.targets {
+            if unit.contains(target.src_path.as_std_path())
+                && !targets
+                    .iter()
+                    .any(|t| t.name == target.name && t.kind == target.kind)
+            {
+                targets.push(target);
+            }
+        }
+    }
+
+    targets
+}
+
+/// Derive the module path of a source file relative to the root of its target
+///
+/// Returns `None` for the target root itself, or for files outside the target's directory.
+pub fn module_path_for_file(file: &Path, target: &Target) -> Option<String> {
+    let root = target.src_path.as_std_path();
+    if file == root {
+        return None;
+    }
+
+    let relative = file.strip_prefix(root.parent()?).ok()?.with_extension("");
+    let mut segments = relative
+        .components()
+        .map(|c| c.as_os_str().to_str().map(str::to_string))
+        .collect::<Option<Vec<_>>>()?;
+
+    if segments.last().is_some_and(|last| last == "mod") {
+        segments.pop();
+    }
+
+    if segments.is_empty() {
+        None
+    } else {
+        Some(segments.join("::"))
+    }
+}
+
 /// Find crates that depend on changed crates
 pub fn find_dependent_crates<'a>(
     changed_crates: &IndexSet<&String>,
@@ -116,6 +735,32 @@ pub fn find_dependent_crates<'a>(
     Ok(dependent_crates)
 }
 
+/// Find the chain of crates through which a dependent crate was selected, starting from
+/// the changed crate it depends on
+pub fn find_dependency_path(
+    crate_name: &str,
+    changed_crates: &IndexSet<&String>,
+    metadata: &Metadata,
+) -> Option<Vec<String>> {
+    let package = metadata.packages.iter().find(|p| p.name == crate_name)?;
+
+    package
+        .dependencies
+        .iter()
+        .find(|dep| changed_crates.contains(&dep.name))
+        .map(|dep| vec![dep.name.clone(), package.name.clone()])
+}
+
+/// Find the workspace crates with doc-tests, which only library targets have
+pub fn find_doc_test_crates(metadata: &Metadata) -> IndexSet<String> {
+    metadata
+        .workspace_packages()
+        .into_iter()
+        .filter(|package| package.targets.iter().any(|target| target.doctest))
+        .map(|package| package.name.clone())
+        .collect()
+}
+
 /// Verify that all specified crates exist in the workspace
 pub fn verify_crates_exist(
     metadata: &cargo_metadata::Metadata,
@@ -148,12 +148,15 @@ fn test_find_changed_crates() {
         },
     ];
 
-    let result = find_changed_crates(&changed_files, &crates).unwrap();
+    let file_dependencies = FileDependencies::default();
+    let mappings = CrateMappings::default();
+    let result =
+        find_changed_crates(&changed_files, &crates, &file_dependencies, &mappings).unwrap();
 
     assert_eq!(result.len(), 3);
-    assert!(result.contains(&"crate1".to_string()));
-    assert!(result.contains(&"crate2".to_string()));
-    assert!(result.contains(&"crate3".to_string()));
+    assert!(result.contains_key(&"crate1".to_string()));
+    assert!(result.contains_key(&"crate2".to_string()));
+    assert!(result.contains_key(&"crate3".to_string()));
 }
 
 #[test]
@@ -194,3 +197,481 @@ fn test_verify_crates_exist() {
         _ => panic!("Expected UnknownCrate error"),
     }
 }
+
+fn create_test_target(name: &str, kind: TargetKind, src_path: &str) -> Target {
+    cargo_metadata::TargetBuilder::default()
+        .name(name)
+        .kind(vec![kind])
+        .src_path(src_path)
+        .build()
+        .unwrap()
+}
+
+fn create_filtered_package() -> Package {
+    PackageBuilder::new(
+        "crate1",
+        Version::new(1, 0, 0),
+        PackageId {
+            repr: "crate1".to_string(),
+        },
+        "/workspace/crate1/Cargo.toml",
+    )
+    .targets(vec![
+        create_test_target("crate1", TargetKind::Lib, "/workspace/crate1/src/lib.rs"),
+        create_test_target("cli", TargetKind::Bin, "/workspace/crate1/src/main.rs"),
+        create_test_target(
+            "integration",
+            TargetKind::Test,
+            "/workspace/crate1/tests/integration.rs",
+        ),
+    ])
+    .build()
+    .unwrap()
+}
+
+fn create_filtered_dep_info() -> Vec<DepInfo> {
+    vec![
+        dep_info::parse_dep_info(
+            "lib: crate1/src/lib.rs crate1/src/foo/mod.rs crate1/src/foo/bar.rs crate1/src/shared.rs",
+            Path::new("/workspace"),
+        ),
+        dep_info::parse_dep_info(
+            "bin: crate1/src/main.rs crate1/src/shared.rs",
+            Path::new("/workspace"),
+        ),
+        dep_info::parse_dep_info(
+            "test: crate1/tests/integration.rs",
+            Path::new("/workspace"),
+        ),
+    ]
+}
+
+fn changed_file(path: &str) -> ChangedFile {
+    ChangedFile {
+        old_path: None,
+        current_path: PathBuf::from(path),
+        file_type: FileType::File,
+        change_type: ChangeType::Modified,
+    }
+}
+
+#[test]
+fn test_module_path_for_file() {
+    let lib = create_test_target("crate1", TargetKind::Lib, "/workspace/crate1/src/lib.rs");
+
+    let module = |path: &str| module_path_for_file(Path::new(path), &lib);
+
+    assert_eq!(module("/workspace/crate1/src/lib.rs"), None);
+    assert_eq!(
+        module("/workspace/crate1/src/foo.rs"),
+        Some("foo".to_string())
+    );
+    assert_eq!(
+        module("/workspace/crate1/src/foo/mod.rs"),
+        Some("foo".to_string())
+    );
+    assert_eq!(
+        module("/workspace/crate1/src/foo/bar.rs"),
+        Some("foo::bar".to_string())
+    );
+    assert_eq!(module("/workspace/crate1/build.rs"), None);
+}
+
+#[test]
+fn test_find_test_filter_modules() {
+    let package = create_filtered_package();
+    let dep_info = create_filtered_dep_info();
+
+    let files = [
+        Path::new("/workspace/crate1/src/foo/bar.rs"),
+        Path::new("/workspace/crate1/src/foo/mod.rs"),
+        Path::new("/workspace/crate1/src/shared.rs"),
+        Path::new("/workspace/crate1/tests/integration.rs"),
+    ];
+
+    let filter = find_test_filter(&files, &package, &dep_info);
+
+    assert_eq!(
+        filter,
+        TestFilter::Targets {
+            targets: vec![
+                TargetFilter {
+                    target: TestTarget::Lib,
+                    modules: Some(IndexSet::from([
+                        "foo::bar".to_string(),
+                        "foo".to_string(),
+                        "shared".to_string(),
+                    ])),
+                },
+                TargetFilter {
+                    target: TestTarget::Bin("cli".to_string()),
+                    modules: None,
+                },
+                TargetFilter {
+                    target: TestTarget::Test("integration".to_string()),
+                    modules: None,
+                },
+                TargetFilter {
+                    target: TestTarget::Doc,
+                    modules: None,
+                },
+            ]
+        }
+    );
+}
+
+#[test]
+fn test_find_test_filter_bin_module() {
+    let package = create_filtered_package();
+    let dep_info = vec![dep_info::parse_dep_info(
+        "bin: crate1/src/main.rs crate1/src/cli.rs",
+        Path::new("/workspace"),
+    )];
+
+    let filter = find_test_filter(
+        &[Path::new("/workspace/crate1/src/cli.rs")],
+        &package,
+        &dep_info,
+    );
+
+    assert_eq!(
+        filter,
+        TestFilter::Targets {
+            targets: vec![TargetFilter {
+                target: TestTarget::Bin("cli".to_string()),
+                modules: Some(IndexSet::from(["cli".to_string()])),
+            }]
+        }
+    );
+}
+
+#[test]
+fn test_find_test_filter_lib_module_selects_dependent_targets() {
+    let package = PackageBuilder::new(
+        "crate1",
+        Version::new(1, 0, 0),
+        PackageId {
+            repr: "crate1".to_string(),
+        },
+        "/workspace/crate1/Cargo.toml",
+    )
+    .targets(vec![
+        create_test_target("crate1", TargetKind::Lib, "/workspace/crate1/src/lib.rs"),
+        create_test_target("it", TargetKind::Test, "/workspace/crate1/tests/it.rs"),
+    ])
+    .build()
+    .unwrap();
+    let dep_info = vec![
+        dep_info::parse_dep_info(
+            "lib: crate1/src/lib.rs crate1/src/foo.rs",
+            Path::new("/workspace"),
+        ),
+        dep_info::parse_dep_info("test: crate1/tests/it.rs", Path::new("/workspace")),
+    ];
+
+    let filter = find_test_filter(
+        &[Path::new("/workspace/crate1/src/foo.rs")],
+        &package,
+        &dep_info,
+    );
+
+    assert_eq!(
+        filter,
+        TestFilter::Targets {
+            targets: vec![
+                TargetFilter {
+                    target: TestTarget::Lib,
+                    modules: Some(IndexSet::from(["foo".to_string()])),
+                },
+                TargetFilter {
+                    target: TestTarget::Doc,
+                    modules: None,
+                },
+                TargetFilter {
+                    target: TestTarget::Test("it".to_string()),
+                    modules: None,
+                },
+            ]
+        }
+    );
+}
+
+#[test]
+fn test_find_test_filter_fallbacks() {
+    let package = create_filtered_package();
+    let dep_info = create_filtered_dep_info();
+
+    let fallback = |path: &str| find_test_filter(&[Path::new(path)], &package, &dep_info);
+
+    assert_eq!(
+        fallback("/workspace/crate1/src/lib.rs"),
+        TestFilter::Crate {
+            reason: FallbackReason::CrateRootChanged(PathBuf::from("src/lib.rs"))
+        }
+    );
+    assert_eq!(
+        fallback("/workspace/crate1/Cargo.toml"),
+        TestFilter::Crate {
+            reason: FallbackReason::ManifestChanged(PathBuf::from("Cargo.toml"))
+        }
+    );
+    assert_eq!(
+        fallback("/workspace/crate1/README.md"),
+        TestFilter::Crate {
+            reason: FallbackReason::NonSourceFile(PathBuf::from("README.md"))
+        }
+    );
+    assert_eq!(
+        fallback("/workspace/crate1/src/new.rs"),
+        TestFilter::Crate {
+            reason: FallbackReason::UnknownSourceFile(PathBuf::from("src/new.rs"))
+        }
+    );
+}
+
+#[test]
+fn test_find_test_filters_without_dep_info() {
+    let crates = Crates(HashSet::from([create_test_crate(
+        "crate1",
+        "/workspace/crate1",
+    )]));
+    let metadata = create_test_metadata();
+
+    let filters = find_test_filters(
+        &[changed_file("/workspace/crate1/src/foo.rs")],
+        &crates,
+        &FileDependencies::default(),
+        &metadata,
+        &[],
+    );
+
+    assert_eq!(
+        filters.get("crate1"),
+        Some(&TestFilter::Crate {
+            reason: FallbackReason::NoDepInfo
+        })
+    );
+}
+
+#[test]
+fn test_get_file_dependencies() {
+    let crates = Crates(HashSet::from([
+        create_test_crate("crate1", "/workspace/crate1"),
+        create_test_crate("crate2", "/workspace/crate2"),
+    ]));
+    let mut metadata = create_test_metadata();
+    metadata.packages[0].targets = vec![create_test_target(
+        "crate1",
+        TargetKind::Lib,
+        "/workspace/crate1/src/lib.rs",
+    )];
+
+    let dep_info = vec![dep_info::parse_dep_info(
+        "lib: crate1/src/lib.rs fixtures/data.json target/debug/build/crate1-1/out/gen.rs",
+        Path::new("/workspace"),
+    )];
+
+    let dependencies = get_file_dependencies(&metadata, &crates, &dep_info);
+
+    assert_eq!(
+        dependencies.0,
+        vec![(
+            "crate1".to_string(),
+            PathBuf::from("/workspace/fixtures/data.json")
+        )]
+    );
+}
+
+#[test]
+fn test_find_changed_crates_with_file_dependencies() {
+    let crates = Crates(HashSet::from([
+        create_test_crate("crate1", "/workspace/crate1"),
+        create_test_crate("crate2", "/workspace/crate2"),
+    ]));
+    let file_dependencies = FileDependencies(vec![
+        (
+            "crate1".to_string(),
+            PathBuf::from("/workspace/fixtures/data.json"),
+        ),
+        ("crate2".to_string(), PathBuf::from("/workspace/proto")),
+    ]);
+
+    let mappings = CrateMappings::default();
+
+    let result = find_changed_crates(
+        &[changed_file("/workspace/fixtures/data.json")],
+        &crates,
+        &file_dependencies,
+        &mappings,
+    )
+    .unwrap();
+    assert_eq!(result.len(), 1);
+    assert!(result.contains_key(&"crate1".to_string()));
+
+    // Directories watched by build scripts match any file within them
+    let result = find_changed_crates(
+        &[changed_file("/workspace/proto/api/service.proto")],
+        &crates,
+        &file_dependencies,
+        &mappings,
+    )
+    .unwrap();
+    assert_eq!(result.len(), 1);
+    assert!(result.contains_key(&"crate2".to_string()));
+}
+
+fn create_test_config(mappings: &[(&str, &[&str])]) -> Config {
+    let crates = mappings
+        .iter()
+        .map(|(name, patterns)| {
+            (
+                name.to_string(),
+                crate::config::CrateConfig {
+                    triggered_by: patterns.iter().map(|p| p.to_string()).collect(),
+                    ..Default::default()
+                },
+            )
+        })
+        .collect();
+
+    Config {
+        crates,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_find_changed_crates_with_mappings() {
+    let crates = Crates(HashSet::from([
+        create_test_crate("crate1", "/workspace/crate1"),
+        create_test_crate("crate2", "/workspace/crate2"),
+        create_test_crate("crate3", "/workspace/crate3"),
+    ]));
+    let metadata = create_test_metadata();
+    let config = create_test_config(&[
+        ("crate1", &["proto/**"]),
+        ("crate2", &["schemas/*.json", "proto/**"]),
+        ("crate3", &["schemas/*.json"]),
+    ]);
+    let mappings = CrateMappings::from_config(&config, &metadata, Path::new("/workspace")).unwrap();
+    let file_dependencies = FileDependencies::default();
+
+    let result = find_changed_crates(
+        &[
+            changed_file("/workspace/crate3/src/lib.rs"),
+            changed_file("/workspace/proto/v1/api.proto"),
+            changed_file("/workspace/schemas/nested/user.json"),
+        ],
+        &crates,
+        &file_dependencies,
+        &mappings,
+    )
+    .unwrap();
+
+    assert_eq!(result.len(), 3);
+    // A crate changed directly is reported as modified even if a pattern also matches
+    assert_eq!(result[&"crate3".to_string()], DiscoveryType::Modified);
+    assert_eq!(
+        result[&"crate1".to_string()],
+        DiscoveryType::Mapped {
+            pattern: "proto/**".to_string()
+        }
+    );
+    assert_eq!(
+        result[&"crate2".to_string()],
+        DiscoveryType::Mapped {
+            pattern: "proto/**".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_crate_mappings_validation() {
+    let metadata = create_test_metadata();
+
+    let unknown_crate = create_test_config(&[("missing", &["proto/**"])]);
+    assert!(matches!(
+        CrateMappings::from_config(&unknown_crate, &metadata, Path::new("/workspace")),
+        Err(AppError::UnknownCrate { .. })
+    ));
+
+    let invalid_pattern = create_test_config(&[("crate1", &["proto/[**"])]);
+    assert!(matches!(
+        CrateMappings::from_config(&invalid_pattern, &metadata, Path::new("/workspace")),
+        Err(AppError::InvalidConfig { .. })
+    ));
+}
+
+#[test]
+fn test_ignore_patterns_partition() {
+    let config = Config {
+        ignore: vec!["**/*.md".to_string(), "docs/**".to_string()],
+        ..Default::default()
+    };
+    let ignore_patterns = IgnorePatterns::from_config(&config, Path::new("/workspace")).unwrap();
+
+    let (kept, ignored) = ignore_patterns.partition(vec![
+        changed_file("/workspace/crate1/src/lib.rs"),
+        changed_file("/workspace/crate1/README.md"),
+        changed_file("/workspace/docs/guide/index.html"),
+    ]);
+
+    assert_eq!(kept.len(), 1);
+    assert_eq!(
+        kept[0].current_path,
+        PathBuf::from("/workspace/crate1/src/lib.rs")
+    );
+    assert_eq!(ignored.len(), 2);
+    assert_eq!(ignored[0].1, "**/*.md");
+    assert_eq!(ignored[1].1, "docs/**");
+}
+
+#[test]
+fn test_ignore_patterns_take_precedence_over_mappings() {
+    let crates = Crates(HashSet::from([
+        create_test_crate("crate1", "/workspace/crate1"),
+        create_test_crate("crate2", "/workspace/crate2"),
+    ]));
+    let metadata = create_test_metadata();
+    let mut config = create_test_config(&[("crate2", &["docs/**"])]);
+    config.ignore = vec!["**/*.md".to_string()];
+    let mappings = CrateMappings::from_config(&config, &metadata, Path::new("/workspace")).unwrap();
+    let ignore_patterns = IgnorePatterns::from_config(&config, Path::new("/workspace")).unwrap();
+    let file_dependencies = FileDependencies::default();
+
+    let (kept, ignored) = ignore_patterns.partition(vec![
+        changed_file("/workspace/crate1/README.md"),
+        changed_file("/workspace/docs/guide.md"),
+    ]);
+    let result = find_changed_crates(&kept, &crates, &file_dependencies, &mappings).unwrap();
+
+    // Ignored files select neither the crate containing them nor crates triggered by them
+    assert_eq!(ignored.len(), 2);
+    assert!(result.is_empty());
+
+    let (kept, _) = ignore_patterns.partition(vec![changed_file("/workspace/docs/schema.json")]);
+    let result = find_changed_crates(&kept, &crates, &file_dependencies, &mappings).unwrap();
+
+    assert_eq!(
+        result[&"crate2".to_string()],
+        DiscoveryType::Mapped {
+            pattern: "docs/**".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_find_dependency_path() {
+    let metadata = create_test_metadata();
+    let crate1_name = "crate1".to_string();
+    let changed_crates = IndexSet::from([&crate1_name]);
+
+    assert_eq!(
+        find_dependency_path("crate2", &changed_crates, &metadata),
+        Some(vec!["crate1".to_string(), "crate2".to_string()])
+    );
+    assert_eq!(
+        find_dependency_path("crate3", &changed_crates, &metadata),
+        None
+    );
+}
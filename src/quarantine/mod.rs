@@ -0,0 +1,212 @@
+use std::{fs, path::Path};
+
+use indexmap::{IndexMap, IndexSet};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::Config,
+    error::AppError,
+    history::RunRecord,
+    testing::{
+        cases::{TestCase, TestCaseStatus},
+        result::{TestOutcome, TestResult},
+    },
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Location of the quarantine file, relative to the workspace root, unless configured otherwise
+pub const DEFAULT_PATH: &str = ".config/test-quarantine.txt";
+
+/// Number of consecutive runs a quarantined entry has to pass before it is flagged for removal
+pub const REMOVAL_STREAK: usize = 5;
+
+/// Known-flaky crates and tests whose failures do not fail the run
+///
+/// The quarantine file lists one entry per line, either a crate name or a test given as
+/// `<crate>::<test name>`. Blank lines and lines starting with `#` are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Quarantine {
+    crates: IndexSet<String>,
+    tests: IndexMap<String, IndexSet<String>>,
+    /// Number of most recent runs in a row each entry passed in, from the run history
+    streaks: IndexMap<String, usize>,
+}
+
+/// Whether a quarantined entry passed in a run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct QuarantineOutcome {
+    pub entry: String,
+    pub passed: bool,
+}
+
+impl Quarantine {
+    /// Load the quarantine file named in the configuration, or from the default location
+    ///
+    /// A missing file at the default location is an empty quarantine.
+    pub fn from_config(config: &Config, workspace_root: &Path) -> Result<Self, AppError> {
+        let path = workspace_root.join(config.quarantine.as_deref().unwrap_or(DEFAULT_PATH));
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => Ok(Quarantine::parse(&contents)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && config.quarantine.is_none() => {
+                Ok(Quarantine::default())
+            }
+            Err(e) => Err(AppError::InvalidConfig {
+                reason: format!("failed to read quarantine file '{}': {}", path.display(), e),
+            }),
+        }
+    }
+
+    pub fn parse(contents: &str) -> Self {
+        let mut quarantine = Quarantine::default();
+
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            match line.split_once("::") {
+                Some((crate_name, test)) => {
+                    quarantine
+                        .tests
+                        .entry(crate_name.to_string())
+                        .or_default()
+                        .insert(test.to_string());
+                }
+                None => {
+                    quarantine.crates.insert(line.to_string());
+                }
+            }
+        }
+
+        quarantine
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.crates.is_empty() && self.tests.is_empty()
+    }
+
+    /// Whether every test in the crate is quarantined
+    pub fn contains_crate(&self, crate_name: &str) -> bool {
+        self.crates.contains(crate_name)
+    }
+
+    /// Tests quarantined individually within a crate
+    pub fn tests(&self, crate_name: &str) -> Vec<String> {
+        self.tests
+            .get(crate_name)
+            .map(|tests| tests.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn contains_test(&self, crate_name: &str, test: &str) -> bool {
+        self.contains_crate(crate_name)
+            || self
+                .tests
+                .get(crate_name)
+                .is_some_and(|tests| tests.contains(test))
+    }
+
+    /// Whether the failures among a crate's tests are all quarantined, so that running the
+    /// crate again would not change whether the run fails
+    pub fn covers_failures(&self, crate_name: &str, cases: &[TestCase]) -> bool {
+        if self.contains_crate(crate_name) {
+            return true;
+        }
+
+        let mut failures = cases
+            .iter()
+            .filter(|case| case.status == TestCaseStatus::Failed)
+            .peekable();
+
+        failures.peek().is_some() && failures.all(|case| self.contains_test(crate_name, &case.name))
+    }
+
+    /// Mark the quarantined failures of a crate, reporting the crate as quarantined rather
+    /// than failed when every failure is quarantined
+    pub fn apply(&self, result: &mut TestResult) {
+        if result.outcome != TestOutcome::Failed
+            || !self.covers_failures(&result.crate_name, &result.test_cases)
+        {
+            return;
+        }
+
+        for case in &mut result.test_cases {
+            if case.status == TestCaseStatus::Failed {
+                case.status = TestCaseStatus::Quarantined;
+            }
+        }
+        result.outcome = TestOutcome::Quarantined;
+    }
+
+    /// The quarantined entries a crate's result covers and whether each of them passed
+    pub fn outcomes(&self, result: &TestResult) -> Vec<QuarantineOutcome> {
+        let mut outcomes = Vec::new();
+
+        if self.contains_crate(&result.crate_name) {
+            outcomes.push(QuarantineOutcome {
+                entry: result.crate_name.clone(),
+                passed: result.outcome == TestOutcome::Passed,
+            });
+        }
+
+        for test in self.tests(&result.crate_name) {
+            if let Some(case) = result.test_cases.iter().find(|case| case.name == test) {
+                outcomes.push(QuarantineOutcome {
+                    entry: format!("{}::{}", result.crate_name, test),
+                    passed: case.status == TestCaseStatus::Passed,
+                });
+            }
+        }
+
+        outcomes
+    }
+
+    /// Count the runs in a row in which each entry passed, given recent runs newest first
+    ///
+    /// Runs that did not cover an entry are skipped.
+    pub fn with_history(mut self, runs: &[RunRecord]) -> Self {
+        let mut broken = IndexSet::new();
+
+        for outcome in runs
+            .iter()
+            .flat_map(|run| &run.crates)
+            .flat_map(|crate_outcome| &crate_outcome.quarantined)
+        {
+            if broken.contains(&outcome.entry) {
+                continue;
+            }
+
+            if outcome.passed {
+                *self.streaks.entry(outcome.entry.clone()).or_default() += 1;
+            } else {
+                broken.insert(outcome.entry.clone());
+            }
+        }
+
+        self
+    }
+
+    /// Entries that passed in this run and in enough previous runs to be removed from the
+    /// quarantine file
+    pub fn removable(&self, results: &[&TestResult]) -> Vec<String> {
+        results
+            .iter()
+            .flat_map(|result| self.outcomes(result))
+            .filter(|outcome| {
+                outcome.passed
+                    && self
+                        .streaks
+                        .get(&outcome.entry)
+                        .copied()
+                        .unwrap_or_default()
+                        + 1
+                        >= REMOVAL_STREAK
+            })
+            .map(|outcome| outcome.entry)
+            .collect()
+    }
+}
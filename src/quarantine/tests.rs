@@ -0,0 +1,206 @@
+use std::time::Duration;
+
+use super::*;
+use crate::history::CrateOutcome;
+use crate::testing::plan::RunMode;
+
+const QUARANTINE_FILE: &str = "
+# Known-flaky tests
+crate_a::tests::flaky_network
+
+crate_b
+";
+
+fn case(name: &str, status: TestCaseStatus) -> TestCase {
+    TestCase {
+        name: name.to_string(),
+        status,
+        duration: None,
+        stdout: None,
+    }
+}
+
+fn result(crate_name: &str, outcome: TestOutcome, test_cases: Vec<TestCase>) -> TestResult {
+    TestResult {
+        crate_name: crate_name.to_string(),
+        outcome,
+        output: String::new(),
+        duration: Duration::from_millis(100),
+        test_cases,
+        attempts: 1,
+        sub_results: Vec::new(),
+        diagnostics: Vec::new(),
+    }
+}
+
+fn run(quarantined: &[(&str, bool)]) -> RunRecord {
+    RunRecord {
+        started_at: 1,
+        head: None,
+        changed_files: Vec::new(),
+        mode: RunMode::Test,
+        planned: Vec::new(),
+        crates: vec![CrateOutcome {
+            crate_name: "crate_a".to_string(),
+            success: true,
+            flaky: false,
+            duration_ms: 100,
+            quarantined: quarantined
+                .iter()
+                .map(|(entry, passed)| QuarantineOutcome {
+                    entry: entry.to_string(),
+                    passed: *passed,
+                })
+                .collect(),
+        }],
+        duration_ms: 100,
+    }
+}
+
+#[test]
+fn test_parse() {
+    let quarantine = Quarantine::parse(QUARANTINE_FILE);
+
+    assert!(!quarantine.is_empty());
+    assert!(quarantine.contains_crate("crate_b"));
+    assert!(!quarantine.contains_crate("crate_a"));
+    assert_eq!(quarantine.tests("crate_a"), vec!["tests::flaky_network"]);
+    assert!(quarantine.tests("crate_b").is_empty());
+    assert!(Quarantine::parse("# nothing yet\n").is_empty());
+}
+
+#[test]
+fn test_from_config() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let mut config = Config::default();
+
+    assert!(Quarantine::from_config(&config, dir.path())
+        .unwrap()
+        .is_empty());
+
+    config.quarantine = Some("quarantine.txt".to_string());
+    assert!(matches!(
+        Quarantine::from_config(&config, dir.path()),
+        Err(AppError::InvalidConfig { .. })
+    ));
+
+    std::fs::write(dir.path().join("quarantine.txt"), QUARANTINE_FILE).unwrap();
+    assert_eq!(
+        Quarantine::from_config(&config, dir.path()).unwrap(),
+        Quarantine::parse(QUARANTINE_FILE)
+    );
+}
+
+#[test]
+fn test_apply_quarantined_failures() {
+    let quarantine = Quarantine::parse(QUARANTINE_FILE);
+    let mut failed = result(
+        "crate_a",
+        TestOutcome::Failed,
+        vec![
+            case("tests::works", TestCaseStatus::Passed),
+            case("tests::flaky_network", TestCaseStatus::Failed),
+        ],
+    );
+
+    quarantine.apply(&mut failed);
+
+    assert_eq!(failed.outcome, TestOutcome::Quarantined);
+    assert!(failed.success());
+    assert_eq!(failed.test_cases[1].status, TestCaseStatus::Quarantined);
+}
+
+#[test]
+fn test_apply_keeps_other_failures() {
+    let quarantine = Quarantine::parse(QUARANTINE_FILE);
+    let mut failed = result(
+        "crate_a",
+        TestOutcome::Failed,
+        vec![
+            case("tests::broken", TestCaseStatus::Failed),
+            case("tests::flaky_network", TestCaseStatus::Failed),
+        ],
+    );
+
+    quarantine.apply(&mut failed);
+
+    assert_eq!(failed.outcome, TestOutcome::Failed);
+    assert_eq!(failed.test_cases[1].status, TestCaseStatus::Failed);
+
+    // Without individual results only a quarantined crate is known to be covered
+    let mut no_cases = result("crate_a", TestOutcome::Failed, Vec::new());
+    quarantine.apply(&mut no_cases);
+    assert_eq!(no_cases.outcome, TestOutcome::Failed);
+
+    let mut crate_failed = result("crate_b", TestOutcome::Failed, Vec::new());
+    quarantine.apply(&mut crate_failed);
+    assert_eq!(crate_failed.outcome, TestOutcome::Quarantined);
+}
+
+#[test]
+fn test_outcomes() {
+    let quarantine = Quarantine::parse(QUARANTINE_FILE);
+
+    assert_eq!(
+        quarantine.outcomes(&result(
+            "crate_a",
+            TestOutcome::Passed,
+            vec![case("tests::flaky_network", TestCaseStatus::Passed)],
+        )),
+        vec![QuarantineOutcome {
+            entry: "crate_a::tests::flaky_network".to_string(),
+            passed: true,
+        }]
+    );
+    assert_eq!(
+        quarantine.outcomes(&result("crate_b", TestOutcome::Quarantined, Vec::new())),
+        vec![QuarantineOutcome {
+            entry: "crate_b".to_string(),
+            passed: false,
+        }]
+    );
+    assert!(quarantine
+        .outcomes(&result("crate_c", TestOutcome::Passed, Vec::new()))
+        .is_empty());
+}
+
+#[test]
+fn test_removable_after_passing_streak() {
+    let entry = "crate_a::tests::flaky_network";
+    let passing = result(
+        "crate_a",
+        TestOutcome::Passed,
+        vec![case("tests::flaky_network", TestCaseStatus::Passed)],
+    );
+
+    let runs = vec![run(&[(entry, true)]); REMOVAL_STREAK - 1];
+    let quarantine = Quarantine::parse(QUARANTINE_FILE).with_history(&runs);
+    assert_eq!(quarantine.removable(&[&passing]), vec![entry]);
+
+    // The streak ends at the most recent failure, skipping runs that did not cover the entry
+    let mut runs = vec![run(&[(entry, true)]); REMOVAL_STREAK - 2];
+    runs.push(run(&[]));
+    runs.push(run(&[(entry, false)]));
+    let quarantine = Quarantine::parse(QUARANTINE_FILE).with_history(&runs);
+    assert!(quarantine.removable(&[&passing]).is_empty());
+
+    let failing = result(
+        "crate_a",
+        TestOutcome::Quarantined,
+        vec![case("tests::flaky_network", TestCaseStatus::Quarantined)],
+    );
+    let runs = vec![run(&[(entry, true)]); REMOVAL_STREAK];
+    let quarantine = Quarantine::parse(QUARANTINE_FILE).with_history(&runs);
+    assert!(quarantine.removable(&[&failing]).is_empty());
+}
+
+#[test]
+fn test_history_entry_round_trip() {
+    let record = run(&[("crate_b", false)]);
+    let json = serde_json::to_string(&record).unwrap();
+
+    assert_eq!(serde_json::from_str::<RunRecord>(&json).unwrap(), record);
+    assert!(!serde_json::to_string(&run(&[]))
+        .unwrap()
+        .contains("quarantined"));
+}
@@ -0,0 +1,373 @@
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use super::Reporter;
+use crate::{
+    error::AppError,
+    hash::fnv1a,
+    testing::{plan::TestPlan, result::TestResult},
+    vcs::ChangedFile,
+};
+
+/// Default path of the GitLab code quality report
+pub const DEFAULT_CODE_QUALITY_REPORT: &str = "gl-code-quality-report.json";
+
+/// CI systems that failures can be annotated for
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationFormat {
+    /// Detect the CI system from its environment variables
+    Auto,
+    /// GitHub Actions `::error` workflow commands
+    Github,
+    /// GitLab code quality report
+    Gitlab,
+}
+
+impl AnnotationFormat {
+    /// Resolve `Auto` to the CI system the tool is running in, if any
+    pub fn resolve(self) -> Option<AnnotationFormat> {
+        match self {
+            AnnotationFormat::Auto => {
+                let is_set = |name| std::env::var(name).is_ok_and(|value| value == "true");
+
+                if is_set("GITHUB_ACTIONS") {
+                    Some(AnnotationFormat::Github)
+                } else if is_set("GITLAB_CI") {
+                    Some(AnnotationFormat::Gitlab)
+                } else {
+                    None
+                }
+            }
+            format => Some(format),
+        }
+    }
+}
+
+/// Where a test panicked, parsed from its output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailureLocation {
+    /// Name of the panicking thread, which libtest sets to the test name
+    pub test: Option<String>,
+    pub file: PathBuf,
+    pub line: u32,
+    pub column: Option<u32>,
+    pub message: String,
+}
+
+/// Find the locations of panics in test output, in both the current
+/// `thread 'name' panicked at src/lib.rs:10:9:` format followed by the message,
+/// and the older `thread 'name' panicked at 'message', src/lib.rs:10:9` format
+///
+/// Panics in files outside the workspace, such as the standard library, are skipped.
+pub fn parse_failure_locations(output: &str) -> Vec<FailureLocation> {
+    let mut locations = Vec::new();
+    let mut lines = output.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        let Some(rest) = line.trim_start().strip_prefix("thread '") else {
+            continue;
+        };
+        let Some((thread, rest)) = rest.split_once("' panicked at ") else {
+            continue;
+        };
+
+        let (location, mut message) = match rest.strip_suffix(':') {
+            Some(location) => (location, Vec::new()),
+            None => match rest
+                .strip_prefix('\'')
+                .and_then(|rest| rest.rsplit_once("', "))
+            {
+                Some((message, location)) => (location, vec![message]),
+                None => continue,
+            },
+        };
+
+        let Some((file, line, column)) = parse_location(location) else {
+            continue;
+        };
+
+        if message.is_empty() {
+            while let Some(next) = lines.peek() {
+                if is_message_end(next) {
+                    break;
+                }
+                message.push(next);
+                lines.next();
+            }
+        }
+
+        if file.is_absolute() {
+            continue;
+        }
+
+        locations.push(FailureLocation {
+            test: (thread != "<unnamed>").then(|| thread.to_string()),
+            file,
+            line,
+            column,
+            message: message.join("\n").trim_end().to_string(),
+        });
+    }
+
+    locations
+}
+
+/// Parse a `file:line:column` location
+fn parse_location(location: &str) -> Option<(PathBuf, u32, Option<u32>)> {
+    let mut parts = location.rsplitn(3, ':');
+    let last = parts.next()?.parse().ok()?;
+
+    match (parts.next(), parts.next()) {
+        (Some(line), Some(file)) => match line.parse() {
+            Ok(line) => Some((PathBuf::from(file), line, Some(last))),
+            Err(_) => None,
+        },
+        (Some(file), None) => Some((PathBuf::from(file), last, None)),
+        _ => None,
+    }
+}
+
+fn is_message_end(line: &str) -> bool {
+    line.trim().is_empty()
+        || line.starts_with("note: ")
+        || line.starts_with("stack backtrace:")
+        || line.starts_with("thread '")
+        || line.starts_with("---- ")
+}
+
+/// Escape data for a GitHub workflow command
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a property value for a GitHub workflow command
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Format the GitHub workflow commands that annotate a failed crate
+pub fn github_annotations(result: &TestResult) -> Vec<String> {
+    let locations = parse_failure_locations(&result.output);
+
+    if locations.is_empty() {
+        return vec![format!(
+            "::error title={}::{}",
+            escape_property(&format!("{} failed", result.crate_name)),
+            escape_data(&format!("tests failed in {}", result.crate_name))
+        )];
+    }
+
+    locations
+        .iter()
+        .map(|location| {
+            let title = match &location.test {
+                Some(test) => format!("{} {}", result.crate_name, test),
+                None => result.crate_name.clone(),
+            };
+            let column = location
+                .column
+                .map(|column| format!(",col={}", column))
+                .unwrap_or_default();
+            let message = if location.message.is_empty() {
+                "test panicked"
+            } else {
+                &location.message
+            };
+
+            format!(
+                "::error file={},line={}{},title={}::{}",
+                escape_property(&location.file.to_string_lossy()),
+                location.line,
+                column,
+                escape_property(&title),
+                escape_data(message)
+            )
+        })
+        .collect()
+}
+
+/// Reporter that prints GitHub Actions workflow commands for failing tests
+pub struct GithubReporter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> GithubReporter<W> {
+    pub fn new(writer: W) -> Self {
+        GithubReporter { writer }
+    }
+}
+
+impl<W: Write> Reporter for GithubReporter<W> {
+    fn note(&mut self, _: &str) {}
+
+    fn tip(&mut self, _: &str) {}
+
+    fn error(&mut self, _: &str) {}
+
+    fn changed_files(&mut self, _: &[ChangedFile], _: &Path) {}
+
+    fn test_start(&mut self, _: &str, _: usize, _: usize) {}
+
+    fn test_result(&mut self, result: &TestResult) {
+        if result.success() {
+            return;
+        }
+
+        for annotation in github_annotations(result) {
+            if let Err(e) = writeln!(self.writer, "{}", annotation) {
+                eprintln!("Write error: {}", e);
+            }
+        }
+
+        let _ = self.flush();
+    }
+
+    fn test_summary(&mut self, _: usize, _: usize, _: usize, _: usize, _: f64) {}
+
+    fn plan_summary(&mut self, _: &TestPlan) {}
+
+    fn test_failures(&mut self, _: &[TestResult]) {}
+
+    fn test_failure_details(&mut self, _: &str, _: &str) {}
+
+    fn no_tests(&mut self) {}
+
+    fn dry_run(&mut self) {}
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// A single issue in a GitLab code quality report
+#[derive(Debug, Serialize)]
+pub struct CodeQualityIssue {
+    pub description: String,
+    pub check_name: String,
+    pub fingerprint: String,
+    pub severity: &'static str,
+    pub location: CodeQualityLocation,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CodeQualityLocation {
+    pub path: String,
+    pub lines: CodeQualityLines,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CodeQualityLines {
+    pub begin: u32,
+}
+
+/// Build the code quality issues for a failed crate, skipping failures without a location
+pub fn code_quality_issues(result: &TestResult) -> Vec<CodeQualityIssue> {
+    parse_failure_locations(&result.output)
+        .into_iter()
+        .map(|location| {
+            let path = location.file.to_string_lossy().replace('\\', "/");
+            let test = location.test.as_deref().unwrap_or("<unnamed>");
+            let description = if location.message.is_empty() {
+                format!("{} {} panicked", result.crate_name, test)
+            } else {
+                format!("{} {}: {}", result.crate_name, test, location.message)
+            };
+
+            CodeQualityIssue {
+                description,
+                check_name: "cargo-test-changed".to_string(),
+                fingerprint: fingerprint(&format!(
+                    "{}\0{}\0{}\0{}",
+                    result.crate_name, test, path, location.line
+                )),
+                severity: "major",
+                location: CodeQualityLocation {
+                    path,
+                    lines: CodeQualityLines {
+                        begin: location.line,
+                    },
+                },
+            }
+        })
+        .collect()
+}
+
+/// A stable hash, so the same failure has the same fingerprint across runs
+fn fingerprint(text: &str) -> String {
+    format!("{:016x}", fnv1a(text.as_bytes()))
+}
+
+/// Reporter that writes failing tests as a GitLab code quality report once the run finishes
+pub struct GitlabReporter {
+    path: PathBuf,
+    issues: Vec<CodeQualityIssue>,
+    /// Why the report could not be written, returned by `flush`
+    write_error: Option<AppError>,
+}
+
+impl GitlabReporter {
+    pub fn new(path: PathBuf) -> Self {
+        GitlabReporter {
+            path,
+            issues: Vec::new(),
+            write_error: None,
+        }
+    }
+
+    fn write(&self) -> Result<(), AppError> {
+        let write_failed = |reason: String| AppError::WriteFailed {
+            path: self.path.display().to_string(),
+            reason,
+        };
+
+        let json =
+            serde_json::to_string_pretty(&self.issues).map_err(|e| write_failed(e.to_string()))?;
+        std::fs::write(&self.path, json).map_err(|e| write_failed(e.to_string()))
+    }
+}
+
+impl Reporter for GitlabReporter {
+    fn note(&mut self, _: &str) {}
+
+    fn tip(&mut self, _: &str) {}
+
+    fn error(&mut self, _: &str) {}
+
+    fn changed_files(&mut self, _: &[ChangedFile], _: &Path) {}
+
+    fn test_start(&mut self, _: &str, _: usize, _: usize) {}
+
+    fn test_result(&mut self, result: &TestResult) {
+        if !result.success() {
+            self.issues.extend(code_quality_issues(result));
+        }
+    }
+
+    fn test_summary(&mut self, _: usize, _: usize, _: usize, _: usize, _: f64) {
+        self.write_error = self.write().err();
+    }
+
+    fn plan_summary(&mut self, _: &TestPlan) {}
+
+    fn test_failures(&mut self, _: &[TestResult]) {}
+
+    fn test_failure_details(&mut self, _: &str, _: &str) {}
+
+    fn no_tests(&mut self) {}
+
+    fn dry_run(&mut self) {}
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.write_error.take() {
+            Some(e) => Err(io::Error::other(e)),
+            None => Ok(()),
+        }
+    }
+}
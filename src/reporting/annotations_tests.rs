@@ -0,0 +1,138 @@
+use std::{path::PathBuf, time::Duration};
+
+use crate::error::AppError;
+use crate::reporting::annotations::{
+    code_quality_issues, github_annotations, parse_failure_locations, FailureLocation,
+    GitlabReporter,
+};
+use crate::reporting::{finish, Reporter};
+use crate::testing::result::{TestOutcome, TestResult};
+
+fn failed_result(output: &str) -> TestResult {
+    TestResult {
+        crate_name: "crate_a".to_string(),
+        outcome: TestOutcome::Failed,
+        output: output.to_string(),
+        duration: Duration::from_millis(100),
+        test_cases: Vec::new(),
+        attempts: 1,
+        sub_results: Vec::new(),
+        diagnostics: Vec::new(),
+    }
+}
+
+#[test]
+fn test_parse_failure_locations() {
+    let output = "\
+running 3 tests
+test tests::fails ... FAILED
+test tests::old ... FAILED
+test tests::std ... FAILED
+
+failures:
+
+---- tests::fails stdout ----
+thread 'tests::fails' panicked at crates/a/src/lib.rs:10:9:
+assertion `left == right` failed
+  left: 1
+ right: 2
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+---- tests::old stdout ----
+thread 'tests::old' panicked at 'called `Option::unwrap()` on a `None` value', crates/a/src/lib.rs:20:5
+
+---- tests::std stdout ----
+thread 'tests::std' panicked at /rustc/abc123/library/core/src/option.rs:1010:5:
+explicit panic
+";
+
+    assert_eq!(
+        parse_failure_locations(output),
+        vec![
+            FailureLocation {
+                test: Some("tests::fails".to_string()),
+                file: PathBuf::from("crates/a/src/lib.rs"),
+                line: 10,
+                column: Some(9),
+                message: "assertion `left == right` failed\n  left: 1\n right: 2".to_string(),
+            },
+            FailureLocation {
+                test: Some("tests::old".to_string()),
+                file: PathBuf::from("crates/a/src/lib.rs"),
+                line: 20,
+                column: Some(5),
+                message: "called `Option::unwrap()` on a `None` value".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_github_annotations() {
+    let result = failed_result(
+        "thread 'tests::fails' panicked at crates/a/src/lib.rs:10:9:\nvalue was 50%, expected: 1\n",
+    );
+
+    assert_eq!(
+        github_annotations(&result),
+        vec![
+            "::error file=crates/a/src/lib.rs,line=10,col=9,title=crate_a tests%3A%3Afails::value was 50%25, expected: 1"
+        ]
+    );
+}
+
+#[test]
+fn test_github_annotation_without_location() {
+    let result = failed_result("error[E0308]: mismatched types\n");
+
+    assert_eq!(
+        github_annotations(&result),
+        vec!["::error title=crate_a failed::tests failed in crate_a"]
+    );
+}
+
+#[test]
+fn test_code_quality_issues() {
+    let result = failed_result(
+        "thread 'tests::fails' panicked at crates/a/src/lib.rs:10:9:\nexplicit panic\n",
+    );
+
+    let issues = serde_json::to_value(code_quality_issues(&result)).unwrap();
+    let fingerprint = issues[0]["fingerprint"].as_str().unwrap().to_string();
+
+    assert_eq!(fingerprint.len(), 16);
+    assert_eq!(
+        issues,
+        serde_json::json!([{
+            "description": "crate_a tests::fails: explicit panic",
+            "check_name": "cargo-test-changed",
+            "fingerprint": fingerprint,
+            "severity": "major",
+            "location": {
+                "path": "crates/a/src/lib.rs",
+                "lines": { "begin": 10 }
+            }
+        }])
+    );
+
+    // The fingerprint only depends on the failure, so GitLab can track it across pipelines
+    let again = serde_json::to_value(code_quality_issues(&result)).unwrap();
+    assert_eq!(again[0]["fingerprint"], fingerprint.as_str());
+}
+
+#[test]
+fn test_gitlab_reporter_fails_when_report_cannot_be_written() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("missing/gl-code-quality-report.json");
+    let mut reporter = GitlabReporter::new(path.clone());
+
+    reporter.test_result(&failed_result(
+        "thread 'tests::fails' panicked at crates/a/src/lib.rs:10:9:\nexplicit panic\n",
+    ));
+    reporter.test_summary(0, 1, 0, 0, 0.1);
+
+    assert!(matches!(
+        finish(&mut reporter),
+        Err(AppError::WriteFailed { path: failed, .. }) if failed == path.display().to_string()
+    ));
+}
@@ -1,31 +1,74 @@
 use crate::{
+    explain::{Exclusion, Explanation},
+    history::RunRecord,
+    metadata::ChangeOrigin,
     testing::{
-        plan::{DiscoveryType, TestCrates, TestPlan},
-        result::TestResult,
+        cases::TestCounts,
+        diagnostics,
+        filter::{TargetFilter, TestFilter, TestTarget},
+        plan::{DiscoveryType, RunMode, TestCrates, TestPlan},
+        result::{TestOutcome, TestResult},
+        shard::ShardAssignment,
     },
     vcs::ChangedFile,
+    watch::WatchStatus,
 };
 
-use super::{pluralize, Reporter};
+use super::{pluralize, strip_ansi, Reporter};
 use colored::Colorize;
 use std::{
     io::{self, Write},
     path::Path,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 pub struct ConsoleReporter<W: Write> {
     writer: W,
     verbose: bool,
+    /// Per-test counts of the crates run so far, shown in the summary
+    crate_counts: Vec<(String, TestCounts)>,
+    /// What is done with each crate, from the plan
+    mode: RunMode,
+    /// Whether the writer is a terminal, where status lines can be rewritten in place
+    terminal: bool,
+    /// Whether output keeps its colors, which files should not
+    colors: bool,
 }
 
 impl<W: Write> ConsoleReporter<W> {
     pub fn new(writer: W, verbose: bool) -> Self {
-        ConsoleReporter { writer, verbose }
+        ConsoleReporter {
+            writer,
+            verbose,
+            crate_counts: Vec::new(),
+            mode: RunMode::default(),
+            terminal: false,
+            colors: true,
+        }
+    }
+
+    /// Write output without colors or other escape sequences, including those in the runner's
+    /// output
+    pub fn without_colors(mut self) -> Self {
+        self.colors = false;
+        self
+    }
+
+    /// Rewrite status lines in place, for writers that are a terminal
+    pub fn with_terminal(mut self, terminal: bool) -> Self {
+        self.terminal = terminal;
+        self
     }
 
     /// Write formatted output to the console and handle errors
     fn write_fmt(&mut self, args: std::fmt::Arguments<'_>) -> io::Result<()> {
-        match self.writer.write_fmt(args) {
+        let written = if self.colors {
+            self.writer.write_fmt(args)
+        } else {
+            self.writer
+                .write_all(strip_ansi(&args.to_string()).as_bytes())
+        };
+        match written {
             Ok(_) => Ok(()),
             Err(e) => {
                 eprintln!("Write error: {}", e);
@@ -99,10 +142,11 @@ impl<W: Write> Reporter for ConsoleReporter<W> {
         let prefix = if self.verbose { "📦 " } else { "" };
 
         self.try_write(format_args!(
-            "{}{:width$}/{} test crate {}",
+            "{}{:width$}/{} {} crate {}",
             prefix,
             test_number,
             total_tests,
+            self.mode.verb(),
             crate_name,
             width = width
         ));
@@ -116,34 +160,138 @@ impl<W: Write> Reporter for ConsoleReporter<W> {
         let _ = self.flush();
     }
 
-    fn test_result(&mut self, _: &str, success: bool, _: u64) {
+    fn test_result(&mut self, result: &TestResult) {
+        if let Some(counts) = result.counts() {
+            self.crate_counts.push((result.crate_name.clone(), counts));
+        }
+
         if self.verbose {
             self.try_write(format_args!("\n"));
-        } else if success {
-            self.try_writeln(format_args!("{}", "ok".bold().green()));
-        } else {
-            self.try_writeln(format_args!("{}", "FAILED".bold().red()));
+            return;
+        }
+
+        match result.outcome {
+            TestOutcome::Passed => self.try_write(format_args!("{}", "ok".bold().green())),
+            TestOutcome::Failed => self.try_write(format_args!("{}", "FAILED".bold().red())),
+            TestOutcome::Flaky if result.attempts > 1 => self.try_write(format_args!(
+                "{} (passed on attempt {})",
+                "FLAKY".bold().yellow(),
+                result.attempts
+            )),
+            TestOutcome::Flaky => self.try_write(format_args!("{}", "FLAKY".bold().yellow())),
+            TestOutcome::Quarantined => {
+                self.try_write(format_args!("{}", "FAILED (quarantined)".bold().yellow()))
+            }
+        }
+
+        if !result.sub_results.is_empty() {
+            let parts = result
+                .sub_results
+                .iter()
+                .map(|sub_result| {
+                    let status = if sub_result.success {
+                        "ok".green()
+                    } else {
+                        "FAILED".red()
+                    };
+                    format!("{} {}", sub_result.kind.label(), status)
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            self.try_write(format_args!(" ({})", parts));
+        }
+
+        let summary = diagnostics::summarize(&result.diagnostics);
+        if !summary.is_empty() {
+            self.try_write(format_args!(" ({})", summary));
+        }
+        self.try_writeln(format_args!(""));
+
+        for diagnostic in &result.diagnostics {
+            let level = if diagnostic.is_error() {
+                diagnostic.level.bold().red()
+            } else {
+                diagnostic.level.bold().yellow()
+            };
+            let location = diagnostic
+                .location()
+                .map(|location| format!("{} ", location.dimmed()))
+                .unwrap_or_default();
+            let code = diagnostic
+                .code
+                .as_ref()
+                .map(|code| format!(" [{}]", code))
+                .unwrap_or_default();
+            self.try_writeln(format_args!(
+                "    {}{}: {}{}",
+                location, level, diagnostic.message, code
+            ));
         }
     }
 
-    fn test_summary(&mut self, passed: usize, failed: usize, duration_secs: f64) {
+    fn test_summary(
+        &mut self,
+        passed: usize,
+        failed: usize,
+        flaky: usize,
+        quarantined: usize,
+        duration_secs: f64,
+    ) {
         if !self.verbose {
             self.try_write(format_args!("\n"));
         }
 
+        if !self.crate_counts.is_empty() {
+            let width = self
+                .crate_counts
+                .iter()
+                .map(|(name, _)| name.len())
+                .max()
+                .unwrap_or_default();
+
+            for (crate_name, counts) in std::mem::take(&mut self.crate_counts) {
+                self.try_writeln(format_args!(
+                    "{:width$}  {}",
+                    crate_name,
+                    counts,
+                    width = width
+                ));
+            }
+            self.try_write(format_args!("\n"));
+        }
+
         let status = if failed == 0 {
             "ok".bold().green()
         } else {
             "FAILED".bold().red()
         };
 
+        let flaky = if flaky > 0 {
+            format!("{} flaky; ", flaky)
+        } else {
+            String::new()
+        };
+        let quarantined = if quarantined > 0 {
+            format!("{} quarantined; ", quarantined)
+        } else {
+            String::new()
+        };
+
         self.try_writeln(format_args!(
-            "test result: {}. {} passed; {} failed; finished in {:.2}s\n",
-            status, passed, failed, duration_secs
+            "{} result: {}. {} passed; {}{} failed; {}finished in {:.2}s\n",
+            self.mode.verb(),
+            status,
+            passed,
+            flaky,
+            failed,
+            quarantined,
+            duration_secs
         ));
     }
 
     fn plan_summary(&mut self, test_plan: &TestPlan) {
+        self.mode = test_plan.mode;
+
         match &test_plan.crates {
             TestCrates::Manual(crates) => {
                 let word = pluralize(crates.len(), "crate", "crates");
@@ -151,7 +299,7 @@ impl<W: Write> Reporter for ConsoleReporter<W> {
             }
             TestCrates::Discovered(crates) => {
                 let (modified, dependent) = crates.iter().partition::<Vec<_>, _>(|c| {
-                    matches!(c.discovery_type, DiscoveryType::Modified)
+                    !matches!(c.discovery_type, DiscoveryType::Dependent)
                 });
 
                 let (modified_count, dependent_count) = (modified.len(), dependent.len());
@@ -170,25 +318,73 @@ impl<W: Write> Reporter for ConsoleReporter<W> {
                     ));
                 }
 
+                if !test_plan.cached.is_empty() {
+                    self.try_write(format_args!("; {} cached", test_plan.cached.len()));
+                }
+
+                if !test_plan.shared_cached.is_empty() {
+                    self.try_write(format_args!(
+                        " ({} from the shared store)",
+                        test_plan.shared_cached.len()
+                    ));
+                }
+
                 if self.verbose {
                     self.try_writeln(format_args!(":"));
 
                     let test_crates = crates.iter().filter(|c| {
                         test_plan.with_dependents
-                            || matches!(c.discovery_type, DiscoveryType::Modified)
+                            || !matches!(c.discovery_type, DiscoveryType::Dependent)
                     });
 
                     for test_crate in test_crates {
-                        let symbol = match test_crate.discovery_type {
-                            DiscoveryType::Modified => "*".bold().yellow(),
-                            DiscoveryType::Dependent => ">".bold().red(),
+                        let (mut symbol, mut details) = match &test_crate.discovery_type {
+                            DiscoveryType::Modified => ("*".bold().yellow(), Vec::new()),
+                            DiscoveryType::Dependent => (">".bold().red(), Vec::new()),
+                            DiscoveryType::Mapped { pattern } => {
+                                ("~".bold().cyan(), vec![format!("via {}", pattern)])
+                            }
                         };
-                        self.try_writeln(format_args!("  {} {}", symbol, test_crate.name));
+
+                        if let Some(TestFilter::Targets { targets }) =
+                            test_plan.test_filters.get(&test_crate.name)
+                        {
+                            details.push(describe_targets(targets));
+                        }
+
+                        if test_plan.shared_cached.contains(&test_crate.name) {
+                            symbol = "=".bold().dimmed();
+                            details.push("cached in the shared store".to_string());
+                        } else if test_plan.cached.contains(&test_crate.name) {
+                            symbol = "=".bold().dimmed();
+                            details.push("cached".to_string());
+                        }
+
+                        if details.is_empty() {
+                            self.try_writeln(format_args!("  {} {}", symbol, test_crate.name));
+                        } else {
+                            self.try_writeln(format_args!(
+                                "  {} {} ({})",
+                                symbol,
+                                test_crate.name,
+                                details.join("; ")
+                            ));
+                        }
                     }
                 } else {
                     self.try_write(format_args!("\n"));
                 }
 
+                for (crate_name, filter) in &test_plan.test_filters {
+                    if let TestFilter::Crate { reason } = filter {
+                        self.note(&format!(
+                            "running all tests in {}, {}",
+                            crate_name.bold(),
+                            reason
+                        ));
+                    }
+                }
+
                 self.try_write(format_args!("\n"));
             }
         }
@@ -223,7 +419,251 @@ impl<W: Write> Reporter for ConsoleReporter<W> {
         self.note("dry run mode enabled, skipping actual tests");
     }
 
+    fn explain(&mut self, explanation: &Explanation) {
+        let name = explanation.crate_name.bold();
+
+        if explanation.in_plan {
+            self.try_writeln(format_args!("{} is in the test plan", name));
+        } else {
+            self.try_writeln(format_args!("{} is not in the test plan", name));
+        }
+
+        if !explanation.changes.is_empty() {
+            self.try_writeln(format_args!("  changed files:"));
+            for change in &explanation.changes {
+                match &change.origin {
+                    ChangeOrigin::Directory => {
+                        self.try_writeln(format_args!("    * {}", change.path.display()));
+                    }
+                    ChangeOrigin::FileDependency => {
+                        self.try_writeln(format_args!(
+                            "    * {} (file dependency)",
+                            change.path.display()
+                        ));
+                    }
+                    ChangeOrigin::Pattern(pattern) => {
+                        self.try_writeln(format_args!(
+                            "    ~ {} (via {})",
+                            change.path.display(),
+                            pattern
+                        ));
+                    }
+                }
+            }
+        }
+
+        if let Some(path) = &explanation.dependency_path {
+            self.try_writeln(format_args!(
+                "  depends on a changed crate: {}",
+                path.join(" -> ")
+            ));
+        }
+
+        match &explanation.exclusion {
+            Some(Exclusion::NotAffected) => {
+                self.try_writeln(format_args!(
+                    "  none of the changed files affect this crate"
+                ));
+            }
+            Some(Exclusion::DependentsNotIncluded) => {
+                self.tip(&format!(
+                    "dependents are not tested by default, pass `{}` to include them",
+                    "--with-dependents".bold().yellow()
+                ));
+            }
+            Some(Exclusion::Ignored {
+                files,
+                dependency_path,
+            }) => {
+                if let Some(path) = dependency_path {
+                    self.try_writeln(format_args!(
+                        "  depends on a crate whose changes were ignored: {}",
+                        path.join(" -> ")
+                    ));
+                }
+                self.try_writeln(format_args!("  changed files ignored by a pattern:"));
+                for file in files {
+                    self.try_writeln(format_args!(
+                        "    - {} (ignored by {})",
+                        file.path.display(),
+                        file.pattern
+                    ));
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn history(&mut self, runs: &[RunRecord]) {
+        if runs.is_empty() {
+            self.try_writeln(format_args!("no runs recorded yet"));
+            return;
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or_default();
+
+        for run in runs {
+            let status = if run.success() {
+                "ok".bold().green()
+            } else {
+                "FAILED".bold().red()
+            };
+            let head = run
+                .head
+                .as_deref()
+                .map(|head| &head[..head.len().min(8)])
+                .unwrap_or("-");
+            let crate_count = run.crates.len();
+
+            // Runs recorded before plans were kept only list the crates that were tested
+            let runners = run.runners();
+            let with = if runners.is_empty() {
+                String::new()
+            } else {
+                format!(" with {}", runners.join(", "))
+            };
+
+            self.try_writeln(format_args!(
+                "{:>16}  {:8}  {:6}  {} {} {} in {:.2}s{}",
+                format_age(now.saturating_sub(run.started_at)),
+                head,
+                status,
+                run.mode.verb(),
+                crate_count,
+                pluralize(crate_count, "crate", "crates"),
+                run.duration_ms as f64 / 1000.0,
+                with
+            ));
+
+            let failed = run.failed_crates();
+            if !failed.is_empty() {
+                self.try_writeln(format_args!("{:>16}  failed: {}", "", failed.join(", ")));
+            }
+
+            let skipped = run
+                .skipped_crates()
+                .into_iter()
+                .map(|(crate_name, reason)| format!("{} ({})", crate_name, reason.description()))
+                .collect::<Vec<_>>();
+            if !skipped.is_empty() {
+                self.try_writeln(format_args!("{:>16}  not run: {}", "", skipped.join(", ")));
+            }
+        }
+    }
+
+    fn watch_status(&mut self, status: &WatchStatus) {
+        // Clear the status line left by a previous `Watching` status
+        if self.terminal {
+            self.try_write(format_args!("\r\x1b[2K"));
+        }
+
+        match status {
+            WatchStatus::Watching { last_run_passed } => {
+                let last_run = match last_run_passed {
+                    Some(true) => format!(" (last run {})", "ok".green()),
+                    Some(false) => format!(" (last run {})", "FAILED".red()),
+                    None => String::new(),
+                };
+                let status = format!("{}{}", "watching for changes...".dimmed(), last_run);
+                if self.terminal {
+                    self.try_write(format_args!("{}", status));
+                    let _ = self.writer.flush();
+                } else {
+                    self.try_writeln(format_args!("{}", status));
+                }
+            }
+            WatchStatus::Changed { files, crates } => {
+                let changed = format!(
+                    "{} changed {}",
+                    files.len(),
+                    pluralize(files.len(), "file", "files")
+                );
+                if crates.is_empty() {
+                    self.try_writeln(format_args!("{}, no crates affected", changed));
+                } else {
+                    self.try_writeln(format_args!("{}, testing {}", changed, crates.join(", ")));
+                }
+            }
+            WatchStatus::Cancelled => {
+                // The restarted run reports its crates again
+                self.crate_counts.clear();
+                self.note("files changed during the run, restarting");
+            }
+        }
+    }
+
+    fn shards(&mut self, shards: &[ShardAssignment], current: usize) {
+        for shard in shards {
+            let crate_count = shard.crates.len();
+            let mut header = format!(
+                "shard {}/{}: {} {}",
+                shard.index,
+                shards.len(),
+                crate_count,
+                pluralize(crate_count, "crate", "crates")
+            );
+            if let Some(duration_ms) = shard.estimated_duration_ms {
+                header.push_str(&format!(", estimated {:.2}s", duration_ms as f64 / 1000.0));
+            }
+
+            if shard.index == current {
+                self.try_writeln(format_args!("{} (this job)", header.bold()));
+            } else {
+                self.try_writeln(format_args!("{}", header));
+            }
+
+            for name in &shard.crates {
+                self.try_writeln(format_args!("    {}", name));
+            }
+        }
+    }
+
     fn flush(&mut self) -> io::Result<()> {
         self.writer.flush()
     }
 }
+
+/// Describe how long ago something happened, given the elapsed milliseconds
+fn format_age(elapsed_ms: u64) -> String {
+    let secs = elapsed_ms / 1000;
+    let (count, unit) = match secs {
+        0..60 => return "just now".to_string(),
+        60..3600 => (secs / 60, "minute"),
+        3600..86400 => (secs / 3600, "hour"),
+        _ => (secs / 86400, "day"),
+    };
+
+    format!(
+        "{} {} ago",
+        count,
+        pluralize(count as usize, unit, &format!("{}s", unit))
+    )
+}
+
+/// Describe the targets and modules a filtered crate will run
+fn describe_targets(targets: &[TargetFilter]) -> String {
+    targets
+        .iter()
+        .map(|filter| {
+            let target = match &filter.target {
+                TestTarget::Lib => "lib".to_string(),
+                TestTarget::Bin(name) => format!("bin {}", name),
+                TestTarget::Test(name) => format!("test {}", name),
+                TestTarget::Doc => "doc-tests".to_string(),
+            };
+
+            match &filter.modules {
+                Some(modules) => format!(
+                    "{}: {}",
+                    target,
+                    modules.iter().cloned().collect::<Vec<_>>().join(", ")
+                ),
+                None => target,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -0,0 +1,161 @@
+use indexmap::{IndexMap, IndexSet};
+use schemars::{JsonSchema, Schema};
+use serde::Serialize;
+
+use crate::{
+    explain::Explanation,
+    history::RunRecord,
+    testing::{
+        cases::{TestCase, TestCaseStatus, TestCounts},
+        diagnostics::Diagnostic,
+        filter::TestFilter,
+        plan::{DiscoveredTestCrate, ManualTestCrate, RunMode},
+        result::{SubResult, TestOutcome},
+        shard::ShardAssignment,
+    },
+    vcs::ChangedFile,
+    watch::WatchStatus,
+};
+
+/// Version of the JSON event format, bumped whenever an existing field changes or is removed
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A single line of JSON output
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct JsonEvent<'a> {
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub event: Event<'a>,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+impl<'a> JsonEvent<'a> {
+    pub fn new(event: Event<'a>, timestamp: u64) -> Self {
+        JsonEvent {
+            schema_version: SCHEMA_VERSION,
+            event,
+            timestamp,
+        }
+    }
+}
+
+/// Events emitted by the JSON reporter, tagged by `event_type` with their data in `payload`
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(tag = "event_type", content = "payload", rename_all = "snake_case")]
+pub enum Event<'a> {
+    Note {
+        message: &'a str,
+    },
+    Tip {
+        message: &'a str,
+    },
+    Error {
+        message: &'a str,
+    },
+    ChangedFiles {
+        files: &'a [ChangedFile],
+    },
+    TestStart {
+        #[serde(rename = "crate")]
+        crate_name: &'a str,
+        test_number: usize,
+        total_tests: usize,
+    },
+    TestResult {
+        #[serde(rename = "crate")]
+        crate_name: &'a str,
+        /// Whether the crate passed, including crates that only passed when retried
+        success: bool,
+        outcome: TestOutcome,
+        /// Number of times the crate was run, including retries
+        attempts: usize,
+        duration_ms: u64,
+        /// Number of tests by outcome, or null if the runner's output could not be parsed
+        tests: Option<TestCounts>,
+        test_cases: Vec<TestCaseRecord<'a>>,
+        /// Results of the runner's tests and doc-tests, when doc-tests are run separately
+        #[serde(skip_serializing_if = "<[_]>::is_empty")]
+        sub_results: &'a [SubResult],
+        /// Compiler and lint diagnostics, for modes that report them
+        #[serde(skip_serializing_if = "<[_]>::is_empty")]
+        diagnostics: &'a [Diagnostic],
+    },
+    TestSummary {
+        /// Crates that passed on the first attempt
+        passed: usize,
+        failed: usize,
+        /// Crates that failed at first, then passed when retried
+        flaky: usize,
+        /// Crates that only failed in quarantined tests
+        quarantined: usize,
+        duration_secs: f64,
+    },
+    PlanSummary(PlanSummary<'a>),
+    TestFailure {
+        #[serde(rename = "crate")]
+        crate_name: &'a str,
+        output: &'a str,
+    },
+    NoTests {},
+    DryRun {},
+    Explain(&'a Explanation),
+    History {
+        /// Recent runs, newest first
+        runs: &'a [RunRecord],
+    },
+    Shards {
+        /// Shard of the job that printed the assignment, starting at 1
+        current: usize,
+        shards: &'a [ShardAssignment],
+    },
+    WatchStatus(&'a WatchStatus),
+}
+
+/// A single test within a crate
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TestCaseRecord<'a> {
+    pub name: &'a str,
+    pub status: &'a TestCaseStatus,
+    pub duration_ms: Option<u64>,
+    pub stdout: Option<&'a str>,
+}
+
+impl<'a> From<&'a TestCase> for TestCaseRecord<'a> {
+    fn from(case: &'a TestCase) -> Self {
+        TestCaseRecord {
+            name: &case.name,
+            status: &case.status,
+            duration_ms: case.duration.map(|duration| duration.as_millis() as u64),
+            stdout: case.stdout.as_deref(),
+        }
+    }
+}
+
+/// The crates that are about to be tested
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(tag = "run_type", rename_all = "snake_case")]
+pub enum PlanSummary<'a> {
+    /// Crates given explicitly with `--crates`
+    Manual {
+        mode: RunMode,
+        crates: &'a IndexSet<ManualTestCrate>,
+    },
+    /// Crates selected from the changed files
+    Discovered {
+        /// Whether the crates are tested or only compiled
+        mode: RunMode,
+        with_dependents: bool,
+        crates: &'a IndexSet<DiscoveredTestCrate>,
+        test_filters: &'a IndexMap<String, TestFilter>,
+        /// Crates skipped because their passing result is cached
+        cached: &'a IndexSet<String>,
+        /// Crates among `cached` whose passing result came from the shared result store
+        shared_cached: &'a IndexSet<String>,
+    },
+}
+
+/// The JSON Schema describing every line of JSON output
+pub fn json_schema() -> Schema {
+    schemars::schema_for!(JsonEvent<'static>)
+}
@@ -0,0 +1,309 @@
+use std::path::PathBuf;
+
+use indexmap::{IndexMap, IndexSet};
+use insta::assert_json_snapshot;
+
+use crate::explain::{Exclusion, Explanation};
+use crate::history::{CrateOutcome, PlannedCrate, RunRecord, SkipReason};
+use crate::reporting::events::{json_schema, Event, JsonEvent, PlanSummary, TestCaseRecord};
+use crate::testing::{
+    cases::{TestCaseStatus, TestCounts},
+    diagnostics::Diagnostic,
+    filter::{FallbackReason, TargetFilter, TestFilter, TestTarget},
+    plan::{DiscoveredTestCrate, DiscoveryType, ManualTestCrate, RunMode},
+    result::{SubResult, SubResultKind, TestOutcome},
+    shard::ShardAssignment,
+};
+use crate::vcs::{ChangeType, ChangedFile, FileType};
+
+fn event(event: Event) -> JsonEvent {
+    JsonEvent::new(event, 1_700_000_000_000)
+}
+
+#[test]
+fn test_message_events() {
+    assert_json_snapshot!("note", event(Event::Note { message: "a note" }));
+    assert_json_snapshot!("tip", event(Event::Tip { message: "a tip" }));
+    assert_json_snapshot!(
+        "error",
+        event(Event::Error {
+            message: "an error"
+        })
+    );
+}
+
+#[test]
+fn test_changed_files_event() {
+    let files = [
+        ChangedFile {
+            current_path: PathBuf::from("crates/a/src/lib.rs"),
+            old_path: None,
+            file_type: FileType::File,
+            change_type: ChangeType::Modified,
+        },
+        ChangedFile {
+            current_path: PathBuf::from("crates/b/src/new.rs"),
+            old_path: Some(PathBuf::from("crates/b/src/old.rs")),
+            file_type: FileType::File,
+            change_type: ChangeType::Added,
+        },
+    ];
+
+    assert_json_snapshot!(
+        "changed_files",
+        event(Event::ChangedFiles { files: &files })
+    );
+}
+
+#[test]
+fn test_test_events() {
+    assert_json_snapshot!(
+        "test_start",
+        event(Event::TestStart {
+            crate_name: "crate_a",
+            test_number: 1,
+            total_tests: 2,
+        })
+    );
+    assert_json_snapshot!(
+        "test_result",
+        event(Event::TestResult {
+            crate_name: "crate_a",
+            success: false,
+            outcome: TestOutcome::Failed,
+            attempts: 1,
+            duration_ms: 1250,
+            tests: Some(TestCounts {
+                passed: 1,
+                failed: 1,
+                ignored: 0,
+                flaky: 0,
+                quarantined: 0,
+            }),
+            test_cases: vec![
+                TestCaseRecord {
+                    name: "tests::it_works",
+                    status: &TestCaseStatus::Passed,
+                    duration_ms: Some(4),
+                    stdout: None,
+                },
+                TestCaseRecord {
+                    name: "tests::fails",
+                    status: &TestCaseStatus::Failed,
+                    duration_ms: Some(10),
+                    stdout: Some("assertion failed"),
+                },
+            ],
+            sub_results: &[],
+            diagnostics: &[],
+        })
+    );
+    assert_json_snapshot!(
+        "test_result_with_doc_tests",
+        event(Event::TestResult {
+            crate_name: "crate_a",
+            success: false,
+            outcome: TestOutcome::Failed,
+            attempts: 1,
+            duration_ms: 1250,
+            tests: None,
+            test_cases: Vec::new(),
+            sub_results: &[
+                SubResult {
+                    kind: SubResultKind::Tests,
+                    success: true,
+                    duration_ms: 900,
+                    tests: None,
+                },
+                SubResult {
+                    kind: SubResultKind::DocTests,
+                    success: false,
+                    duration_ms: 350,
+                    tests: None,
+                },
+            ],
+            diagnostics: &[],
+        })
+    );
+    assert_json_snapshot!(
+        "test_result_with_diagnostics",
+        event(Event::TestResult {
+            crate_name: "crate_a",
+            success: true,
+            outcome: TestOutcome::Passed,
+            attempts: 1,
+            duration_ms: 800,
+            tests: None,
+            test_cases: Vec::new(),
+            sub_results: &[],
+            diagnostics: &[Diagnostic {
+                level: "warning".to_string(),
+                code: Some("clippy::needless_return".to_string()),
+                message: "unneeded `return` statement".to_string(),
+                file: Some(PathBuf::from("crates/a/src/lib.rs")),
+                line: Some(12),
+                column: Some(5),
+                rendered: String::new(),
+            }],
+        })
+    );
+    assert_json_snapshot!(
+        "test_failure",
+        event(Event::TestFailure {
+            crate_name: "crate_a",
+            output: "thread 'tests::it_works' panicked",
+        })
+    );
+    assert_json_snapshot!(
+        "test_summary",
+        event(Event::TestSummary {
+            passed: 1,
+            failed: 1,
+            flaky: 0,
+            quarantined: 0,
+            duration_secs: 2.5,
+        })
+    );
+    assert_json_snapshot!("no_tests", event(Event::NoTests {}));
+    assert_json_snapshot!("dry_run", event(Event::DryRun {}));
+}
+
+#[test]
+fn test_manual_plan_summary_event() {
+    let crates = IndexSet::from([ManualTestCrate {
+        name: "crate_a".to_string(),
+    }]);
+
+    assert_json_snapshot!(
+        "manual_plan_summary",
+        event(Event::PlanSummary(PlanSummary::Manual {
+            mode: RunMode::Test,
+            crates: &crates,
+        }))
+    );
+}
+
+#[test]
+fn test_discovered_plan_summary_event() {
+    let crates = IndexSet::from([
+        DiscoveredTestCrate {
+            name: "crate_a".to_string(),
+            discovery_type: DiscoveryType::Modified,
+        },
+        DiscoveredTestCrate {
+            name: "crate_b".to_string(),
+            discovery_type: DiscoveryType::Mapped {
+                pattern: "schemas/**".to_string(),
+            },
+        },
+        DiscoveredTestCrate {
+            name: "crate_c".to_string(),
+            discovery_type: DiscoveryType::Dependent,
+        },
+    ]);
+    let test_filters = IndexMap::from([
+        (
+            "crate_a".to_string(),
+            TestFilter::Targets {
+                targets: vec![TargetFilter {
+                    target: TestTarget::Lib,
+                    modules: Some(IndexSet::from(["parser".to_string()])),
+                }],
+            },
+        ),
+        (
+            "crate_b".to_string(),
+            TestFilter::Crate {
+                reason: FallbackReason::NonSourceFile(PathBuf::from("schemas/a.json")),
+            },
+        ),
+    ]);
+    let cached = IndexSet::from(["crate_c".to_string()]);
+    let shared_cached = IndexSet::from(["crate_c".to_string()]);
+
+    assert_json_snapshot!(
+        "discovered_plan_summary",
+        event(Event::PlanSummary(PlanSummary::Discovered {
+            mode: RunMode::Check,
+            with_dependents: true,
+            crates: &crates,
+            test_filters: &test_filters,
+            cached: &cached,
+            shared_cached: &shared_cached,
+        }))
+    );
+}
+
+#[test]
+fn test_explain_event() {
+    let explanation = Explanation {
+        crate_name: "crate_c".to_string(),
+        in_plan: false,
+        changes: Vec::new(),
+        dependency_path: Some(vec!["crate_a".to_string(), "crate_c".to_string()]),
+        exclusion: Some(Exclusion::DependentsNotIncluded),
+    };
+
+    assert_json_snapshot!("explain", event(Event::Explain(&explanation)));
+}
+
+#[test]
+fn test_shards_event() {
+    let shards = [
+        ShardAssignment {
+            index: 1,
+            crates: vec!["crate_a".to_string(), "crate_c".to_string()],
+            estimated_duration_ms: Some(3000),
+        },
+        ShardAssignment {
+            index: 2,
+            crates: vec!["crate_b".to_string()],
+            estimated_duration_ms: Some(2500),
+        },
+    ];
+
+    assert_json_snapshot!(
+        "shards",
+        event(Event::Shards {
+            current: 2,
+            shards: &shards,
+        })
+    );
+}
+
+#[test]
+fn test_json_schema() {
+    assert_json_snapshot!("json_schema", json_schema());
+}
+
+#[test]
+fn test_history_event() {
+    let runs = [RunRecord {
+        started_at: 1_700_000_000_000,
+        head: Some("0123456789abcdef0123456789abcdef01234567".to_string()),
+        changed_files: vec![PathBuf::from("crates/a/src/lib.rs")],
+        mode: RunMode::Test,
+        planned: vec![
+            PlannedCrate {
+                crate_name: "crate_a".to_string(),
+                runner: "nextest".to_string(),
+                skipped: None,
+            },
+            PlannedCrate {
+                crate_name: "crate_b".to_string(),
+                runner: "nextest".to_string(),
+                skipped: Some(SkipReason::FailFast),
+            },
+        ],
+        crates: vec![CrateOutcome {
+            crate_name: "crate_a".to_string(),
+            success: false,
+            flaky: false,
+            duration_ms: 1250,
+            quarantined: Vec::new(),
+        }],
+        duration_ms: 1300,
+    }];
+
+    assert_json_snapshot!("history", event(Event::History { runs: &runs }));
+}
@@ -1,25 +1,24 @@
 use crate::{
+    explain::Explanation,
+    history::RunRecord,
     testing::{
         plan::{TestCrates, TestPlan},
         result::TestResult,
+        shard::ShardAssignment,
     },
     vcs::ChangedFile,
+    watch::WatchStatus,
 };
 
-use super::Reporter;
-use serde::Serialize;
+use super::{
+    events::{Event, JsonEvent, PlanSummary},
+    Reporter,
+};
 use std::{
     io::{self, Write},
     path::Path,
 };
 
-#[derive(Serialize)]
-struct JsonEvent {
-    event_type: String,
-    payload: serde_json::Value,
-    timestamp: u128,
-}
-
 pub struct JsonReporter<W: Write> {
     writer: W,
 }
@@ -30,22 +29,16 @@ impl<W: Write> JsonReporter<W> {
     }
 
     /// Helper method to safely emit an event, handling all potential errors
-    fn emit_event(&mut self, event_type: &str, payload: serde_json::Value) {
+    fn emit_event(&mut self, event: Event) {
         let timestamp = match std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
-            Ok(duration) => duration.as_millis(),
+            Ok(duration) => duration.as_millis() as u64,
             Err(e) => {
                 eprintln!("Error getting system time: {}", e);
                 0 // Fallback to 0 on error
             }
         };
 
-        let event = JsonEvent {
-            event_type: event_type.to_string(),
-            payload,
-            timestamp,
-        };
-
-        let json_string = match serde_json::to_string(&event) {
+        let json_string = match serde_json::to_string(&JsonEvent::new(event, timestamp)) {
             Ok(json) => json,
             Err(e) => {
                 eprintln!("JSON serialization error: {}", e);
@@ -63,79 +56,79 @@ impl<W: Write> JsonReporter<W> {
 
 impl<W: Write> Reporter for JsonReporter<W> {
     fn note(&mut self, message: &str) {
-        self.emit_event("note", serde_json::json!({ "message": message }));
+        self.emit_event(Event::Note { message });
     }
 
     fn tip(&mut self, message: &str) {
-        self.emit_event("tip", serde_json::json!({ "message": message }));
+        self.emit_event(Event::Tip { message });
     }
 
     fn error(&mut self, message: &str) {
-        self.emit_event("error", serde_json::json!({ "message": message }));
+        self.emit_event(Event::Error { message });
     }
 
     fn changed_files(&mut self, changed_files: &[ChangedFile], _: &Path) {
-        self.emit_event(
-            "changed_files",
-            serde_json::json!({ "files": changed_files }),
-        );
+        self.emit_event(Event::ChangedFiles {
+            files: changed_files,
+        });
     }
 
     fn test_start(&mut self, crate_name: &str, test_number: usize, total_tests: usize) {
-        self.emit_event(
-            "test_start",
-            serde_json::json!({
-                "crate": crate_name,
-                "test_number": test_number,
-                "total_tests": total_tests
-            }),
-        );
-    }
-
-    fn test_result(&mut self, crate_name: &str, success: bool, duration_ms: u64) {
-        self.emit_event(
-            "test_result",
-            serde_json::json!({
-                "crate": crate_name,
-                "success": success,
-                "duration_ms": duration_ms
-            }),
-        );
-    }
-
-    fn test_summary(&mut self, passed: usize, failed: usize, duration_secs: f64) {
-        self.emit_event(
-            "test_summary",
-            serde_json::json!({
-                "passed": passed,
-                "failed": failed,
-                "duration_secs": duration_secs
-            }),
-        );
+        self.emit_event(Event::TestStart {
+            crate_name,
+            test_number,
+            total_tests,
+        });
+    }
+
+    fn test_result(&mut self, result: &TestResult) {
+        self.emit_event(Event::TestResult {
+            crate_name: &result.crate_name,
+            success: result.success(),
+            outcome: result.outcome,
+            attempts: result.attempts,
+            duration_ms: result.duration.as_millis() as u64,
+            tests: result.counts(),
+            test_cases: result.test_cases.iter().map(Into::into).collect(),
+            sub_results: &result.sub_results,
+            diagnostics: &result.diagnostics,
+        });
+    }
+
+    fn test_summary(
+        &mut self,
+        passed: usize,
+        failed: usize,
+        flaky: usize,
+        quarantined: usize,
+        duration_secs: f64,
+    ) {
+        self.emit_event(Event::TestSummary {
+            passed,
+            failed,
+            flaky,
+            quarantined,
+            duration_secs,
+        });
     }
 
     fn plan_summary(&mut self, test_plan: &TestPlan) {
-        match &test_plan.crates {
-            TestCrates::Manual(crates) => {
-                self.emit_event(
-                    "plan_summary",
-                    serde_json::json!({
-                        "run_type": "manual",
-                        "crates": crates,
-                    }),
-                );
-            }
-            TestCrates::Discovered(crates) => {
-                self.emit_event(
-                    "plan_summary",
-                    serde_json::json!({
-                        "run_type": "discovered",
-                        "with_dependents": test_plan.with_dependents,
-                        "crates": crates,
-                    }),
-                );
-            }
-        }
+        let summary = match &test_plan.crates {
+            TestCrates::Manual(crates) => PlanSummary::Manual {
+                mode: test_plan.mode,
+                crates,
+            },
+            TestCrates::Discovered(crates) => PlanSummary::Discovered {
+                mode: test_plan.mode,
+                with_dependents: test_plan.with_dependents,
+                crates,
+                test_filters: &test_plan.test_filters,
+                cached: &test_plan.cached,
+                shared_cached: &test_plan.shared_cached,
+            },
+        };
+
+        self.emit_event(Event::PlanSummary(summary));
     }
 
     fn test_failures(&mut self, failures: &[TestResult]) {
@@ -145,21 +138,31 @@ impl<W: Write> Reporter for JsonReporter<W> {
     }
 
     fn test_failure_details(&mut self, crate_name: &str, output: &str) {
-        self.emit_event(
-            "test_failure",
-            serde_json::json!({
-                "crate": crate_name,
-                "output": output
-            }),
-        );
+        self.emit_event(Event::TestFailure { crate_name, output });
     }
 
     fn no_tests(&mut self) {
-        self.emit_event("no_tests", serde_json::json!({}));
+        self.emit_event(Event::NoTests {});
     }
 
     fn dry_run(&mut self) {
-        self.emit_event("dry_run", serde_json::json!({}));
+        self.emit_event(Event::DryRun {});
+    }
+
+    fn explain(&mut self, explanation: &Explanation) {
+        self.emit_event(Event::Explain(explanation));
+    }
+
+    fn history(&mut self, runs: &[RunRecord]) {
+        self.emit_event(Event::History { runs });
+    }
+
+    fn shards(&mut self, shards: &[ShardAssignment], current: usize) {
+        self.emit_event(Event::Shards { current, shards });
+    }
+
+    fn watch_status(&mut self, status: &WatchStatus) {
+        self.emit_event(Event::WatchStatus(status));
     }
 
     fn flush(&mut self) -> io::Result<()> {
@@ -0,0 +1,305 @@
+use std::{
+    fmt::Write,
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use super::{strip_ansi, Reporter};
+use crate::{
+    error::AppError,
+    testing::{
+        cases::{TestCase, TestCaseStatus},
+        plan::TestPlan,
+        result::{TestOutcome, TestResult},
+    },
+    vcs::ChangedFile,
+};
+
+/// A JUnit XML report with one test suite per crate
+#[derive(Debug, Default)]
+pub struct JunitReport {
+    suites: Vec<TestSuite>,
+}
+
+#[derive(Debug)]
+struct TestSuite {
+    name: String,
+    duration: Duration,
+    outcome: TestOutcome,
+    output: String,
+    test_cases: Vec<TestCase>,
+}
+
+impl JunitReport {
+    pub fn new() -> Self {
+        JunitReport::default()
+    }
+
+    /// Add a test suite for a crate's result
+    pub fn add_result(&mut self, result: &TestResult) {
+        self.suites.push(TestSuite {
+            name: result.crate_name.clone(),
+            duration: result.duration,
+            outcome: result.outcome,
+            output: result.output.clone(),
+            test_cases: result.test_cases.clone(),
+        });
+    }
+
+    /// Render the report as a JUnit XML document
+    pub fn render(&self) -> String {
+        let (tests, failures) = self
+            .suites
+            .iter()
+            .map(TestSuite::counts)
+            .fold((0, 0), |(tests, failures), (t, f)| {
+                (tests + t, failures + f)
+            });
+        let duration: Duration = self.suites.iter().map(|suite| suite.duration).sum();
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuites name=\"cargo-test-changed\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">",
+            tests,
+            failures,
+            duration.as_secs_f64()
+        );
+
+        for suite in &self.suites {
+            suite.render(&mut xml);
+        }
+
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+
+    /// Write the report to a file
+    pub fn write(&self, path: &Path) -> Result<(), AppError> {
+        std::fs::write(path, self.render()).map_err(|e| AppError::WriteFailed {
+            path: path.display().to_string(),
+            reason: e.to_string(),
+        })
+    }
+}
+
+/// Reporter that collects crate results and writes them as JUnit XML once the run finishes
+pub struct JunitReporter {
+    path: PathBuf,
+    report: JunitReport,
+    /// Why the report could not be written, returned by `flush`
+    write_error: Option<AppError>,
+}
+
+impl JunitReporter {
+    pub fn new(path: PathBuf) -> Self {
+        JunitReporter {
+            path,
+            report: JunitReport::new(),
+            write_error: None,
+        }
+    }
+
+    fn write(&mut self) {
+        self.write_error = self.report.write(&self.path).err();
+    }
+}
+
+impl Reporter for JunitReporter {
+    fn note(&mut self, _: &str) {}
+
+    fn tip(&mut self, _: &str) {}
+
+    fn error(&mut self, _: &str) {}
+
+    fn changed_files(&mut self, _: &[ChangedFile], _: &Path) {}
+
+    fn test_start(&mut self, _: &str, _: usize, _: usize) {}
+
+    fn test_result(&mut self, result: &TestResult) {
+        self.report.add_result(result);
+    }
+
+    fn test_summary(&mut self, _: usize, _: usize, _: usize, _: usize, _: f64) {
+        self.write();
+    }
+
+    fn plan_summary(&mut self, _: &TestPlan) {}
+
+    fn test_failures(&mut self, _: &[TestResult]) {}
+
+    fn test_failure_details(&mut self, _: &str, _: &str) {}
+
+    fn no_tests(&mut self) {
+        self.write();
+    }
+
+    fn dry_run(&mut self) {
+        self.write();
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.write_error.take() {
+            Some(e) => Err(io::Error::other(e)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl TestSuite {
+    /// Count the number of tests and failures in the suite
+    fn counts(&self) -> (usize, usize) {
+        if self.test_cases.is_empty() {
+            (1, usize::from(self.outcome == TestOutcome::Failed))
+        } else {
+            let failures = self
+                .test_cases
+                .iter()
+                .filter(|case| case.status == TestCaseStatus::Failed)
+                .count();
+            (self.test_cases.len(), failures)
+        }
+    }
+
+    fn render(&self, xml: &mut String) {
+        let (tests, failures) = self.counts();
+        let skipped = self
+            .test_cases
+            .iter()
+            .filter(|case| case.status == TestCaseStatus::Ignored)
+            .count();
+
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">",
+            escape(&self.name),
+            tests,
+            failures,
+            skipped,
+            self.duration.as_secs_f64()
+        );
+
+        if self.test_cases.is_empty() {
+            // Without per-test data the whole crate is reported as a single test case
+            let output = self.output.as_str();
+            let failure = match self.outcome {
+                TestOutcome::Passed => None,
+                TestOutcome::Failed => Some(Failure::Failed(output)),
+                TestOutcome::Flaky => Some(Failure::Flaky(output)),
+                TestOutcome::Quarantined => Some(Failure::Quarantined(output)),
+            };
+            self.render_case(xml, &self.name, Some(self.duration), failure);
+        } else {
+            for case in &self.test_cases {
+                match case.status {
+                    TestCaseStatus::Ignored => {
+                        let _ = writeln!(
+                            xml,
+                            "    <testcase name=\"{}\" classname=\"{}\"><skipped/></testcase>",
+                            escape(&case.name),
+                            escape(&self.name)
+                        );
+                    }
+                    TestCaseStatus::Passed => {
+                        self.render_case(xml, &case.name, case.duration, None)
+                    }
+                    TestCaseStatus::Failed
+                    | TestCaseStatus::Flaky
+                    | TestCaseStatus::Quarantined => {
+                        // Runners that do not capture output per test only have the output of
+                        // the whole crate, which is the closest there is to the test's output
+                        let output = case.stdout.as_deref().unwrap_or(&self.output);
+                        let failure = match case.status {
+                            TestCaseStatus::Flaky => Failure::Flaky(output),
+                            TestCaseStatus::Quarantined => Failure::Quarantined(output),
+                            _ => Failure::Failed(output),
+                        };
+                        self.render_case(xml, &case.name, case.duration, Some(failure))
+                    }
+                }
+            }
+        }
+
+        // Per-test output is interleaved in the runner's output, so it is attached to the suite
+        if self.outcome != TestOutcome::Passed && !self.test_cases.is_empty() {
+            let _ = writeln!(xml, "    <system-out>{}</system-out>", escape(&self.output));
+        }
+
+        xml.push_str("  </testsuite>\n");
+    }
+
+    /// Render a test case, with an element describing its failure if it did not simply pass
+    fn render_case(
+        &self,
+        xml: &mut String,
+        name: &str,
+        duration: Option<Duration>,
+        failure: Option<Failure>,
+    ) {
+        let _ = write!(
+            xml,
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+            escape(name),
+            escape(&self.name),
+            duration.unwrap_or_default().as_secs_f64()
+        );
+
+        match failure {
+            None => xml.push_str("/>\n"),
+            Some(Failure::Failed(output)) => {
+                let _ = writeln!(
+                    xml,
+                    "><failure message=\"test failed\">{}</failure></testcase>",
+                    escape(output)
+                );
+            }
+            Some(Failure::Flaky(output)) => {
+                let _ = writeln!(
+                    xml,
+                    "><flakyFailure message=\"test failed before passing on retry\">{}</flakyFailure></testcase>",
+                    escape(output)
+                );
+            }
+            Some(Failure::Quarantined(output)) => {
+                let _ = writeln!(
+                    xml,
+                    "><skipped message=\"quarantined test failed\"/><system-out>{}</system-out></testcase>",
+                    escape(output)
+                );
+            }
+        }
+    }
+}
+
+/// How a test case that did not simply pass is rendered, with its output
+enum Failure<'a> {
+    /// A `failure` element
+    Failed(&'a str),
+    /// A `flakyFailure` element, for tests that passed when retried
+    Flaky(&'a str),
+    /// A `skipped` element, since quarantined failures do not fail the run
+    Quarantined(&'a str),
+}
+
+/// Escape text for use in XML content or attributes, dropping terminal escape sequences and
+/// characters XML cannot represent
+fn escape(text: &str) -> String {
+    let text = strip_ansi(text);
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
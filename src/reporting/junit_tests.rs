@@ -0,0 +1,234 @@
+use std::time::Duration;
+
+use tempfile::TempDir;
+
+use crate::error::AppError;
+use crate::reporting::{
+    finish,
+    junit::{JunitReport, JunitReporter},
+    ReportFormat, ReportOptions, ReportTarget, Reporter,
+};
+use crate::testing::{
+    cases::{TestCase, TestCaseStatus},
+    result::{TestOutcome, TestResult},
+};
+
+#[test]
+fn test_render_crate_level_suites() {
+    let mut report = JunitReport::new();
+    report.add_result(&TestResult {
+        crate_name: "crate1".to_string(),
+        outcome: TestOutcome::Passed,
+        output: "test result: ok".to_string(),
+        duration: Duration::from_millis(1500),
+        test_cases: Vec::new(),
+        attempts: 1,
+        sub_results: Vec::new(),
+        diagnostics: Vec::new(),
+    });
+    report.add_result(&TestResult {
+        crate_name: "crate2".to_string(),
+        outcome: TestOutcome::Failed,
+        output: "assertion `left == right` failed\n  left: <1>".to_string(),
+        duration: Duration::from_millis(250),
+        test_cases: Vec::new(),
+        attempts: 1,
+        sub_results: Vec::new(),
+        diagnostics: Vec::new(),
+    });
+
+    let xml = report.render();
+
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+    assert!(xml.contains(
+        "<testsuites name=\"cargo-test-changed\" tests=\"2\" failures=\"1\" time=\"1.750\">"
+    ));
+    assert!(xml.contains(
+        "<testsuite name=\"crate1\" tests=\"1\" failures=\"0\" skipped=\"0\" time=\"1.500\">"
+    ));
+    assert!(xml.contains("<testcase name=\"crate1\" classname=\"crate1\" time=\"1.500\"/>"));
+    assert!(xml.contains(
+        "<failure message=\"test failed\">assertion `left == right` failed\n  left: &lt;1&gt;</failure>"
+    ));
+    assert!(!xml.contains("<system-out>"));
+}
+
+#[test]
+fn test_render_per_test_cases() {
+    let mut report = JunitReport::new();
+    report.add_result(&TestResult {
+        crate_name: "crate1".to_string(),
+        outcome: TestOutcome::Failed,
+        output: "\u{1b}[31mFAIL\u{1b}[0m & more".to_string(),
+        duration: Duration::from_millis(30),
+        test_cases: vec![
+            TestCase {
+                name: "tests::passes".to_string(),
+                status: TestCaseStatus::Passed,
+                duration: Some(Duration::from_millis(10)),
+                stdout: None,
+            },
+            TestCase {
+                name: "tests::fails".to_string(),
+                status: TestCaseStatus::Failed,
+                duration: Some(Duration::from_millis(20)),
+                stdout: None,
+            },
+            TestCase {
+                name: "tests::skipped".to_string(),
+                status: TestCaseStatus::Ignored,
+                duration: None,
+                stdout: None,
+            },
+        ],
+        attempts: 1,
+        sub_results: Vec::new(),
+        diagnostics: Vec::new(),
+    });
+
+    let xml = report.render();
+
+    assert!(xml.contains(
+        "<testsuite name=\"crate1\" tests=\"3\" failures=\"1\" skipped=\"1\" time=\"0.030\">"
+    ));
+    assert!(xml.contains("<testcase name=\"tests::passes\" classname=\"crate1\" time=\"0.010\"/>"));
+    assert!(xml.contains(
+        "<testcase name=\"tests::fails\" classname=\"crate1\" time=\"0.020\"><failure message=\"test failed\">FAIL &amp; more</failure></testcase>"
+    ));
+    assert!(xml
+        .contains("<testcase name=\"tests::skipped\" classname=\"crate1\"><skipped/></testcase>"));
+    // Colors are stripped from the output
+    assert!(xml.contains("<system-out>FAIL &amp; more</system-out>"));
+}
+
+#[test]
+fn test_render_flaky_tests() {
+    let mut report = JunitReport::new();
+    report.add_result(&TestResult {
+        crate_name: "crate1".to_string(),
+        outcome: TestOutcome::Flaky,
+        output: "connection reset".to_string(),
+        duration: Duration::from_millis(40),
+        test_cases: vec![TestCase {
+            name: "tests::flaky".to_string(),
+            status: TestCaseStatus::Flaky,
+            duration: Some(Duration::from_millis(20)),
+            stdout: Some("connection reset".to_string()),
+        }],
+        attempts: 2,
+        sub_results: Vec::new(),
+        diagnostics: Vec::new(),
+    });
+    report.add_result(&TestResult {
+        crate_name: "crate2".to_string(),
+        outcome: TestOutcome::Flaky,
+        output: "timed out".to_string(),
+        duration: Duration::from_millis(10),
+        test_cases: Vec::new(),
+        attempts: 2,
+        sub_results: Vec::new(),
+        diagnostics: Vec::new(),
+    });
+
+    let xml = report.render();
+
+    assert!(xml.contains(
+        "<testsuites name=\"cargo-test-changed\" tests=\"2\" failures=\"0\" time=\"0.050\">"
+    ));
+    assert!(xml.contains(
+        "<testcase name=\"tests::flaky\" classname=\"crate1\" time=\"0.020\"><flakyFailure message=\"test failed before passing on retry\">connection reset</flakyFailure></testcase>"
+    ));
+    assert!(xml.contains(
+        "<testcase name=\"crate2\" classname=\"crate2\" time=\"0.010\"><flakyFailure message=\"test failed before passing on retry\">timed out</flakyFailure></testcase>"
+    ));
+}
+
+#[test]
+fn test_render_suites_in_run_order() {
+    let result = |name: &str, outcome: TestOutcome| TestResult {
+        crate_name: name.to_string(),
+        outcome,
+        output: String::new(),
+        duration: Duration::from_millis(10),
+        test_cases: Vec::new(),
+        attempts: 1,
+        sub_results: Vec::new(),
+        diagnostics: Vec::new(),
+    };
+    let mut report = JunitReport::new();
+    report.add_result(&result("crate2", TestOutcome::Failed));
+    report.add_result(&result("crate1", TestOutcome::Passed));
+
+    let xml = report.render();
+
+    let failed = xml.find("<testsuite name=\"crate2\"").unwrap();
+    let passed = xml.find("<testsuite name=\"crate1\"").unwrap();
+    assert!(failed < passed);
+}
+
+#[test]
+fn test_render_strips_escape_sequences() {
+    let mut report = JunitReport::new();
+    report.add_result(&TestResult {
+        crate_name: "crate1".to_string(),
+        outcome: TestOutcome::Failed,
+        output: String::new(),
+        duration: Duration::from_millis(10),
+        test_cases: vec![TestCase {
+            name: "tests::fails".to_string(),
+            status: TestCaseStatus::Failed,
+            duration: None,
+            stdout: Some(
+                "\u{1b}[1;31merror\u{1b}[0m at \u{1b}]8;;file:///lib.rs\u{7}lib.rs\u{1b}]8;;\u{1b}\\\u{1}"
+                    .to_string(),
+            ),
+        }],
+        attempts: 1,
+        sub_results: Vec::new(),
+        diagnostics: Vec::new(),
+    });
+
+    let xml = report.render();
+
+    // Control characters that are not part of a sequence are dropped as well
+    assert!(xml.contains("<failure message=\"test failed\">error at lib.rs</failure>"));
+}
+
+#[test]
+fn test_reporter_writes_empty_report_without_tests() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("junit.xml");
+    std::fs::write(&path, "<testsuites>stale</testsuites>").unwrap();
+
+    // Creating the reporter removes the report of an earlier run
+    let target = ReportTarget {
+        format: ReportFormat::Junit,
+        path: path.clone(),
+        append: false,
+    };
+    let mut reporter = target.create(&ReportOptions::default()).unwrap();
+    assert!(!path.exists());
+
+    reporter.no_tests();
+    finish(reporter.as_mut()).unwrap();
+
+    let xml = std::fs::read_to_string(&path).unwrap();
+    assert!(xml.contains(
+        "<testsuites name=\"cargo-test-changed\" tests=\"0\" failures=\"0\" time=\"0.000\">"
+    ));
+    assert!(!xml.contains("stale"));
+}
+
+#[test]
+fn test_reporter_fails_when_report_cannot_be_written() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("missing/junit.xml");
+    let mut reporter = JunitReporter::new(path.clone());
+
+    reporter.dry_run();
+
+    assert!(matches!(
+        finish(&mut reporter),
+        Err(AppError::WriteFailed { path: failed, .. }) if failed == path.display().to_string()
+    ));
+}
@@ -0,0 +1,384 @@
+use std::{
+    fmt::Write as _,
+    fs::OpenOptions,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use super::{pluralize, Reporter};
+use crate::{
+    error::AppError,
+    testing::{
+        diagnostics,
+        plan::{DiscoveryType, TestCrates, TestPlan},
+        result::{TestOutcome, TestResult},
+    },
+    vcs::ChangedFile,
+};
+
+/// Default number of bytes of failure output included for each failed crate
+pub const DEFAULT_MAX_FAILURE_OUTPUT: usize = 4096;
+
+/// A compact Markdown report, suitable for PR comments and GitHub step summaries
+#[derive(Debug)]
+pub struct MarkdownReport {
+    max_failure_output: usize,
+    /// Description of how the crates were selected
+    plan: Option<String>,
+    /// Crates in the plan, with how they were selected
+    crates: Vec<(String, String)>,
+    results: Vec<CrateRow>,
+    /// Crates skipped because their passing result is cached
+    cached: Vec<String>,
+    /// Crates among `cached` whose passing result came from the shared result store
+    shared_cached: Vec<String>,
+    dry_run: bool,
+}
+
+#[derive(Debug)]
+struct CrateRow {
+    name: String,
+    outcome: TestOutcome,
+    duration: Duration,
+    tests: Option<String>,
+    /// Commands that failed, when more than one was run for the crate
+    failed_parts: Vec<&'static str>,
+    output: String,
+}
+
+impl MarkdownReport {
+    pub fn new(max_failure_output: usize) -> Self {
+        MarkdownReport {
+            max_failure_output,
+            plan: None,
+            crates: Vec::new(),
+            results: Vec::new(),
+            cached: Vec::new(),
+            shared_cached: Vec::new(),
+            dry_run: false,
+        }
+    }
+
+    /// Record the crates that are about to be tested
+    pub fn set_plan(&mut self, test_plan: &TestPlan) {
+        let tested = test_plan.get_selected_crates();
+        self.cached = test_plan.cached.iter().cloned().collect();
+        self.shared_cached = test_plan.shared_cached.iter().cloned().collect();
+
+        match &test_plan.crates {
+            TestCrates::Manual(crates) => {
+                let word = pluralize(crates.len(), "crate", "crates");
+                self.plan = Some(format!("Manually testing {} {}", crates.len(), word));
+                self.crates = crates
+                    .iter()
+                    .map(|c| (c.name.clone(), "manual".to_string()))
+                    .collect();
+            }
+            TestCrates::Discovered(crates) => {
+                let dependent_count = crates
+                    .iter()
+                    .filter(|c| matches!(c.discovery_type, DiscoveryType::Dependent))
+                    .count();
+                let changed_count = crates.len() - dependent_count;
+
+                let mut plan = format!(
+                    "**{}** changed {}",
+                    changed_count,
+                    pluralize(changed_count, "crate", "crates")
+                );
+                if test_plan.with_dependents {
+                    let _ = write!(
+                        plan,
+                        ", **{}** dependent {}",
+                        dependent_count,
+                        pluralize(dependent_count, "crate", "crates")
+                    );
+                }
+                if !test_plan.cached.is_empty() {
+                    let _ = write!(plan, ", **{}** cached", test_plan.cached.len());
+                }
+                if !test_plan.shared_cached.is_empty() {
+                    let _ = write!(
+                        plan,
+                        " ({} from the shared store)",
+                        test_plan.shared_cached.len()
+                    );
+                }
+                self.plan = Some(plan);
+
+                self.crates = crates
+                    .iter()
+                    .filter(|c| tested.contains(&&c.name))
+                    .map(|c| {
+                        let selected_by = match &c.discovery_type {
+                            DiscoveryType::Modified => "changed".to_string(),
+                            DiscoveryType::Dependent => "dependent".to_string(),
+                            DiscoveryType::Mapped { pattern } => format!("`{}`", pattern),
+                        };
+                        (c.name.clone(), selected_by)
+                    })
+                    .collect();
+            }
+        }
+    }
+
+    /// Add a crate's result to the table
+    pub fn add_result(&mut self, result: &TestResult) {
+        self.results.push(CrateRow {
+            name: result.crate_name.clone(),
+            outcome: result.outcome,
+            duration: result.duration,
+            tests: result
+                .counts()
+                .map(|counts| counts.to_string())
+                .or_else(|| {
+                    Some(diagnostics::summarize(&result.diagnostics))
+                        .filter(|summary| !summary.is_empty())
+                }),
+            failed_parts: result
+                .sub_results
+                .iter()
+                .filter(|sub_result| !sub_result.success)
+                .map(|sub_result| sub_result.kind.label())
+                .collect(),
+            output: if result.success() {
+                String::new()
+            } else {
+                result.output.clone()
+            },
+        });
+    }
+
+    pub fn set_dry_run(&mut self) {
+        self.dry_run = true;
+    }
+
+    /// Render the report as Markdown
+    pub fn render(&self) -> String {
+        let mut md = String::from("## cargo test-changed\n\n");
+
+        let Some(plan) = &self.plan else {
+            md.push_str("No crates to test.\n");
+            return md;
+        };
+
+        let _ = writeln!(md, "{}\n", plan);
+
+        if self.dry_run {
+            md.push_str("Dry run, tests were not run.\n\n");
+        }
+
+        md.push_str("| Crate | Selected by | Result | Tests | Duration |\n");
+        md.push_str("| --- | --- | --- | --- | ---: |\n");
+
+        for (name, selected_by) in &self.crates {
+            match self.results.iter().find(|row| &row.name == name) {
+                Some(row) => {
+                    let mut outcome = match row.outcome {
+                        TestOutcome::Passed => "✅ passed",
+                        TestOutcome::Failed => "❌ failed",
+                        TestOutcome::Flaky => "⚠️ flaky",
+                        TestOutcome::Quarantined => "🔇 quarantined",
+                    }
+                    .to_string();
+                    if !row.failed_parts.is_empty() {
+                        let _ = write!(outcome, " ({})", row.failed_parts.join(", "));
+                    }
+
+                    let _ = writeln!(
+                        md,
+                        "| `{}` | {} | {} | {} | {:.2}s |",
+                        name,
+                        selected_by,
+                        outcome,
+                        row.tests.as_deref().unwrap_or("-"),
+                        row.duration.as_secs_f64()
+                    );
+                }
+                None if self.shared_cached.contains(name) => {
+                    let _ = writeln!(
+                        md,
+                        "| `{}` | {} | ⏭️ cached (shared) | - | - |",
+                        name, selected_by
+                    );
+                }
+                None if self.cached.contains(name) => {
+                    let _ = writeln!(md, "| `{}` | {} | ⏭️ cached | - | - |", name, selected_by);
+                }
+                None => {
+                    let _ = writeln!(md, "| `{}` | {} | - | - | - |", name, selected_by);
+                }
+            }
+        }
+
+        if !self.dry_run {
+            let count = |outcome| {
+                self.results
+                    .iter()
+                    .filter(|row| row.outcome == outcome)
+                    .count()
+            };
+            let (passed, failed, flaky, quarantined) = (
+                count(TestOutcome::Passed),
+                count(TestOutcome::Failed),
+                count(TestOutcome::Flaky),
+                count(TestOutcome::Quarantined),
+            );
+            let duration: Duration = self.results.iter().map(|row| row.duration).sum();
+
+            let _ = write!(
+                md,
+                "\n**Result:** {} {} passed, ",
+                if failed == 0 { "✅" } else { "❌" },
+                passed
+            );
+            if flaky > 0 {
+                let _ = write!(md, "{} flaky, ", flaky);
+            }
+            let _ = write!(md, "{} failed", failed);
+            if quarantined > 0 {
+                let _ = write!(md, ", {} quarantined", quarantined);
+            }
+            let _ = writeln!(md, " in {:.2}s", duration.as_secs_f64());
+        }
+
+        let failures: Vec<_> = self
+            .results
+            .iter()
+            .filter(|row| row.outcome == TestOutcome::Failed)
+            .collect();
+        if !failures.is_empty() {
+            md.push_str("\n### Failures\n");
+
+            for row in failures {
+                let output = truncate_output(&row.output, self.max_failure_output);
+                let fence = "`".repeat(longest_backtick_run(&output).max(2) + 1);
+
+                let _ = write!(
+                    md,
+                    "\n<details>\n<summary><code>{}</code></summary>\n\n{}text\n{}\n{}\n\n</details>\n",
+                    row.name,
+                    fence,
+                    output.trim_end(),
+                    fence
+                );
+            }
+        }
+
+        md
+    }
+
+    /// Write the report to a file, appending to it rather than replacing its contents if requested
+    pub fn write(&self, path: &Path, append: bool) -> Result<(), AppError> {
+        let write_failed = |e: io::Error| AppError::WriteFailed {
+            path: path.display().to_string(),
+            reason: e.to_string(),
+        };
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)
+            .map_err(write_failed)?;
+
+        file.write_all(self.render().as_bytes())
+            .map_err(write_failed)
+    }
+}
+
+/// Keep the end of the output, where the failure is usually reported, within `max_bytes`
+fn truncate_output(output: &str, max_bytes: usize) -> String {
+    if output.len() <= max_bytes {
+        return output.to_string();
+    }
+
+    let mut start = output.len() - max_bytes;
+    while !output.is_char_boundary(start) {
+        start += 1;
+    }
+
+    format!(
+        "... truncated, showing the last {} bytes ...\n{}",
+        max_bytes,
+        &output[start..]
+    )
+}
+
+/// Find the longest run of backticks, so the code fence around the output is longer
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default()
+}
+
+/// Reporter that collects the plan and results and writes them as Markdown once the run finishes
+pub struct MarkdownReporter {
+    path: PathBuf,
+    append: bool,
+    report: MarkdownReport,
+    /// Why the summary could not be written, returned by `flush`
+    write_error: Option<AppError>,
+}
+
+impl MarkdownReporter {
+    pub fn new(path: PathBuf, append: bool, max_failure_output: usize) -> Self {
+        MarkdownReporter {
+            path,
+            append,
+            report: MarkdownReport::new(max_failure_output),
+            write_error: None,
+        }
+    }
+
+    fn write(&mut self) {
+        self.write_error = self.report.write(&self.path, self.append).err();
+    }
+}
+
+impl Reporter for MarkdownReporter {
+    fn note(&mut self, _: &str) {}
+
+    fn tip(&mut self, _: &str) {}
+
+    fn error(&mut self, _: &str) {}
+
+    fn changed_files(&mut self, _: &[ChangedFile], _: &Path) {}
+
+    fn test_start(&mut self, _: &str, _: usize, _: usize) {}
+
+    fn test_result(&mut self, result: &TestResult) {
+        self.report.add_result(result);
+    }
+
+    fn test_summary(&mut self, _: usize, _: usize, _: usize, _: usize, _: f64) {
+        self.write();
+    }
+
+    fn plan_summary(&mut self, test_plan: &TestPlan) {
+        self.report.set_plan(test_plan);
+    }
+
+    fn test_failures(&mut self, _: &[TestResult]) {}
+
+    fn test_failure_details(&mut self, _: &str, _: &str) {}
+
+    fn no_tests(&mut self) {
+        self.write();
+    }
+
+    fn dry_run(&mut self) {
+        self.report.set_dry_run();
+        self.write();
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.write_error.take() {
+            Some(e) => Err(io::Error::other(e)),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use std::{path::PathBuf, time::Duration};
+
+use indexmap::{IndexMap, IndexSet};
+
+use crate::error::AppError;
+use crate::quarantine::Quarantine;
+use crate::reporting::{
+    finish,
+    markdown::{MarkdownReport, MarkdownReporter},
+    Reporter,
+};
+use crate::testing::{
+    cases::{TestCase, TestCaseStatus},
+    plan::{DiscoveredTestCrate, DiscoveryType, RunMode, TestCrates, TestPlan},
+    result::{SubResult, SubResultKind, TestOutcome, TestResult},
+    schedule::Schedule,
+};
+
+fn test_plan(with_dependents: bool) -> TestPlan {
+    TestPlan {
+        workspace_root: PathBuf::from("/workspace"),
+        crates: TestCrates::Discovered(IndexSet::from([
+            DiscoveredTestCrate {
+                name: "crate_a".to_string(),
+                discovery_type: DiscoveryType::Modified,
+            },
+            DiscoveredTestCrate {
+                name: "crate_b".to_string(),
+                discovery_type: DiscoveryType::Mapped {
+                    pattern: "proto/**".to_string(),
+                },
+            },
+            DiscoveredTestCrate {
+                name: "crate_c".to_string(),
+                discovery_type: DiscoveryType::Dependent,
+            },
+        ])),
+        mode: RunMode::Test,
+        with_dependents,
+        fail_fast: false,
+        retries: 0,
+        verbose: false,
+        test_runner_args: Vec::new(),
+        test_filters: IndexMap::new(),
+        cached: IndexSet::new(),
+        shared_cached: IndexSet::new(),
+        fingerprints: IndexMap::new(),
+        quarantine: Quarantine::default(),
+        schedule: Schedule::default(),
+        crate_stats: IndexMap::new(),
+        doc_test_crates: IndexSet::new(),
+        shard: None,
+    }
+}
+
+fn test_case(name: &str, status: TestCaseStatus) -> TestCase {
+    TestCase {
+        name: name.to_string(),
+        status,
+        duration: None,
+        stdout: None,
+    }
+}
+
+#[test]
+fn test_render_results() {
+    let mut report = MarkdownReport::new(4096);
+    report.set_plan(&test_plan(true));
+    report.add_result(&TestResult {
+        crate_name: "crate_a".to_string(),
+        outcome: TestOutcome::Passed,
+        output: "test result: ok".to_string(),
+        duration: Duration::from_millis(1500),
+        test_cases: vec![
+            test_case("tests::a", TestCaseStatus::Passed),
+            test_case("tests::b", TestCaseStatus::Ignored),
+        ],
+        attempts: 1,
+        sub_results: Vec::new(),
+        diagnostics: Vec::new(),
+    });
+    report.add_result(&TestResult {
+        crate_name: "crate_b".to_string(),
+        outcome: TestOutcome::Failed,
+        output: "thread 'tests::fails' panicked\n```\n".to_string(),
+        duration: Duration::from_millis(250),
+        test_cases: Vec::new(),
+        attempts: 1,
+        sub_results: Vec::new(),
+        diagnostics: Vec::new(),
+    });
+
+    assert_eq!(
+        report.render(),
+        "\
+## cargo test-changed
+
+**2** changed crates, **1** dependent crate
+
+| Crate | Selected by | Result | Tests | Duration |
+| --- | --- | --- | --- | ---: |
+| `crate_a` | changed | ✅ passed | 1 passed, 0 failed, 1 ignored | 1.50s |
+| `crate_b` | `proto/**` | ❌ failed | - | 0.25s |
+| `crate_c` | dependent | - | - | - |
+
+**Result:** ❌ 1 passed, 1 failed in 1.75s
+
+### Failures
+
+<details>
+<summary><code>crate_b</code></summary>
+
+````text
+thread 'tests::fails' panicked
+```
+````
+
+</details>
+"
+    );
+}
+
+#[test]
+fn test_render_dry_run_without_dependents() {
+    let mut report = MarkdownReport::new(4096);
+    report.set_plan(&test_plan(false));
+    report.set_dry_run();
+
+    assert_eq!(
+        report.render(),
+        "\
+## cargo test-changed
+
+**2** changed crates
+
+Dry run, tests were not run.
+
+| Crate | Selected by | Result | Tests | Duration |
+| --- | --- | --- | --- | ---: |
+| `crate_a` | changed | - | - | - |
+| `crate_b` | `proto/**` | - | - | - |
+"
+    );
+}
+
+#[test]
+fn test_render_cached_crates() {
+    let mut plan = test_plan(true);
+    plan.cached = IndexSet::from(["crate_b".to_string(), "crate_c".to_string()]);
+    plan.shared_cached = IndexSet::from(["crate_b".to_string()]);
+
+    let mut report = MarkdownReport::new(4096);
+    report.set_plan(&plan);
+    report.add_result(&TestResult {
+        crate_name: "crate_a".to_string(),
+        outcome: TestOutcome::Passed,
+        output: String::new(),
+        duration: Duration::from_millis(500),
+        test_cases: Vec::new(),
+        attempts: 1,
+        sub_results: Vec::new(),
+        diagnostics: Vec::new(),
+    });
+
+    assert_eq!(
+        report.render(),
+        "\
+## cargo test-changed
+
+**2** changed crates, **1** dependent crate, **2** cached (1 from the shared store)
+
+| Crate | Selected by | Result | Tests | Duration |
+| --- | --- | --- | --- | ---: |
+| `crate_a` | changed | ✅ passed | - | 0.50s |
+| `crate_b` | `proto/**` | ⏭️ cached (shared) | - | - |
+| `crate_c` | dependent | ⏭️ cached | - | - |
+
+**Result:** ✅ 1 passed, 0 failed in 0.50s
+"
+    );
+}
+
+#[test]
+fn test_render_truncates_failure_output() {
+    let mut report = MarkdownReport::new(10);
+    report.set_plan(&test_plan(false));
+    report.add_result(&TestResult {
+        crate_name: "crate_a".to_string(),
+        outcome: TestOutcome::Failed,
+        output: "a long line of output\nthe failure".to_string(),
+        duration: Duration::from_millis(100),
+        test_cases: Vec::new(),
+        attempts: 1,
+        sub_results: Vec::new(),
+        diagnostics: Vec::new(),
+    });
+
+    let markdown = report.render();
+
+    assert!(
+        markdown.contains("```text\n... truncated, showing the last 10 bytes ...\nhe failure\n```")
+    );
+    assert!(!markdown.contains("a long line"));
+}
+
+#[test]
+fn test_render_failed_doc_tests() {
+    let mut report = MarkdownReport::new(4096);
+    report.set_plan(&test_plan(false));
+    report.add_result(&TestResult {
+        crate_name: "crate_a".to_string(),
+        outcome: TestOutcome::Failed,
+        output: String::new(),
+        duration: Duration::from_millis(800),
+        test_cases: Vec::new(),
+        attempts: 1,
+        sub_results: vec![
+            SubResult {
+                kind: SubResultKind::Tests,
+                success: true,
+                duration_ms: 500,
+                tests: None,
+            },
+            SubResult {
+                kind: SubResultKind::DocTests,
+                success: false,
+                duration_ms: 300,
+                tests: None,
+            },
+        ],
+        diagnostics: Vec::new(),
+    });
+
+    assert!(report
+        .render()
+        .contains("| `crate_a` | changed | ❌ failed (doc-tests) | - | 0.80s |"));
+}
+
+#[test]
+fn test_append_to_existing_file() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("summary.md");
+    std::fs::write(&path, "# Existing step summary\n").unwrap();
+
+    let report = MarkdownReport::new(4096);
+    report.write(&path, true).unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "# Existing step summary\n## cargo test-changed\n\nNo crates to test.\n"
+    );
+}
+
+#[test]
+fn test_reporter_fails_when_summary_cannot_be_written() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("missing/summary.md");
+    let mut reporter = MarkdownReporter::new(path.clone(), false, 4096);
+
+    reporter.no_tests();
+
+    assert!(matches!(
+        finish(&mut reporter),
+        Err(AppError::WriteFailed { path: failed, .. }) if failed == path.display().to_string()
+    ));
+}
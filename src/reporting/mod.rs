@@ -1,12 +1,39 @@
-use std::path::Path;
+use std::{
+    fs::OpenOptions,
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use clap::ValueEnum;
 
 use crate::{
-    testing::{plan::TestPlan, result::TestResult},
+    error::AppError,
+    explain::Explanation,
+    history::RunRecord,
+    testing::{plan::TestPlan, result::TestResult, shard::ShardAssignment},
     vcs::ChangedFile,
+    watch::WatchStatus,
 };
 
+pub mod annotations;
 pub mod console;
+pub mod events;
 pub mod json;
+pub mod junit;
+pub mod markdown;
+pub mod multi;
+
+#[cfg(test)]
+mod annotations_tests;
+#[cfg(test)]
+mod events_tests;
+#[cfg(test)]
+mod junit_tests;
+#[cfg(test)]
+mod markdown_tests;
+#[cfg(test)]
+mod multi_tests;
 
 /// Reporter trait for different output formats
 pub trait Reporter {
@@ -26,10 +53,18 @@ pub trait Reporter {
     fn test_start(&mut self, crate_name: &str, test_number: usize, total_tests: usize);
 
     /// Report a test result (success or failure)
-    fn test_result(&mut self, crate_name: &str, success: bool, duration_ms: u64);
+    fn test_result(&mut self, result: &TestResult);
 
-    /// Report test summary
-    fn test_summary(&mut self, passed: usize, failed: usize, duration_secs: f64);
+    /// Report test summary, where flaky and quarantined crates are counted separately from
+    /// those that passed or failed
+    fn test_summary(
+        &mut self,
+        passed: usize,
+        failed: usize,
+        flaky: usize,
+        quarantined: usize,
+        duration_secs: f64,
+    );
 
     /// Report a test plan summary
     fn plan_summary(&mut self, test_plan: &TestPlan);
@@ -46,10 +81,141 @@ pub trait Reporter {
     /// Report dry run mode
     fn dry_run(&mut self);
 
-    /// Flush any buffered output
+    /// Report why a crate is or is not part of the test plan, for the `explain` command
+    fn explain(&mut self, _explanation: &Explanation) {}
+
+    /// Report recent runs from the history file, newest first, for the `history` command
+    fn history(&mut self, _runs: &[RunRecord]) {}
+
+    /// Report how the selected crates are split across shards, with `current` the shard
+    /// of this job
+    fn shards(&mut self, _shards: &[ShardAssignment], _current: usize) {}
+
+    /// Report what watch mode is doing
+    fn watch_status(&mut self, _status: &WatchStatus) {}
+
+    /// Flush any buffered output, failing if a report file could not be written
     fn flush(&mut self) -> std::io::Result<()>;
 }
 
+/// Flush the reporters once a run has finished, so a requested report that could not be
+/// written fails the run
+pub fn finish(reporter: &mut dyn Reporter) -> Result<(), AppError> {
+    reporter
+        .flush()
+        .map_err(|e| match e.downcast::<AppError>() {
+            Ok(e) => e,
+            Err(e) => AppError::Other(e.into()),
+        })
+}
+
+/// Output formats that can be written to a file with `--report`
+#[derive(ValueEnum, Debug, Clone, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Human-readable output, as printed to the terminal
+    Console,
+    /// Newline-delimited JSON events
+    Json,
+    /// JUnit XML
+    Junit,
+    /// Markdown summary, for PR comments and GitHub step summaries
+    Markdown,
+}
+
+/// A report format paired with the file it should be written to, given as `FORMAT=PATH`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportTarget {
+    pub format: ReportFormat,
+    pub path: PathBuf,
+    /// Append to the file instead of replacing it
+    pub append: bool,
+}
+
+/// Settings shared by the reporters that write to files
+#[derive(Debug, Clone)]
+pub struct ReportOptions {
+    /// Number of bytes of failure output to include for each failed crate
+    pub max_failure_output: usize,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        ReportOptions {
+            max_failure_output: markdown::DEFAULT_MAX_FAILURE_OUTPUT,
+        }
+    }
+}
+
+impl FromStr for ReportTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (format, path) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected FORMAT=PATH, got '{}'", s))?;
+
+        if path.is_empty() {
+            return Err(format!("missing path for '{}' report", format));
+        }
+
+        Ok(ReportTarget {
+            format: ReportFormat::from_str(format, true)?,
+            path: PathBuf::from(path),
+            append: false,
+        })
+    }
+}
+
+impl ReportTarget {
+    /// Create a reporter that writes to the target's file
+    pub fn create(&self, options: &ReportOptions) -> Result<Box<dyn Reporter>, AppError> {
+        match self.format {
+            ReportFormat::Junit => {
+                // A report left by an earlier run would be taken for this run's if it stops
+                // before writing one
+                match std::fs::remove_file(&self.path) {
+                    Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                        return Err(AppError::WriteFailed {
+                            path: self.path.display().to_string(),
+                            reason: e.to_string(),
+                        });
+                    }
+                    _ => {}
+                }
+                return Ok(Box::new(junit::JunitReporter::new(self.path.clone())));
+            }
+            ReportFormat::Markdown => {
+                return Ok(Box::new(markdown::MarkdownReporter::new(
+                    self.path.clone(),
+                    self.append,
+                    options.max_failure_output,
+                )));
+            }
+            ReportFormat::Console | ReportFormat::Json => {}
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(self.append)
+            .truncate(!self.append)
+            .open(&self.path)
+            .map_err(|e| AppError::WriteFailed {
+                path: self.path.display().to_string(),
+                reason: e.to_string(),
+            })?;
+        let writer = BufWriter::new(file);
+
+        Ok(match self.format {
+            ReportFormat::Console => {
+                Box::new(console::ConsoleReporter::new(writer, true).without_colors())
+            }
+            ReportFormat::Json => Box::new(json::JsonReporter::new(writer)),
+            ReportFormat::Junit | ReportFormat::Markdown => unreachable!(),
+        })
+    }
+}
+
 /// Return the singular or plural form of a word based on the count
 pub fn pluralize(count: usize, singular: &str, plural: &str) -> String {
     if count == 1 {
@@ -58,3 +224,42 @@ pub fn pluralize(count: usize, singular: &str, plural: &str) -> String {
         plural.to_string()
     }
 }
+
+/// Remove ANSI escape sequences, such as the colors runners add to their output
+pub fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            stripped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            // Control sequences end with a byte in the range `@` to `~`
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // Operating system commands, such as hyperlinks, end with BEL or ESC `\`
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\u{7}' {
+                        break;
+                    }
+                    if c == '\u{1b}' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    stripped
+}
@@ -0,0 +1,110 @@
+use crate::{
+    explain::Explanation,
+    history::RunRecord,
+    testing::{plan::TestPlan, result::TestResult, shard::ShardAssignment},
+    vcs::ChangedFile,
+    watch::WatchStatus,
+};
+
+use super::Reporter;
+use std::{io, path::Path};
+
+/// Reporter that forwards every event to several reporters
+pub struct MultiReporter {
+    reporters: Vec<Box<dyn Reporter>>,
+}
+
+impl MultiReporter {
+    pub fn new(reporters: Vec<Box<dyn Reporter>>) -> Self {
+        MultiReporter { reporters }
+    }
+
+    fn for_each(&mut self, mut f: impl FnMut(&mut dyn Reporter)) {
+        for reporter in self.reporters.iter_mut() {
+            f(reporter.as_mut());
+        }
+    }
+}
+
+impl Reporter for MultiReporter {
+    fn note(&mut self, message: &str) {
+        self.for_each(|r| r.note(message));
+    }
+
+    fn tip(&mut self, message: &str) {
+        self.for_each(|r| r.tip(message));
+    }
+
+    fn error(&mut self, message: &str) {
+        self.for_each(|r| r.error(message));
+    }
+
+    fn changed_files(&mut self, changed_files: &[ChangedFile], workspace_root: &Path) {
+        self.for_each(|r| r.changed_files(changed_files, workspace_root));
+    }
+
+    fn test_start(&mut self, crate_name: &str, test_number: usize, total_tests: usize) {
+        self.for_each(|r| r.test_start(crate_name, test_number, total_tests));
+    }
+
+    fn test_result(&mut self, result: &TestResult) {
+        self.for_each(|r| r.test_result(result));
+    }
+
+    fn test_summary(
+        &mut self,
+        passed: usize,
+        failed: usize,
+        flaky: usize,
+        quarantined: usize,
+        duration_secs: f64,
+    ) {
+        self.for_each(|r| r.test_summary(passed, failed, flaky, quarantined, duration_secs));
+    }
+
+    fn plan_summary(&mut self, test_plan: &TestPlan) {
+        self.for_each(|r| r.plan_summary(test_plan));
+    }
+
+    fn test_failures(&mut self, failures: &[TestResult]) {
+        self.for_each(|r| r.test_failures(failures));
+    }
+
+    fn test_failure_details(&mut self, crate_name: &str, output: &str) {
+        self.for_each(|r| r.test_failure_details(crate_name, output));
+    }
+
+    fn no_tests(&mut self) {
+        self.for_each(|r| r.no_tests());
+    }
+
+    fn dry_run(&mut self) {
+        self.for_each(|r| r.dry_run());
+    }
+
+    fn explain(&mut self, explanation: &Explanation) {
+        self.for_each(|r| r.explain(explanation));
+    }
+
+    fn history(&mut self, runs: &[RunRecord]) {
+        self.for_each(|r| r.history(runs));
+    }
+
+    fn shards(&mut self, shards: &[ShardAssignment], current: usize) {
+        self.for_each(|r| r.shards(shards, current));
+    }
+
+    fn watch_status(&mut self, status: &WatchStatus) {
+        self.for_each(|r| r.watch_status(status));
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut result = Ok(());
+        for reporter in self.reporters.iter_mut() {
+            if let Err(e) = reporter.flush() {
+                result = result.and(Err(e));
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,95 @@
+use std::{path::PathBuf, time::Duration};
+
+use tempfile::TempDir;
+
+use crate::reporting::{multi::MultiReporter, ReportFormat, ReportOptions, ReportTarget, Reporter};
+use crate::testing::result::{TestOutcome, TestResult};
+
+#[test]
+fn test_parse_report_target() {
+    let target: ReportTarget = "json=out.ndjson".parse().unwrap();
+    assert_eq!(
+        target,
+        ReportTarget {
+            format: ReportFormat::Json,
+            path: PathBuf::from("out.ndjson"),
+            append: false,
+        }
+    );
+
+    let target: ReportTarget = "JUnit=reports/junit.xml".parse().unwrap();
+    assert_eq!(target.format, ReportFormat::Junit);
+    assert_eq!(target.path, PathBuf::from("reports/junit.xml"));
+}
+
+#[test]
+fn test_parse_invalid_report_target() {
+    assert!("json".parse::<ReportTarget>().is_err());
+    assert!("json=".parse::<ReportTarget>().is_err());
+    assert!("yaml=out.yaml".parse::<ReportTarget>().is_err());
+}
+
+#[test]
+fn test_multi_reporter_writes_every_report() {
+    let dir = TempDir::new().unwrap();
+    let json_path = dir.path().join("out.ndjson");
+    let junit_path = dir.path().join("junit.xml");
+
+    let targets = [
+        ReportTarget {
+            format: ReportFormat::Json,
+            path: json_path.clone(),
+            append: false,
+        },
+        ReportTarget {
+            format: ReportFormat::Junit,
+            path: junit_path.clone(),
+            append: false,
+        },
+    ];
+    let mut reporter = MultiReporter::new(
+        targets
+            .iter()
+            .map(|t| t.create(&ReportOptions::default()).unwrap())
+            .collect(),
+    );
+
+    reporter.test_result(&TestResult {
+        crate_name: "crate1".to_string(),
+        outcome: TestOutcome::Passed,
+        output: String::new(),
+        duration: Duration::from_millis(10),
+        test_cases: Vec::new(),
+        attempts: 1,
+        sub_results: Vec::new(),
+        diagnostics: Vec::new(),
+    });
+    reporter.test_summary(1, 0, 0, 0, 0.01);
+    reporter.flush().unwrap();
+
+    let json = std::fs::read_to_string(&json_path).unwrap();
+    assert_eq!(json.lines().count(), 2);
+    assert!(json.contains("\"crate1\""));
+
+    let xml = std::fs::read_to_string(&junit_path).unwrap();
+    assert!(xml.contains("name=\"crate1\""));
+}
+
+#[test]
+fn test_console_report_file_has_no_colors() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("console.log");
+    let target = ReportTarget {
+        format: ReportFormat::Console,
+        path: path.clone(),
+        append: false,
+    };
+    let mut reporter = target.create(&ReportOptions::default()).unwrap();
+
+    reporter.note("runner said \u{1b}[1;31merror\u{1b}[0m");
+    reporter.flush().unwrap();
+
+    let console = std::fs::read_to_string(&path).unwrap();
+    assert!(console.contains("runner said error"));
+    assert!(!console.contains('\u{1b}'));
+}
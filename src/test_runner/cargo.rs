@@ -1,4 +1,8 @@
-use super::TestRunner;
+use super::{cargo_build_args, TestRunner};
+use crate::testing::{
+    cases::{self, TestCase},
+    filter::{TargetFilter, TestTarget},
+};
 use std::process::Command;
 
 pub struct CargoRunner;
@@ -10,6 +14,72 @@ impl TestRunner for CargoRunner {
         cmd
     }
 
+    fn split_targets(&self, targets: &[TargetFilter]) -> Vec<Vec<TargetFilter>> {
+        // Name filters after `--` apply to every target of an invocation, so targets narrowed
+        // to modules are run apart from those run in full
+        let (narrowed, full): (Vec<_>, Vec<_>) = targets
+            .iter()
+            .cloned()
+            .partition(|filter| filter.modules.is_some());
+        if narrowed.is_empty() || full.iter().all(|filter| filter.target == TestTarget::Doc) {
+            return vec![targets.to_vec()];
+        }
+
+        vec![narrowed, full]
+    }
+
+    fn filter_args(&self, _: &str, targets: &[TargetFilter]) -> Vec<String> {
+        let mut args = Vec::new();
+
+        // Doc-tests cannot be selected together with other targets, so they are run by
+        // `doc_test_command` instead
+        let targets: Vec<_> = targets
+            .iter()
+            .filter(|filter| filter.target != TestTarget::Doc)
+            .collect();
+
+        for filter in &targets {
+            match &filter.target {
+                TestTarget::Lib => args.push("--lib".to_string()),
+                TestTarget::Bin(name) => args.extend(["--bin".to_string(), name.clone()]),
+                TestTarget::Test(name) => args.extend(["--test".to_string(), name.clone()]),
+                TestTarget::Doc => {}
+            }
+        }
+
+        // Name filters apply to every selected target, so they can only be used
+        // when every target is restricted to specific modules
+        if targets.iter().all(|filter| filter.modules.is_some()) {
+            args.push("--".to_string());
+            for module in targets
+                .iter()
+                .flat_map(|filter| filter.modules.iter().flatten())
+            {
+                let module = format!("{}::", module);
+                if !args.contains(&module) {
+                    args.push(module);
+                }
+            }
+        }
+
+        args
+    }
+
+    fn runs_doc_tests(&self) -> bool {
+        true
+    }
+
+    fn doc_test_command(&self, crate_name: &str, args: &[String]) -> Option<Command> {
+        let mut cmd = Command::new("cargo");
+        cmd.args(["test", "--doc", "-p", crate_name]);
+        cmd.args(cargo_build_args(args, "--profile"));
+        Some(cmd)
+    }
+
+    fn parse_test_cases(&self, output: &str) -> Vec<TestCase> {
+        cases::parse_libtest_output(output)
+    }
+
     fn is_installed(&self) -> bool {
         // Cargo is always installed if we're running a cargo command
         true
@@ -0,0 +1,93 @@
+use crate::test_runner::{CargoRunner, TestRunner};
+use crate::testing::filter::{TargetFilter, TestTarget};
+
+fn filter(target: TestTarget, modules: Option<&[&str]>) -> TargetFilter {
+    TargetFilter {
+        target,
+        modules: modules.map(|modules| modules.iter().map(|m| m.to_string()).collect()),
+    }
+}
+
+/// Arguments of each invocation the runner makes for the targets
+fn invocations(targets: &[TargetFilter]) -> Vec<Vec<String>> {
+    CargoRunner
+        .split_targets(targets)
+        .iter()
+        .map(|group| CargoRunner.filter_args("api", group))
+        .collect()
+}
+
+#[test]
+fn test_filter_args_narrow_modules() {
+    let targets = [
+        filter(TestTarget::Lib, Some(&["foo", "bar::baz"])),
+        filter(TestTarget::Bin("cli".to_string()), Some(&["foo"])),
+    ];
+
+    assert_eq!(
+        invocations(&targets),
+        [["--lib", "--bin", "cli", "--", "foo::", "bar::baz::"]]
+    );
+}
+
+#[test]
+fn test_lib_module_with_integration_tests_runs_separately() {
+    // What a change to `src/foo.rs` selects in a crate with `tests/it.rs`
+    let targets = [
+        filter(TestTarget::Lib, Some(&["foo"])),
+        filter(TestTarget::Doc, None),
+        filter(TestTarget::Test("it".to_string()), None),
+    ];
+
+    assert_eq!(
+        invocations(&targets),
+        [vec!["--lib", "--", "foo::"], vec!["--test", "it"]]
+    );
+}
+
+#[test]
+fn test_full_targets_run_together() {
+    let targets = [
+        filter(TestTarget::Lib, None),
+        filter(TestTarget::Test("it".to_string()), None),
+    ];
+
+    assert_eq!(invocations(&targets), [["--lib", "--test", "it"]]);
+}
+
+#[test]
+fn test_doc_test_command_builds_like_the_tests() {
+    let args: Vec<String> = [
+        "--release",
+        "--features",
+        "db,serde",
+        "--target=x86_64-unknown-linux-gnu",
+        "--profile",
+        "ci",
+        "--no-fail-fast",
+        "--",
+        "--test-threads",
+        "1",
+    ]
+    .map(String::from)
+    .to_vec();
+
+    let cmd = CargoRunner.doc_test_command("api", &args).unwrap();
+
+    assert_eq!(
+        cmd.get_args().collect::<Vec<_>>(),
+        [
+            "test",
+            "--doc",
+            "-p",
+            "api",
+            "--release",
+            "--features",
+            "db,serde",
+            "--target",
+            "x86_64-unknown-linux-gnu",
+            "--profile",
+            "ci",
+        ]
+    );
+}
@@ -0,0 +1,43 @@
+use super::TestRunner;
+use crate::testing::filter::TargetFilter;
+use std::process::Command;
+
+/// Runner that only compiles crates, for modes that neither run tests nor lint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompileRunner {
+    /// `cargo check --all-targets`
+    Check,
+    /// `cargo test --no-run`
+    Build,
+}
+
+impl TestRunner for CompileRunner {
+    fn command(&self, crate_name: &str) -> Command {
+        let mut cmd = Command::new("cargo");
+        match self {
+            CompileRunner::Check => cmd.args(["check", "--all-targets", "-p", crate_name]),
+            CompileRunner::Build => cmd.args(["test", "--no-run", "-p", crate_name]),
+        };
+        cmd
+    }
+
+    fn filter_args(&self, _: &str, _: &[TargetFilter]) -> Vec<String> {
+        // Every target of the crate is compiled
+        Vec::new()
+    }
+
+    fn is_installed(&self) -> bool {
+        true
+    }
+
+    fn installation_instructions(&self) -> String {
+        "cargo should be available since you're running this as a cargo command".to_string()
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            CompileRunner::Check => "check",
+            CompileRunner::Build => "build",
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use crate::test_runner::{CompileRunner, TestRunner};
+
+#[test]
+fn test_commands() {
+    let args = |runner: CompileRunner| {
+        runner
+            .command("api")
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        args(CompileRunner::Check),
+        ["check", "--all-targets", "-p", "api"]
+    );
+    assert_eq!(
+        args(CompileRunner::Build),
+        ["test", "--no-run", "-p", "api"]
+    );
+}
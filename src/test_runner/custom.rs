@@ -0,0 +1,161 @@
+use super::TestRunner;
+use crate::{
+    config::CustomRunnerConfig,
+    error::AppError,
+    metadata::Crates,
+    testing::{
+        cases::{self, TestCase},
+        filter::TargetFilter,
+    },
+};
+use colored::Colorize;
+use indexmap::IndexMap;
+use std::{
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+/// Placeholders that can appear in the arguments of a command template
+const PLACEHOLDERS: &[&str] = &["{crate}", "{manifest_path}", "{crate_dir}"];
+
+/// Test runner configured as a command template, such as `cargo miri test -p {crate}`
+#[derive(Debug, Clone)]
+pub struct CustomRunner {
+    template: Vec<String>,
+    check: Option<Vec<String>>,
+    install: Option<String>,
+    /// Directory of each crate in the workspace
+    crate_dirs: IndexMap<String, PathBuf>,
+}
+
+impl CustomRunner {
+    /// Build the runner from the configuration, or `None` if no command is configured
+    pub fn from_config(
+        config: Option<&CustomRunnerConfig>,
+        crates: &Crates,
+    ) -> Result<Option<Self>, AppError> {
+        let Some(config) = config else {
+            return Ok(None);
+        };
+
+        let template = split_command(&config.command, "command")?;
+        if template.is_empty() {
+            return Err(AppError::InvalidConfig {
+                reason: "`custom-runner.command` is empty".to_string(),
+            });
+        }
+
+        if let Some(placeholder) = template.iter().find_map(|arg| unknown_placeholder(arg)) {
+            return Err(AppError::InvalidConfig {
+                reason: format!(
+                    "unknown placeholder `{}` in `custom-runner.command`, expected one of {}",
+                    placeholder,
+                    PLACEHOLDERS.join(", ")
+                ),
+            });
+        }
+
+        let check = config
+            .check
+            .as_deref()
+            .map(|check| split_command(check, "check"))
+            .transpose()?
+            .filter(|check| !check.is_empty());
+
+        Ok(Some(CustomRunner {
+            template,
+            check,
+            install: config.install.clone(),
+            crate_dirs: crates
+                .iter()
+                .map(|info| (info.name.clone(), info.path.clone()))
+                .collect(),
+        }))
+    }
+
+    /// The arguments of the command for a crate, placeholders included
+    pub fn expand(&self, crate_name: &str) -> Vec<String> {
+        let crate_dir = self.crate_dirs.get(crate_name).cloned().unwrap_or_default();
+        let manifest_path = crate_dir.join("Cargo.toml");
+
+        self.template
+            .iter()
+            .map(|arg| {
+                arg.replace("{crate}", crate_name)
+                    .replace("{manifest_path}", &manifest_path.to_string_lossy())
+                    .replace("{crate_dir}", &crate_dir.to_string_lossy())
+            })
+            .collect()
+    }
+}
+
+impl TestRunner for CustomRunner {
+    fn command(&self, crate_name: &str) -> Command {
+        let args = self.expand(crate_name);
+        let mut cmd = Command::new(&args[0]);
+        cmd.args(&args[1..]);
+        cmd
+    }
+
+    fn filter_args(&self, _: &str, _: &[TargetFilter]) -> Vec<String> {
+        // The arguments the command accepts are unknown, so the whole crate is tested
+        Vec::new()
+    }
+
+    fn parse_test_cases(&self, output: &str) -> Vec<TestCase> {
+        // Commands wrapping `cargo test` print libtest output, anything else yields no cases
+        cases::parse_libtest_output(output)
+    }
+
+    fn is_installed(&self) -> bool {
+        let Some(check) = &self.check else {
+            return true;
+        };
+
+        Command::new(&check[0])
+            .args(&check[1..])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    }
+
+    fn installation_instructions(&self) -> String {
+        match &self.install {
+            Some(install) => install.clone(),
+            None => format!(
+                "check that `{}` succeeds, or set `custom-runner.install` to describe how to install the runner",
+                shell_words::join(self.check.as_deref().unwrap_or_default())
+                    .bold()
+                    .yellow()
+            ),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "custom"
+    }
+}
+
+/// Split a command into arguments the way a POSIX shell would, honouring quotes and escapes
+fn split_command(command: &str, setting: &str) -> Result<Vec<String>, AppError> {
+    shell_words::split(command).map_err(|e| AppError::InvalidConfig {
+        reason: format!("cannot parse `custom-runner.{}`: {}", setting, e),
+    })
+}
+
+/// Find a `{...}` segment of an argument that is not a known placeholder
+fn unknown_placeholder(arg: &str) -> Option<&str> {
+    let mut rest = arg;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}')? + start + 1;
+        let candidate = &rest[start..end];
+        if !PLACEHOLDERS.contains(&candidate) {
+            return Some(candidate);
+        }
+        rest = &rest[end..];
+    }
+
+    None
+}
@@ -0,0 +1,150 @@
+use std::path::PathBuf;
+
+use crate::config::CustomRunnerConfig;
+use crate::error::AppError;
+use crate::metadata::{CrateInfo, Crates};
+use crate::test_runner::{CustomRunner, TestRunner};
+
+fn crates() -> Crates {
+    [CrateInfo {
+        name: "api".to_string(),
+        path: PathBuf::from("/workspace/crates/api"),
+    }]
+    .into_iter()
+    .collect()
+}
+
+fn config(command: &str) -> CustomRunnerConfig {
+    CustomRunnerConfig {
+        command: command.to_string(),
+        ..Default::default()
+    }
+}
+
+fn runner(config: CustomRunnerConfig) -> CustomRunner {
+    CustomRunner::from_config(Some(&config), &crates())
+        .unwrap()
+        .unwrap()
+}
+
+#[test]
+fn test_no_custom_runner_configured() {
+    assert!(CustomRunner::from_config(None, &crates())
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_expand_placeholders() {
+    let runner = runner(config(
+        "cargo llvm-cov --manifest-path {manifest_path} -p {crate} --output-dir={crate_dir}/cov",
+    ));
+
+    assert_eq!(
+        runner.expand("api"),
+        vec![
+            "cargo",
+            "llvm-cov",
+            "--manifest-path",
+            "/workspace/crates/api/Cargo.toml",
+            "-p",
+            "api",
+            "--output-dir=/workspace/crates/api/cov",
+        ]
+    );
+}
+
+#[test]
+fn test_command_uses_first_word_as_program() {
+    let runner = runner(config(
+        "cross test --target aarch64-unknown-linux-gnu -p {crate}",
+    ));
+
+    let cmd = runner.command("api");
+
+    assert_eq!(cmd.get_program(), "cross");
+    assert_eq!(
+        cmd.get_args().collect::<Vec<_>>(),
+        vec!["test", "--target", "aarch64-unknown-linux-gnu", "-p", "api"]
+    );
+}
+
+#[test]
+fn test_rejects_empty_command() {
+    assert!(matches!(
+        CustomRunner::from_config(Some(&config("  ")), &crates()),
+        Err(AppError::InvalidConfig { .. })
+    ));
+}
+
+#[test]
+fn test_split_command_with_quotes() {
+    let runner = runner(config(
+        r#"cargo test -p {crate} -- --skip "slow tests" --exact 'it::a b' path\ with\ spaces"#,
+    ));
+
+    assert_eq!(
+        runner.expand("api"),
+        vec![
+            "cargo",
+            "test",
+            "-p",
+            "api",
+            "--",
+            "--skip",
+            "slow tests",
+            "--exact",
+            "it::a b",
+            "path with spaces",
+        ]
+    );
+}
+
+#[test]
+fn test_rejects_unbalanced_quotes() {
+    let result = CustomRunner::from_config(Some(&config("cargo test --skip \"slow")), &crates());
+
+    let Err(AppError::InvalidConfig { reason }) = result else {
+        panic!("expected an invalid configuration");
+    };
+    assert!(reason.contains("custom-runner.command"));
+
+    let result = CustomRunner::from_config(
+        Some(&CustomRunnerConfig {
+            check: Some("cargo miri --version '".to_string()),
+            ..config("cargo miri test -p {crate}")
+        }),
+        &crates(),
+    );
+    assert!(matches!(result, Err(AppError::InvalidConfig { .. })));
+}
+
+#[test]
+fn test_rejects_unknown_placeholder() {
+    let result = CustomRunner::from_config(Some(&config("cargo test -p {package}")), &crates());
+
+    let Err(AppError::InvalidConfig { reason }) = result else {
+        panic!("expected an invalid configuration");
+    };
+    assert!(reason.contains("{package}"));
+}
+
+#[test]
+fn test_installed_without_check() {
+    assert!(runner(config("cargo miri test -p {crate}")).is_installed());
+}
+
+#[test]
+fn test_failing_check_means_not_installed() {
+    let runner = runner(CustomRunnerConfig {
+        command: "cargo miri test -p {crate}".to_string(),
+        check: Some("cargo-test-changed-missing-binary --version".to_string()),
+        install: Some("rustup +nightly component add miri".to_string()),
+    });
+
+    assert!(!runner.is_installed());
+    assert_eq!(
+        runner.installation_instructions(),
+        "rustup +nightly component add miri"
+    );
+}
@@ -0,0 +1,82 @@
+use super::TestRunner;
+use crate::testing::{
+    diagnostics::{self, Diagnostic},
+    filter::TargetFilter,
+};
+use colored::Colorize;
+use std::process::{Command, Stdio};
+
+/// Runner that lints crates instead of testing them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintRunner {
+    /// `cargo clippy --all-targets`, with diagnostics read from its JSON messages
+    Clippy,
+    /// `cargo fmt --check`
+    Fmt,
+}
+
+impl LintRunner {
+    fn subcommand(&self) -> &'static str {
+        match self {
+            LintRunner::Clippy => "clippy",
+            LintRunner::Fmt => "fmt",
+        }
+    }
+}
+
+impl TestRunner for LintRunner {
+    fn command(&self, crate_name: &str) -> Command {
+        let mut cmd = Command::new("cargo");
+        match self {
+            LintRunner::Clippy => cmd.args([
+                "clippy",
+                "--all-targets",
+                "--message-format=json",
+                "-p",
+                crate_name,
+            ]),
+            LintRunner::Fmt => cmd.args(["fmt", "--check", "-p", crate_name]),
+        };
+        cmd
+    }
+
+    fn filter_args(&self, _: &str, _: &[TargetFilter]) -> Vec<String> {
+        // Lints apply to the whole crate
+        Vec::new()
+    }
+
+    fn parse_diagnostics(&self, output: &str) -> Option<(Vec<Diagnostic>, String)> {
+        match self {
+            LintRunner::Clippy => Some(diagnostics::parse_cargo_messages(output)),
+            LintRunner::Fmt => None,
+        }
+    }
+
+    fn is_installed(&self) -> bool {
+        Command::new("cargo")
+            .args([self.subcommand(), "--version"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    }
+
+    fn installation_instructions(&self) -> String {
+        let component = match self {
+            LintRunner::Clippy => "clippy",
+            LintRunner::Fmt => "rustfmt",
+        };
+        format!(
+            "to install {}, run '{}'",
+            self.subcommand(),
+            format!("rustup component add {}", component)
+                .bold()
+                .yellow()
+        )
+    }
+
+    fn name(&self) -> &'static str {
+        self.subcommand()
+    }
+}
@@ -1,38 +1,199 @@
+use cargo_metadata::semver::Version;
 use clap::ValueEnum;
+use serde::Deserialize;
 use std::process::Command;
 
+use crate::{
+    error::AppError,
+    metadata::Workspace,
+    testing::{cases::TestCase, diagnostics::Diagnostic, filter::TargetFilter},
+};
+
 mod cargo;
+mod compile;
+mod custom;
+mod lint;
 mod nextest;
+mod overrides;
+mod version;
+
+#[cfg(test)]
+mod cargo_tests;
+#[cfg(test)]
+mod compile_tests;
+#[cfg(test)]
+mod custom_tests;
+#[cfg(test)]
+mod nextest_tests;
+#[cfg(test)]
+mod overrides_tests;
+#[cfg(test)]
+mod version_tests;
 
 pub use cargo::CargoRunner;
+pub use compile::CompileRunner;
+pub use custom::CustomRunner;
+pub use lint::LintRunner;
 pub use nextest::NextestRunner;
+pub use overrides::{RunnerOverride, Runners};
+pub use version::Capability;
 
 pub trait TestRunner {
     /// Get the command to run the tests
     fn command(&self, crate_name: &str) -> Command;
 
+    /// Get the arguments that restrict a run to the given targets and modules
+    fn filter_args(&self, crate_name: &str, targets: &[TargetFilter]) -> Vec<String>;
+
+    /// Split the selected targets into groups that are run by separate invocations, for
+    /// runners that cannot narrow some targets to modules while running others in full
+    fn split_targets(&self, targets: &[TargetFilter]) -> Vec<Vec<TargetFilter>> {
+        vec![targets.to_vec()]
+    }
+
+    /// Get the arguments that make the runner retry failing tests itself, or `None` if
+    /// failing crates have to be run again
+    fn retry_args(&self, _retries: usize) -> Option<Vec<String>> {
+        None
+    }
+
+    /// Split a run into a blocking pass that excludes the given tests and a non-blocking pass
+    /// of only those tests, each narrowing the given filter arguments, or `None` if the
+    /// runner cannot select tests by name
+    fn quarantine_args(
+        &self,
+        _filter_args: &[String],
+        _tests: &[String],
+    ) -> Option<(Vec<String>, Vec<String>)> {
+        None
+    }
+
+    /// Whether the runner's own command runs the crate's doc-tests when it is not restricted
+    /// to targets
+    fn runs_doc_tests(&self) -> bool {
+        false
+    }
+
+    /// Get the command that runs only the crate's doc-tests, or `None` if the runner cannot
+    /// run them
+    ///
+    /// `args` are the arguments given to the runner's own command, so the doc-tests can be
+    /// built with the same features and profile.
+    fn doc_test_command(&self, _crate_name: &str, _args: &[String]) -> Option<Command> {
+        None
+    }
+
+    /// Parse compiler diagnostics from the captured output of a run, along with the output to
+    /// show for the crate, or `None` if the runner does not print machine-readable messages
+    fn parse_diagnostics(&self, _output: &str) -> Option<(Vec<Diagnostic>, String)> {
+        None
+    }
+
+    /// Parse individual test results from the captured output of a run
+    fn parse_test_cases(&self, _output: &str) -> Vec<TestCase> {
+        Vec::new()
+    }
+
     /// Check if the test runner is installed
     fn is_installed(&self) -> bool;
 
     /// Get the installation instructions for the test runner
     fn installation_instructions(&self) -> String;
 
+    /// Detect the installed version of the test runner, or `None` if it cannot be determined
+    fn version(&self) -> Option<Version> {
+        None
+    }
+
+    /// Get the earliest version of the test runner with the capability, or `None` if every
+    /// version has it
+    fn minimum_version(&self, _capability: Capability) -> Option<Version> {
+        None
+    }
+
+    /// Get the instructions to upgrade the test runner to a newer version
+    fn upgrade_instructions(&self) -> String {
+        self.installation_instructions()
+    }
+
     /// Get the name of the test runner
     fn name(&self) -> &'static str;
 }
 
-#[derive(ValueEnum, Debug, Clone, Default)]
+/// Flags shared by cargo and the runners wrapping it that change what is built
+const CARGO_BUILD_FLAGS: &[&str] = &[
+    "--release",
+    "-r",
+    "--all-features",
+    "--no-default-features",
+    "--locked",
+    "--frozen",
+    "--offline",
+];
+
+/// Options shared by cargo and the runners wrapping it that change what is built, with a value
+const CARGO_BUILD_OPTIONS: &[&str] = &["--features", "-F", "--target"];
+
+/// Pick the arguments that change what cargo builds, such as features and the profile, out of
+/// the arguments given to a runner, so a `cargo test` run alongside it builds the same way
+///
+/// `profile_option` is the runner's option for the cargo profile, which is passed on as
+/// `--profile`. Arguments after `--` are for the test binaries and are left out.
+fn cargo_build_args(args: &[String], profile_option: &str) -> Vec<String> {
+    let mut build_args = Vec::new();
+    let mut args = args.iter().take_while(|arg| *arg != "--");
+
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value)),
+            _ => (arg.as_str(), None),
+        };
+
+        if CARGO_BUILD_FLAGS.contains(&name) && inline_value.is_none() {
+            build_args.push(arg.clone());
+            continue;
+        }
+
+        let option = if name == profile_option {
+            "--profile"
+        } else if CARGO_BUILD_OPTIONS.contains(&name) {
+            name
+        } else {
+            continue;
+        };
+        let value = match inline_value {
+            Some(value) => Some(value.to_string()),
+            None => args.next().cloned(),
+        };
+        if let Some(value) = value {
+            build_args.extend([option.to_string(), value]);
+        }
+    }
+
+    build_args
+}
+
+#[derive(ValueEnum, Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum TestRunnerType {
     #[default]
     Cargo,
     Nextest,
+    /// The command configured as `custom-runner` in the workspace metadata
+    Custom,
 }
 
 impl TestRunnerType {
-    pub fn create(&self) -> Box<dyn TestRunner> {
+    pub fn create(&self, workspace: &Workspace) -> Result<Box<dyn TestRunner>, AppError> {
         match self {
-            TestRunnerType::Cargo => Box::new(CargoRunner),
-            TestRunnerType::Nextest => Box::new(NextestRunner),
+            TestRunnerType::Cargo => Ok(Box::new(CargoRunner)),
+            TestRunnerType::Nextest => Ok(Box::new(NextestRunner)),
+            TestRunnerType::Custom => match &workspace.custom_runner {
+                Some(runner) => Ok(Box::new(runner.clone())),
+                None => Err(AppError::InvalidConfig {
+                    reason: "`-r custom` requires a `custom-runner` command in `[workspace.metadata.test-changed]`".to_string(),
+                }),
+            },
         }
     }
 }
@@ -1,4 +1,10 @@
-use super::TestRunner;
+use super::{cargo_build_args, version, Capability, TestRunner};
+use crate::testing::{
+    cases::{self, TestCase},
+    diagnostics::Diagnostic,
+    filter::{TargetFilter, TestTarget},
+};
+use cargo_metadata::semver::Version;
 use colored::Colorize;
 use std::process::Command;
 
@@ -8,9 +14,89 @@ impl TestRunner for NextestRunner {
     fn command(&self, crate_name: &str) -> Command {
         let mut cmd = Command::new("cargo");
         cmd.args(["nextest", "run", "--no-tests", "pass", "-p", crate_name]);
+        // Per-test results are read from the JSON events, which nextest prints whatever the
+        // status level configured by the user
+        cmd.args(["--message-format", "libtest-json"]);
+        cmd.env("NEXTEST_EXPERIMENTAL_LIBTEST_JSON", "1");
         cmd
     }
 
+    fn filter_args(&self, crate_name: &str, targets: &[TargetFilter]) -> Vec<String> {
+        let expressions = targets
+            .iter()
+            .filter_map(|filter| {
+                let binary_id = match &filter.target {
+                    TestTarget::Lib => crate_name.to_string(),
+                    TestTarget::Bin(name) => format!("{}::bin/{}", crate_name, name),
+                    TestTarget::Test(name) => format!("{}::{}", crate_name, name),
+                    // Nextest cannot run doc-tests, see `doc_test_command`
+                    TestTarget::Doc => return None,
+                };
+
+                Some(match &filter.modules {
+                    Some(modules) => {
+                        let tests = modules
+                            .iter()
+                            .map(|module| format!("test(/^{}::/)", module))
+                            .collect::<Vec<_>>()
+                            .join(" | ");
+                        format!("(binary_id({}) & ({}))", binary_id, tests)
+                    }
+                    None => format!("binary_id({})", binary_id),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        vec!["-E".to_string(), expressions.join(" | ")]
+    }
+
+    fn retry_args(&self, retries: usize) -> Option<Vec<String>> {
+        Some(vec!["--retries".to_string(), retries.to_string()])
+    }
+
+    fn quarantine_args(
+        &self,
+        filter_args: &[String],
+        tests: &[String],
+    ) -> Option<(Vec<String>, Vec<String>)> {
+        let filter = match filter_args {
+            [flag, expression] if flag == "-E" => expression.as_str(),
+            _ => "all()",
+        };
+        let quarantined = tests
+            .iter()
+            .map(|test| format!("test(={})", escape_matcher(test)))
+            .collect::<Vec<_>>()
+            .join(" | ");
+
+        Some((
+            vec![
+                "-E".to_string(),
+                format!("({}) & not ({})", filter, quarantined),
+            ],
+            vec![
+                "-E".to_string(),
+                format!("({}) & ({})", filter, quarantined),
+            ],
+        ))
+    }
+
+    fn doc_test_command(&self, crate_name: &str, args: &[String]) -> Option<Command> {
+        // Nextest cannot run doc-tests, and its own `--profile` is not a cargo profile
+        let mut cmd = Command::new("cargo");
+        cmd.args(["test", "--doc", "-p", crate_name]);
+        cmd.args(cargo_build_args(args, "--cargo-profile"));
+        Some(cmd)
+    }
+
+    fn parse_diagnostics(&self, output: &str) -> Option<(Vec<Diagnostic>, String)> {
+        Some((Vec::new(), cases::strip_libtest_json(output)))
+    }
+
+    fn parse_test_cases(&self, output: &str) -> Vec<TestCase> {
+        cases::parse_nextest_output(output)
+    }
+
     fn is_installed(&self) -> bool {
         std::process::Command::new("cargo")
             .args(["nextest", "--version"])
@@ -28,7 +114,44 @@ impl TestRunner for NextestRunner {
         )
     }
 
+    fn version(&self) -> Option<Version> {
+        version::detect_version(Command::new("cargo").args(["nextest", "--version"]))
+    }
+
+    fn minimum_version(&self, capability: Capability) -> Option<Version> {
+        match capability {
+            Capability::NoTests => Some(Version::new(0, 9, 75)),
+            Capability::TestSelection => Some(Version::new(0, 9, 13)),
+            Capability::Retries => None,
+            Capability::MachineReadableOutput => Some(Version::new(0, 9, 66)),
+        }
+    }
+
+    fn upgrade_instructions(&self) -> String {
+        format!(
+            "to upgrade nextest, run '{}'",
+            "cargo install cargo-nextest".bold().yellow()
+        )
+    }
+
     fn name(&self) -> &'static str {
         "nextest"
     }
 }
+
+/// Escape a test name for use as a filterset matcher, where `)` would end the matcher, `,`
+/// would separate arguments and surrounding whitespace would be trimmed
+fn escape_matcher(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            '\\' | ')' | ',' | '/' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c if c.is_whitespace() => escaped.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,96 @@
+use crate::test_runner::{NextestRunner, TestRunner};
+
+#[test]
+fn test_quarantine_args() {
+    let filter_args = ["-E".to_string(), "binary_id(api)".to_string()];
+    let tests = ["tests::flaky".to_string()];
+
+    let (blocking, non_blocking) = NextestRunner.quarantine_args(&filter_args, &tests).unwrap();
+
+    assert_eq!(
+        blocking,
+        ["-E", "(binary_id(api)) & not (test(=tests::flaky))"]
+    );
+    // `--no-tests pass` is already part of the command, and nextest rejects it twice
+    assert_eq!(
+        non_blocking,
+        ["-E", "(binary_id(api)) & (test(=tests::flaky))"]
+    );
+}
+
+#[test]
+fn test_quarantine_args_escape_test_names() {
+    let tests = [
+        "cases::parse(a, b)".to_string(),
+        "tests::a|b & c/d\\e".to_string(),
+    ];
+
+    let (_, non_blocking) = NextestRunner.quarantine_args(&[], &tests).unwrap();
+
+    assert_eq!(
+        non_blocking[1],
+        r"(all()) & (test(=cases::parse(a\,\u{20}b\)) | test(=tests::a|b\u{20}&\u{20}c\/d\\e))"
+    );
+}
+
+#[test]
+fn test_doc_test_command_builds_like_the_tests() {
+    let args: Vec<String> = [
+        "--profile",
+        "ci",
+        "--cargo-profile",
+        "bench-fast",
+        "--all-features",
+        "-F",
+        "db",
+        "--retries",
+        "2",
+    ]
+    .map(String::from)
+    .to_vec();
+
+    let cmd = NextestRunner.doc_test_command("api", &args).unwrap();
+
+    // Nextest's `--profile` selects a nextest profile, not a cargo one
+    assert_eq!(
+        cmd.get_args().collect::<Vec<_>>(),
+        [
+            "test",
+            "--doc",
+            "-p",
+            "api",
+            "--profile",
+            "bench-fast",
+            "--all-features",
+            "-F",
+            "db",
+        ]
+    );
+}
+
+#[test]
+fn test_command_prints_libtest_json() {
+    let cmd = NextestRunner.command("api");
+
+    let args = cmd.get_args().collect::<Vec<_>>();
+    assert!(args
+        .windows(2)
+        .any(|pair| pair == ["--message-format", "libtest-json"]));
+    assert!(cmd
+        .get_envs()
+        .any(|(key, value)| key == "NEXTEST_EXPERIMENTAL_LIBTEST_JSON"
+            && value == Some("1".as_ref())));
+}
+
+#[test]
+fn test_parse_diagnostics_hides_libtest_json() {
+    let output = r#"{"type":"suite","event":"started","test_count":1}
+{"type":"test","event":"failed","name":"api$tests::fails","stdout":"assertion failed"}
+        FAIL [   0.010s] api tests::fails
+"#;
+
+    let (diagnostics, readable) = NextestRunner.parse_diagnostics(output).unwrap();
+
+    assert!(diagnostics.is_empty());
+    assert_eq!(readable, "        FAIL [   0.010s] api tests::fails\n");
+}
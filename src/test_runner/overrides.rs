@@ -0,0 +1,122 @@
+use cargo_metadata::Metadata;
+use indexmap::IndexMap;
+
+use super::{CompileRunner, LintRunner, TestRunner, TestRunnerType};
+use crate::{
+    config::Config,
+    error::AppError,
+    metadata::{self, Workspace},
+    testing::plan::RunMode,
+};
+
+/// Runner settings configured for a single crate
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunnerOverride {
+    /// Runner used instead of the one given with `-r`
+    pub runner: Option<TestRunnerType>,
+    /// Arguments passed before those given on the command line
+    pub args: Vec<String>,
+    pub env: IndexMap<String, String>,
+}
+
+impl RunnerOverride {
+    /// Collect the crates configured with a runner, arguments or environment variables
+    pub fn from_config(
+        config: &Config,
+        metadata: &Metadata,
+    ) -> Result<IndexMap<String, Self>, AppError> {
+        let mut overrides = IndexMap::new();
+
+        for (crate_name, crate_config) in &config.crates {
+            let runner_override = RunnerOverride {
+                runner: crate_config.runner.clone(),
+                args: crate_config.args.clone(),
+                env: crate_config.env.clone(),
+            };
+            if runner_override == RunnerOverride::default() {
+                continue;
+            }
+
+            metadata::verify_crates_exist(metadata, std::slice::from_ref(crate_name))?;
+            overrides.insert(crate_name.clone(), runner_override);
+        }
+
+        Ok(overrides)
+    }
+}
+
+/// The runner, arguments and environment used to test each crate
+pub struct Runners {
+    default: Box<dyn TestRunner>,
+    /// Runners for crates configured with a runner other than the default
+    crate_runners: IndexMap<String, Box<dyn TestRunner>>,
+    overrides: IndexMap<String, RunnerOverride>,
+    /// Arguments for crates without configured arguments
+    default_args: Vec<String>,
+}
+
+impl Runners {
+    pub fn new(runner_type: &TestRunnerType, workspace: &Workspace) -> Result<Self, AppError> {
+        let mut crate_runners = IndexMap::new();
+        for (crate_name, runner_override) in &workspace.runner_overrides {
+            match &runner_override.runner {
+                Some(crate_runner) if crate_runner != runner_type => {
+                    crate_runners.insert(crate_name.clone(), crate_runner.create(workspace)?);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Runners {
+            default: runner_type.create(workspace)?,
+            crate_runners,
+            overrides: workspace.runner_overrides.clone(),
+            default_args: Vec::new(),
+        })
+    }
+
+    /// The runners for a mode, where modes that only compile or lint crates use the same
+    /// command for every crate and ignore the configured overrides
+    pub fn for_mode(
+        mode: RunMode,
+        runner_type: &TestRunnerType,
+        workspace: &Workspace,
+    ) -> Result<Self, AppError> {
+        let (default, default_args): (Box<dyn TestRunner>, _) = match mode {
+            RunMode::Test => return Runners::new(runner_type, workspace),
+            RunMode::Check => (Box::new(CompileRunner::Check), Vec::new()),
+            RunMode::Build => (Box::new(CompileRunner::Build), Vec::new()),
+            RunMode::Clippy => (Box::new(LintRunner::Clippy), workspace.clippy_args.clone()),
+            RunMode::Fmt => (Box::new(LintRunner::Fmt), Vec::new()),
+        };
+
+        Ok(Runners {
+            default,
+            crate_runners: IndexMap::new(),
+            overrides: IndexMap::new(),
+            default_args,
+        })
+    }
+
+    pub fn runner(&self, crate_name: &str) -> &dyn TestRunner {
+        self.crate_runners
+            .get(crate_name)
+            .unwrap_or(&self.default)
+            .as_ref()
+    }
+
+    /// Arguments configured for the crate
+    pub fn args(&self, crate_name: &str) -> &[String] {
+        self.overrides
+            .get(crate_name)
+            .map_or(&self.default_args, |runner_override| &runner_override.args)
+    }
+
+    /// Environment variables configured for the crate
+    pub fn env(&self, crate_name: &str) -> impl Iterator<Item = (&String, &String)> {
+        self.overrides
+            .get(crate_name)
+            .into_iter()
+            .flat_map(|runner_override| &runner_override.env)
+    }
+}
@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+
+use cargo_metadata::semver::Version;
+use cargo_metadata::{Metadata, MetadataBuilder, PackageBuilder, PackageId};
+use indexmap::IndexMap;
+
+use crate::config::Config;
+use crate::error::AppError;
+use crate::metadata::{self, CrateMappings, FileDependencies, IgnorePatterns, Workspace};
+use crate::quarantine::Quarantine;
+use crate::test_runner::{RunnerOverride, Runners, TestRunnerType};
+
+fn create_metadata() -> Metadata {
+    let packages: Vec<_> = ["api", "db"]
+        .into_iter()
+        .map(|name| {
+            PackageBuilder::new(
+                name,
+                Version::new(1, 0, 0),
+                PackageId {
+                    repr: name.to_string(),
+                },
+                format!("/workspace/{}/Cargo.toml", name),
+            )
+            .build()
+            .unwrap()
+        })
+        .collect();
+
+    MetadataBuilder::default()
+        .packages(packages)
+        .workspace_root("/workspace")
+        .target_directory("/workspace/target")
+        .workspace_members(vec![])
+        .workspace_default_members(cargo_metadata::WorkspaceDefaultMembers::default())
+        .workspace_metadata(serde_json::Value::Null)
+        .resolve(None)
+        .version(1usize)
+        .build()
+        .unwrap()
+}
+
+fn create_config(crates: serde_json::Value) -> Config {
+    serde_json::from_value(serde_json::json!({ "crates": crates })).unwrap()
+}
+
+fn create_workspace(config: &Config) -> Workspace {
+    let metadata = create_metadata();
+
+    Workspace {
+        root: PathBuf::from("/workspace"),
+        crates: metadata::get_workspace_crates(&metadata).unwrap(),
+        runner_overrides: RunnerOverride::from_config(config, &metadata).unwrap(),
+        metadata,
+        dep_info: Vec::new(),
+        file_dependencies: FileDependencies::default(),
+        mappings: CrateMappings::default(),
+        ignore_patterns: IgnorePatterns::default(),
+        quarantine: Quarantine::default(),
+        custom_runner: None,
+        clippy_args: Vec::new(),
+    }
+}
+
+#[test]
+fn test_overrides_skip_crates_without_runner_settings() {
+    let config = create_config(serde_json::json!({
+        "api": { "triggered-by": ["proto/**"] },
+        "db": { "args": ["--", "--test-threads=1"], "env": { "DATABASE_URL": "sqlite::memory:" } }
+    }));
+
+    let overrides = RunnerOverride::from_config(&config, &create_metadata()).unwrap();
+
+    assert_eq!(
+        overrides,
+        IndexMap::from([(
+            "db".to_string(),
+            RunnerOverride {
+                runner: None,
+                args: vec!["--".to_string(), "--test-threads=1".to_string()],
+                env: IndexMap::from([("DATABASE_URL".to_string(), "sqlite::memory:".to_string())]),
+            }
+        )])
+    );
+}
+
+#[test]
+fn test_overrides_reject_unknown_crate() {
+    let config = create_config(serde_json::json!({ "web": { "runner": "nextest" } }));
+
+    assert!(matches!(
+        RunnerOverride::from_config(&config, &create_metadata()),
+        Err(AppError::UnknownCrate { .. })
+    ));
+}
+
+#[test]
+fn test_runners_use_crate_runner() {
+    let config = create_config(serde_json::json!({
+        "api": { "runner": "nextest", "args": ["--profile", "ci"] }
+    }));
+
+    let runners = Runners::new(&TestRunnerType::Cargo, &create_workspace(&config)).unwrap();
+
+    assert_eq!(runners.runner("api").name(), "nextest");
+    assert_eq!(runners.args("api"), ["--profile", "ci"]);
+    assert_eq!(runners.runner("db").name(), "cargo");
+    assert!(runners.args("db").is_empty());
+    assert_eq!(runners.env("db").count(), 0);
+}
+
+#[test]
+fn test_runners_require_custom_runner_config() {
+    let config = create_config(serde_json::json!({ "api": { "runner": "custom" } }));
+
+    assert!(matches!(
+        Runners::new(&TestRunnerType::Cargo, &create_workspace(&config)),
+        Err(AppError::InvalidConfig { .. })
+    ));
+}
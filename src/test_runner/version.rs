@@ -0,0 +1,45 @@
+use cargo_metadata::semver::Version;
+use std::process::{Command, Stdio};
+
+/// A feature of a test runner that only some of its versions support
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    /// Treating a crate without tests as passing
+    NoTests,
+    /// Restricting a run to individual tests, for module-level precision and quarantined tests
+    TestSelection,
+    /// Retrying failing tests within a run
+    Retries,
+    /// Printing the result of each test in a machine-readable format, whatever the output
+    /// settings of the user
+    MachineReadableOutput,
+}
+
+impl Capability {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Capability::NoTests => "passing crates without tests",
+            Capability::TestSelection => "selecting individual tests",
+            Capability::Retries => "retrying failing tests",
+            Capability::MachineReadableOutput => "machine-readable test results",
+        }
+    }
+}
+
+/// Run a command that prints a version, such as `cargo nextest --version`, and parse the
+/// first word of its output that is a version
+pub fn detect_version(cmd: &mut Command) -> Option<Version> {
+    let output = cmd.stderr(Stdio::null()).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    parse_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Find the first word that is a version, as in `cargo-nextest 0.9.72 (a1b2c3 2024-07-01)`
+pub fn parse_version(output: &str) -> Option<Version> {
+    output
+        .split_whitespace()
+        .find_map(|word| Version::parse(word.trim_start_matches('v')).ok())
+}
@@ -0,0 +1,35 @@
+use cargo_metadata::semver::Version;
+
+use crate::test_runner::version::parse_version;
+use crate::test_runner::{Capability, NextestRunner, TestRunner};
+
+#[test]
+fn test_parse_nextest_version() {
+    assert_eq!(
+        parse_version("cargo-nextest 0.9.72 (41d6b9e5b 2024-07-01)\nrelease: 0.9.72\n"),
+        Some(Version::new(0, 9, 72))
+    );
+}
+
+#[test]
+fn test_parse_prefixed_version() {
+    assert_eq!(parse_version("tool v1.2.3"), Some(Version::new(1, 2, 3)));
+}
+
+#[test]
+fn test_parse_without_version() {
+    assert_eq!(parse_version("error: no such command: `nextest`"), None);
+}
+
+#[test]
+fn test_nextest_minimum_versions() {
+    let runner = NextestRunner;
+
+    assert!(Version::new(0, 9, 72) < runner.minimum_version(Capability::NoTests).unwrap());
+    assert!(Version::new(0, 9, 72) >= runner.minimum_version(Capability::TestSelection).unwrap());
+    assert_eq!(runner.minimum_version(Capability::Retries), None);
+    assert_eq!(
+        runner.minimum_version(Capability::MachineReadableOutput),
+        Some(Version::new(0, 9, 66))
+    );
+}
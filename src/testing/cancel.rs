@@ -0,0 +1,76 @@
+use std::{
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// Shared handle to stop a test run from another thread, killing the runner process in
+/// progress along with the test binaries it started
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    /// ID of the runner process in progress
+    process: Arc<Mutex<Option<u32>>>,
+}
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+
+        if let Some(id) = *self.lock() {
+            kill_tree(id);
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Start the runner in its own process group, so that cancelling also stops the test
+    /// binaries it runs
+    pub fn prepare(&self, cmd: &mut Command) {
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(cmd, 0);
+        #[cfg(not(unix))]
+        let _ = cmd;
+    }
+
+    /// Track a runner process until `finish` is called, killing it straight away if the run
+    /// was already cancelled
+    pub fn start(&self, id: u32) {
+        // Checking under the lock means a concurrent `cancel` either sees the process or has
+        // already set the flag
+        let mut process = self.lock();
+        if self.is_cancelled() {
+            kill_tree(id);
+        }
+
+        *process = Some(id);
+    }
+
+    pub fn finish(&self) {
+        *self.lock() = None;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<u32>> {
+        self.process.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Kill a process started with `CancelToken::prepare` and everything it spawned
+fn kill_tree(id: u32) {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("taskkill");
+        cmd.args(["/F", "/T", "/PID", &id.to_string()]);
+        cmd
+    } else {
+        // The process leads its own group, whose ID is the process ID
+        let mut cmd = Command::new("kill");
+        cmd.args(["-KILL", "--", &format!("-{}", id)]);
+        cmd
+    };
+
+    let _ = cmd.stdout(Stdio::null()).stderr(Stdio::null()).status();
+}
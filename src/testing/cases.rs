@@ -0,0 +1,426 @@
+use std::{collections::HashSet, fmt, time::Duration};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The outcome of a single test within a crate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TestCaseStatus {
+    Passed,
+    Failed,
+    Ignored,
+    /// Failed at first, then passed when retried
+    Flaky,
+    /// Failed, but listed in the quarantine file
+    Quarantined,
+}
+
+/// A single test reported by the test runner
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TestCase {
+    pub name: String,
+    pub status: TestCaseStatus,
+    pub duration: Option<Duration>,
+    /// Output captured from the test, which runners only print for failed tests and,
+    /// for flaky tests, their failed attempts
+    pub stdout: Option<String>,
+}
+
+/// The number of tests in a crate with each outcome
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct TestCounts {
+    pub passed: usize,
+    pub failed: usize,
+    pub ignored: usize,
+    pub flaky: usize,
+    pub quarantined: usize,
+}
+
+impl TestCounts {
+    /// Count the tests by outcome, or `None` if no tests were parsed
+    pub fn from_parsed(cases: &[TestCase]) -> Option<Self> {
+        (!cases.is_empty()).then(|| TestCounts::from_cases(cases))
+    }
+
+    pub fn from_cases(cases: &[TestCase]) -> Self {
+        let mut counts = TestCounts::default();
+
+        for case in cases {
+            match case.status {
+                TestCaseStatus::Passed => counts.passed += 1,
+                TestCaseStatus::Failed => counts.failed += 1,
+                TestCaseStatus::Ignored => counts.ignored += 1,
+                TestCaseStatus::Flaky => counts.flaky += 1,
+                TestCaseStatus::Quarantined => counts.quarantined += 1,
+            }
+        }
+
+        counts
+    }
+}
+
+impl fmt::Display for TestCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} passed, ", self.passed)?;
+        if self.flaky > 0 {
+            write!(f, "{} flaky, ", self.flaky)?;
+        }
+        write!(f, "{} failed, ", self.failed)?;
+        if self.quarantined > 0 {
+            write!(f, "{} quarantined, ", self.quarantined)?;
+        }
+        write!(f, "{} ignored", self.ignored)
+    }
+}
+
+/// Mark the tests that failed in an earlier attempt but passed in the final one as flaky,
+/// keeping the output of their failed attempt
+pub fn mark_flaky(earlier_attempts: &[TestCase], mut cases: Vec<TestCase>) -> Vec<TestCase> {
+    for case in cases
+        .iter_mut()
+        .filter(|case| case.status == TestCaseStatus::Passed)
+    {
+        if let Some(failed) = earlier_attempts
+            .iter()
+            .rev()
+            .find(|earlier| earlier.name == case.name && earlier.status == TestCaseStatus::Failed)
+        {
+            case.status = TestCaseStatus::Flaky;
+            case.stdout = failed.stdout.clone();
+        }
+    }
+
+    cases
+}
+
+/// Combine the attempts of each retried test into a single case, which is flaky if any
+/// attempt failed and a later one passed
+///
+/// Only tests named in `retried` are combined, since tests in different binaries can
+/// share a name.
+fn merge_retries(cases: Vec<TestCase>, retried: &HashSet<String>) -> Vec<TestCase> {
+    let mut merged: Vec<TestCase> = Vec::with_capacity(cases.len());
+
+    for case in cases {
+        let existing = retried
+            .contains(&case.name)
+            .then(|| {
+                merged
+                    .iter_mut()
+                    .find(|existing| existing.name == case.name)
+            })
+            .flatten();
+        let Some(existing) = existing else {
+            merged.push(case);
+            continue;
+        };
+
+        match (&existing.status, &case.status) {
+            (TestCaseStatus::Failed, TestCaseStatus::Passed | TestCaseStatus::Flaky)
+            | (TestCaseStatus::Passed, TestCaseStatus::Flaky) => {
+                existing.status = TestCaseStatus::Flaky;
+                existing.duration = case.duration.or(existing.duration);
+            }
+            (TestCaseStatus::Failed, TestCaseStatus::Failed) => {
+                existing.duration = case.duration.or(existing.duration);
+                existing.stdout = existing.stdout.take().or(case.stdout);
+            }
+            _ => {}
+        }
+    }
+
+    merged
+}
+
+/// Parse the test results nextest prints with `--message-format libtest-json`, falling back to
+/// its human-readable status lines when the output has no JSON test events
+///
+/// The JSON events only hold the final result of each test, so tests that the status lines
+/// show as flaky, or that have several JSON results in the same binary because they were
+/// retried, are reported as flaky.
+pub fn parse_nextest_output(output: &str) -> Vec<TestCase> {
+    let status_lines = parse_nextest_status_lines(output);
+
+    let mut cases = Vec::new();
+    let mut seen = HashSet::new();
+    let mut retried = HashSet::new();
+    for (binary, case) in output.lines().filter_map(parse_libtest_json_event) {
+        if !seen.insert((binary, case.name.clone())) {
+            retried.insert(case.name.clone());
+        }
+        cases.push(case);
+    }
+    if cases.is_empty() {
+        return status_lines;
+    }
+
+    let mut cases = merge_retries(cases, &retried);
+    for case in &mut cases {
+        let flaky = status_lines
+            .iter()
+            .find(|line| line.name == case.name && line.status == TestCaseStatus::Flaky);
+        if let (TestCaseStatus::Passed, Some(flaky)) = (&case.status, flaky) {
+            case.status = TestCaseStatus::Flaky;
+            case.stdout = case.stdout.take().or_else(|| flaky.stdout.clone());
+        }
+    }
+
+    cases
+}
+
+/// Remove the lines of nextest's libtest-compatible JSON output, leaving the output nextest
+/// prints for people
+pub fn strip_libtest_json(output: &str) -> String {
+    output
+        .lines()
+        .filter(|line| !is_libtest_json_line(line))
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+fn is_libtest_json_line(line: &str) -> bool {
+    let line = line.trim();
+    line.starts_with('{')
+        && serde_json::from_str::<serde_json::Value>(line)
+            .is_ok_and(|value| value.get("type").is_some_and(|kind| kind.is_string()))
+}
+
+/// Parse the per-test status lines nextest prints, such as
+/// `PASS [   0.004s] my-crate tests::it_works` or `FAIL [   0.010s] (2/9) my-crate::it tests::fails`,
+/// along with the `--- STDOUT: ... ---` sections printed for failed tests
+///
+/// Attempts of tests retried with `--retries`, printed as `TRY 2 PASS [   0.004s] ...`, are
+/// combined so that a test which failed and then passed is reported once as flaky.
+fn parse_nextest_status_lines(output: &str) -> Vec<TestCase> {
+    let mut cases = Vec::new();
+    let mut retried = HashSet::new();
+    let mut section: Option<(String, Vec<&str>)> = None;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+
+        if section.is_some() {
+            if !trimmed.starts_with("---")
+                && !trimmed.starts_with("RETRY ")
+                && parse_nextest_line(line).is_none()
+            {
+                if let Some((_, lines)) = section.as_mut() {
+                    lines.push(line);
+                }
+                continue;
+            }
+
+            if let Some((name, lines)) = section.take() {
+                attach_stdout(&mut cases, &name, &lines);
+            }
+        }
+
+        if let Some(name) = trimmed
+            .strip_prefix("--- ")
+            .map(strip_attempt)
+            .and_then(|header| header.strip_prefix("STDOUT:"))
+            .and_then(|header| header.strip_suffix("---"))
+            .and_then(|header| header.split_whitespace().last())
+        {
+            section = Some((name.to_string(), Vec::new()));
+        } else if let Some(case) = parse_nextest_line(line) {
+            if strip_attempt(trimmed) != trimmed || case.status == TestCaseStatus::Flaky {
+                retried.insert(case.name.clone());
+            }
+            cases.push(case);
+        }
+    }
+
+    if let Some((name, lines)) = section {
+        attach_stdout(&mut cases, &name, &lines);
+    }
+
+    merge_retries(cases, &retried)
+}
+
+/// Strip the `TRY 2 ` prefix nextest prints before the status of a retried attempt
+fn strip_attempt(text: &str) -> &str {
+    text.strip_prefix("TRY ")
+        .and_then(|rest| rest.split_once(' '))
+        .filter(|(attempt, _)| attempt.parse::<usize>().is_ok())
+        .map_or(text, |(_, rest)| rest.trim_start())
+}
+
+/// Parse libtest's human-readable output, such as `test tests::it_works ... ok`,
+/// along with the `---- name stdout ----` sections printed for failed tests
+///
+/// Lines in libtest's unstable JSON format (`-Z unstable-options --format json`) are also understood.
+pub fn parse_libtest_output(output: &str) -> Vec<TestCase> {
+    let mut cases = Vec::new();
+    let mut section: Option<(String, Vec<&str>)> = None;
+
+    for line in output.lines() {
+        if section.is_some() {
+            let ends_section =
+                line.starts_with("---- ") || line == "failures:" || line == "successes:";
+            if !ends_section {
+                if let Some((_, lines)) = section.as_mut() {
+                    lines.push(line);
+                }
+                continue;
+            }
+
+            if let Some((name, lines)) = section.take() {
+                attach_stdout(&mut cases, &name, &lines);
+            }
+        }
+
+        if let Some(name) = line
+            .strip_prefix("---- ")
+            .and_then(|header| header.strip_suffix(" stdout ----"))
+        {
+            section = Some((name.to_string(), Vec::new()));
+        } else if let Some(case) =
+            parse_libtest_line(line).or_else(|| parse_libtest_json_line(line))
+        {
+            cases.push(case);
+        }
+    }
+
+    if let Some((name, lines)) = section {
+        attach_stdout(&mut cases, &name, &lines);
+    }
+
+    cases
+}
+
+/// Attach captured output to the most recent failed test with the given name
+fn attach_stdout(cases: &mut [TestCase], name: &str, lines: &[&str]) {
+    let stdout = lines.join("\n").trim_end().to_string();
+    if stdout.is_empty() {
+        return;
+    }
+
+    if let Some(case) = cases
+        .iter_mut()
+        .rev()
+        .find(|case| case.name == name && case.status == TestCaseStatus::Failed)
+    {
+        case.stdout = Some(stdout);
+    }
+}
+
+fn parse_libtest_line(line: &str) -> Option<TestCase> {
+    let (name, status) = line.strip_prefix("test ")?.rsplit_once(" ... ")?;
+
+    let status = match status.trim() {
+        "ok" => TestCaseStatus::Passed,
+        "FAILED" => TestCaseStatus::Failed,
+        status if status.starts_with("ignored") => TestCaseStatus::Ignored,
+        _ => return None,
+    };
+
+    // Tests expected to panic are listed as `name - should panic`
+    let name = name.strip_suffix(" - should panic").unwrap_or(name);
+
+    Some(TestCase {
+        name: name.to_string(),
+        status,
+        duration: None,
+        stdout: None,
+    })
+}
+
+/// A test event in libtest's JSON format, which nextest can also produce
+#[derive(Deserialize)]
+struct LibtestEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    event: String,
+    name: String,
+    exec_time: Option<f64>,
+    stdout: Option<String>,
+}
+
+fn parse_libtest_json_line(line: &str) -> Option<TestCase> {
+    parse_libtest_json_event(line).map(|(_, case)| case)
+}
+
+/// Parse a test event in libtest's JSON format, along with the binary nextest names before
+/// the test
+fn parse_libtest_json_event(line: &str) -> Option<(Option<String>, TestCase)> {
+    let line = line.trim();
+    if !line.starts_with('{') {
+        return None;
+    }
+
+    let event: LibtestEvent = serde_json::from_str(line).ok()?;
+    if event.kind != "test" {
+        return None;
+    }
+
+    let status = match event.event.as_str() {
+        "ok" => TestCaseStatus::Passed,
+        "failed" | "timeout" => TestCaseStatus::Failed,
+        "ignored" => TestCaseStatus::Ignored,
+        _ => return None,
+    };
+
+    // nextest prefixes test names with the binary they belong to, as `binary$name`
+    let (binary, name) = match event.name.rsplit_once('$') {
+        Some((binary, name)) => (Some(binary.to_string()), name.to_string()),
+        None => (None, event.name),
+    };
+
+    Some((
+        binary,
+        TestCase {
+            name,
+            status,
+            duration: event.exec_time.map(Duration::from_secs_f64),
+            stdout: event.stdout.filter(|stdout| !stdout.is_empty()),
+        },
+    ))
+}
+
+fn parse_nextest_line(line: &str) -> Option<TestCase> {
+    let line = strip_attempt(line.trim_start());
+    let (status, rest) = line.split_once(' ')?;
+
+    let status = match status {
+        "PASS" => TestCaseStatus::Passed,
+        "FAIL" | "SIGSEGV" | "SIGABRT" | "SIGBUS" | "TIMEOUT" | "ABORT" => TestCaseStatus::Failed,
+        "SKIP" => TestCaseStatus::Ignored,
+        "FLAKY" => TestCaseStatus::Flaky,
+        _ => return None,
+    };
+
+    // The final status of a flaky test includes the attempt that passed, as `FLAKY 2/3 [...]`
+    let mut rest = rest.trim_start();
+    if status == TestCaseStatus::Flaky {
+        rest = rest.split_once(' ')?.1.trim_start();
+    }
+
+    let rest = rest.strip_prefix('[')?;
+    let (duration, rest) = rest.split_once(']')?;
+    let duration = duration
+        .trim()
+        .strip_suffix('s')
+        .and_then(|secs| secs.trim().parse::<f64>().ok())
+        .map(Duration::from_secs_f64);
+
+    // Newer versions of nextest print a progress counter before the test identifier
+    let mut rest = rest.trim_start();
+    if rest.starts_with('(') {
+        rest = rest.split_once(')')?.1.trim_start();
+    }
+
+    let (_binary_id, name) = rest.split_once(' ')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(TestCase {
+        name: name.to_string(),
+        status,
+        duration,
+        stdout: None,
+    })
+}
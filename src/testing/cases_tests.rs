@@ -0,0 +1,335 @@
+use std::time::Duration;
+
+use crate::testing::cases::{
+    mark_flaky, parse_libtest_output, parse_nextest_output, strip_libtest_json, TestCase,
+    TestCaseStatus, TestCounts,
+};
+
+#[test]
+fn test_parse_nextest_output() {
+    let output = "\
+    Finished `test` profile [unoptimized + debuginfo] target(s) in 0.05s
+    Starting 4 tests across 2 binaries (1 test skipped)
+        PASS [   0.004s] my-crate tests::it_works
+        FAIL [   0.120s] my-crate::integration api::returns_error
+        SKIP [         ] my-crate tests::slow
+     TIMEOUT [  60.001s] my-crate tests::hangs
+--- STDOUT:              my-crate::integration api::returns_error ---
+        PASS this line is test output
+------------
+     Summary [   0.124s] 4 tests run: 1 passed, 2 failed, 1 skipped
+";
+
+    assert_eq!(
+        parse_nextest_output(output),
+        vec![
+            TestCase {
+                name: "tests::it_works".to_string(),
+                status: TestCaseStatus::Passed,
+                duration: Some(Duration::from_millis(4)),
+                stdout: None,
+            },
+            TestCase {
+                name: "api::returns_error".to_string(),
+                status: TestCaseStatus::Failed,
+                duration: Some(Duration::from_millis(120)),
+                stdout: Some("        PASS this line is test output".to_string()),
+            },
+            TestCase {
+                name: "tests::slow".to_string(),
+                status: TestCaseStatus::Ignored,
+                duration: None,
+                stdout: None,
+            },
+            TestCase {
+                name: "tests::hangs".to_string(),
+                status: TestCaseStatus::Failed,
+                duration: Some(Duration::from_secs_f64(60.001)),
+                stdout: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_nextest_output_with_progress_counter() {
+    let output = "        PASS [   0.010s] (  2/120) my-crate::bin/cli cli::parses_args\n";
+
+    assert_eq!(
+        parse_nextest_output(output),
+        vec![TestCase {
+            name: "cli::parses_args".to_string(),
+            status: TestCaseStatus::Passed,
+            duration: Some(Duration::from_millis(10)),
+            stdout: None,
+        }]
+    );
+}
+
+#[test]
+fn test_parse_nextest_output_with_retries() {
+    let output = "\
+    Starting 3 tests across 1 binary
+        PASS [   0.004s] my-crate tests::it_works
+   TRY 1 FAIL [   0.010s] my-crate tests::flaky
+--- TRY 1 STDOUT:        my-crate tests::flaky ---
+connection reset
+  RETRY 2/3 [         ] my-crate tests::flaky
+   TRY 2 PASS [   0.008s] my-crate tests::flaky
+   TRY 1 FAIL [   0.002s] my-crate tests::broken
+   TRY 2 FAIL [   0.003s] my-crate tests::broken
+   TRY 3 FAIL [   0.001s] my-crate tests::broken
+------------
+     Summary [   0.030s] 3 tests run: 2 passed (1 flaky), 1 failed, 0 skipped
+   FLAKY 2/3 [   0.008s] my-crate tests::flaky
+        FAIL [   0.001s] my-crate tests::broken
+";
+
+    let cases = parse_nextest_output(output);
+
+    assert_eq!(
+        cases,
+        vec![
+            TestCase {
+                name: "tests::it_works".to_string(),
+                status: TestCaseStatus::Passed,
+                duration: Some(Duration::from_millis(4)),
+                stdout: None,
+            },
+            TestCase {
+                name: "tests::flaky".to_string(),
+                status: TestCaseStatus::Flaky,
+                duration: Some(Duration::from_millis(8)),
+                stdout: Some("connection reset".to_string()),
+            },
+            TestCase {
+                name: "tests::broken".to_string(),
+                status: TestCaseStatus::Failed,
+                duration: Some(Duration::from_millis(1)),
+                stdout: None,
+            },
+        ]
+    );
+    assert_eq!(
+        TestCounts::from_cases(&cases).to_string(),
+        "1 passed, 1 flaky, 1 failed, 0 ignored"
+    );
+}
+
+#[test]
+fn test_mark_flaky() {
+    let case = |name: &str, status, stdout: Option<&str>| TestCase {
+        name: name.to_string(),
+        status,
+        duration: None,
+        stdout: stdout.map(str::to_string),
+    };
+
+    let first_attempt = [
+        case("tests::a", TestCaseStatus::Passed, None),
+        case("tests::b", TestCaseStatus::Failed, Some("timed out")),
+    ];
+    let final_attempt = vec![
+        case("tests::a", TestCaseStatus::Passed, None),
+        case("tests::b", TestCaseStatus::Passed, None),
+    ];
+
+    assert_eq!(
+        mark_flaky(&first_attempt, final_attempt),
+        vec![
+            case("tests::a", TestCaseStatus::Passed, None),
+            case("tests::b", TestCaseStatus::Flaky, Some("timed out")),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_nextest_libtest_json_output() {
+    let output = r#"{"type":"suite","event":"started","test_count":2,"nextest":{"crate":"my-crate","test_binary":"my_crate","kind":"lib"}}
+{"type":"test","event":"started","name":"my-crate$tests::it_works"}
+{"type":"test","event":"ok","name":"my-crate$tests::it_works","exec_time":0.004}
+{"type":"test","event":"failed","name":"my-crate$tests::fails","exec_time":0.010,"stdout":"assertion failed"}
+"#;
+
+    assert_eq!(
+        parse_nextest_output(output),
+        vec![
+            TestCase {
+                name: "tests::it_works".to_string(),
+                status: TestCaseStatus::Passed,
+                duration: Some(Duration::from_millis(4)),
+                stdout: None,
+            },
+            TestCase {
+                name: "tests::fails".to_string(),
+                status: TestCaseStatus::Failed,
+                duration: Some(Duration::from_millis(10)),
+                stdout: Some("assertion failed".to_string()),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_nextest_libtest_json_with_status_lines() {
+    let output = r#"{"type":"test","event":"ok","name":"my-crate$tests::it_works","exec_time":0.004}
+{"type":"test","event":"failed","name":"my-crate$tests::flaky","exec_time":0.010,"stdout":"connection reset"}
+{"type":"test","event":"ok","name":"my-crate$tests::flaky","exec_time":0.008}
+{"type":"test","event":"ok","name":"my-crate::it$tests::it_works","exec_time":0.002}
+        PASS [   0.004s] my-crate tests::it_works
+   TRY 1 FAIL [   0.010s] my-crate tests::flaky
+   TRY 2 PASS [   0.008s] my-crate tests::flaky
+        PASS [   0.002s] my-crate::it tests::it_works
+"#;
+
+    assert_eq!(
+        parse_nextest_output(output),
+        vec![
+            TestCase {
+                name: "tests::it_works".to_string(),
+                status: TestCaseStatus::Passed,
+                duration: Some(Duration::from_millis(4)),
+                stdout: None,
+            },
+            TestCase {
+                name: "tests::flaky".to_string(),
+                status: TestCaseStatus::Flaky,
+                duration: Some(Duration::from_millis(8)),
+                stdout: Some("connection reset".to_string()),
+            },
+            TestCase {
+                name: "tests::it_works".to_string(),
+                status: TestCaseStatus::Passed,
+                duration: Some(Duration::from_millis(2)),
+                stdout: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_strip_libtest_json() {
+    let output = r#"{"type":"suite","event":"started","test_count":1}
+    Starting 1 test across 1 binary
+{"type":"test","event":"ok","name":"my-crate$tests::it_works"}
+{"not":"an event"}
+"#;
+
+    assert_eq!(
+        strip_libtest_json(output),
+        "    Starting 1 test across 1 binary\n{\"not\":\"an event\"}\n"
+    );
+}
+
+#[test]
+fn test_parse_libtest_output() {
+    let output = "\
+    Finished `test` profile [unoptimized + debuginfo] target(s) in 0.05s
+     Running unittests src/lib.rs (target/debug/deps/my_crate-0123456789abcdef)
+
+running 4 tests
+test tests::it_works ... ok
+test tests::slow ... ignored, takes too long
+test tests::panics - should panic ... ok
+test tests::fails ... FAILED
+
+failures:
+
+---- tests::fails stdout ----
+thread 'tests::fails' panicked at src/lib.rs:10:9:
+assertion `left == right` failed
+
+failures:
+    tests::fails
+
+test result: FAILED. 2 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+   Doc-tests my_crate
+
+running 1 test
+test src/lib.rs - add (line 3) ... ok
+";
+
+    let cases = parse_libtest_output(output);
+
+    assert_eq!(
+        cases,
+        vec![
+            TestCase {
+                name: "tests::it_works".to_string(),
+                status: TestCaseStatus::Passed,
+                duration: None,
+                stdout: None,
+            },
+            TestCase {
+                name: "tests::slow".to_string(),
+                status: TestCaseStatus::Ignored,
+                duration: None,
+                stdout: None,
+            },
+            TestCase {
+                name: "tests::panics".to_string(),
+                status: TestCaseStatus::Passed,
+                duration: None,
+                stdout: None,
+            },
+            TestCase {
+                name: "tests::fails".to_string(),
+                status: TestCaseStatus::Failed,
+                duration: None,
+                stdout: Some(
+                    "thread 'tests::fails' panicked at src/lib.rs:10:9:\nassertion `left == right` failed"
+                        .to_string()
+                ),
+            },
+            TestCase {
+                name: "src/lib.rs - add (line 3)".to_string(),
+                status: TestCaseStatus::Passed,
+                duration: None,
+                stdout: None,
+            },
+        ]
+    );
+    assert_eq!(
+        TestCounts::from_cases(&cases),
+        TestCounts {
+            passed: 3,
+            failed: 1,
+            ignored: 1,
+            flaky: 0,
+            quarantined: 0,
+        }
+    );
+    assert_eq!(
+        TestCounts::from_cases(&cases).to_string(),
+        "3 passed, 1 failed, 1 ignored"
+    );
+}
+
+#[test]
+fn test_parse_libtest_json_output() {
+    let output = r#"{ "type": "suite", "event": "started", "test_count": 2 }
+{ "type": "test", "event": "started", "name": "tests::it_works" }
+{ "type": "test", "name": "tests::it_works", "event": "ok", "exec_time": 0.001 }
+{ "type": "test", "name": "tests::slow", "event": "ignored" }
+{ "type": "suite", "event": "ok", "passed": 1, "failed": 0, "ignored": 1, "measured": 0, "filtered_out": 0, "exec_time": 0.002 }
+"#;
+
+    assert_eq!(
+        parse_libtest_output(output),
+        vec![
+            TestCase {
+                name: "tests::it_works".to_string(),
+                status: TestCaseStatus::Passed,
+                duration: Some(Duration::from_millis(1)),
+                stdout: None,
+            },
+            TestCase {
+                name: "tests::slow".to_string(),
+                status: TestCaseStatus::Ignored,
+                duration: None,
+                stdout: None,
+            },
+        ]
+    );
+}
@@ -0,0 +1,141 @@
+use std::path::PathBuf;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::reporting::pluralize;
+
+/// A compiler or lint diagnostic reported while building a crate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct Diagnostic {
+    /// Severity such as `error` or `warning`
+    pub level: String,
+    /// Lint or error code, such as `clippy::needless_return`
+    pub code: Option<String>,
+    pub message: String,
+    /// File of the primary span, relative to the workspace root
+    pub file: Option<PathBuf>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// The diagnostic as the compiler prints it
+    #[serde(skip)]
+    pub rendered: String,
+}
+
+impl Diagnostic {
+    pub fn is_error(&self) -> bool {
+        self.level == "error"
+    }
+
+    /// Where the diagnostic points, such as `src/lib.rs:12:5`
+    pub fn location(&self) -> Option<String> {
+        let file = self.file.as_ref()?.display();
+        Some(match (self.line, self.column) {
+            (Some(line), Some(column)) => format!("{}:{}:{}", file, line, column),
+            (Some(line), None) => format!("{}:{}", file, line),
+            _ => file.to_string(),
+        })
+    }
+}
+
+/// Count diagnostics by severity, such as `1 error, 2 warnings`
+pub fn summarize(diagnostics: &[Diagnostic]) -> String {
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    let warnings = diagnostics.iter().filter(|d| d.level == "warning").count();
+
+    let mut parts = Vec::new();
+    if errors > 0 {
+        parts.push(format!(
+            "{} {}",
+            errors,
+            pluralize(errors, "error", "errors")
+        ));
+    }
+    if warnings > 0 {
+        parts.push(format!(
+            "{} {}",
+            warnings,
+            pluralize(warnings, "warning", "warnings")
+        ));
+    }
+    parts.join(", ")
+}
+
+/// A line of `cargo --message-format=json` output
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Option<CompilerMessage>,
+}
+
+#[derive(Deserialize)]
+struct CompilerMessage {
+    level: String,
+    message: String,
+    code: Option<CompilerCode>,
+    rendered: Option<String>,
+    #[serde(default)]
+    spans: Vec<CompilerSpan>,
+}
+
+#[derive(Deserialize)]
+struct CompilerCode {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct CompilerSpan {
+    file_name: PathBuf,
+    line_start: usize,
+    column_start: usize,
+    is_primary: bool,
+}
+
+/// Parse the diagnostics from the output of a cargo command run with
+/// `--message-format=json`, along with the output as a person would read it
+///
+/// JSON lines are replaced by the rendered diagnostics they carry, and other lines, such as
+/// cargo's progress on stderr, are kept. Diagnostics reported for several targets of the
+/// same crate are only listed once.
+pub fn parse_cargo_messages(output: &str) -> (Vec<Diagnostic>, String) {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    let mut readable = String::new();
+
+    for line in output.lines() {
+        if !line.starts_with('{') {
+            readable.push_str(line);
+            readable.push('\n');
+            continue;
+        }
+
+        let Ok(message) = serde_json::from_str::<CargoMessage>(line) else {
+            readable.push_str(line);
+            readable.push('\n');
+            continue;
+        };
+        let Some(message) = message
+            .message
+            .filter(|_| message.reason == "compiler-message")
+        else {
+            continue;
+        };
+
+        let primary = message.spans.iter().find(|span| span.is_primary);
+        let diagnostic = Diagnostic {
+            level: message.level,
+            code: message.code.map(|code| code.code),
+            message: message.message,
+            file: primary.map(|span| span.file_name.clone()),
+            line: primary.map(|span| span.line_start),
+            column: primary.map(|span| span.column_start),
+            rendered: message.rendered.unwrap_or_default(),
+        };
+
+        if !diagnostics.contains(&diagnostic) {
+            readable.push_str(&diagnostic.rendered);
+            diagnostics.push(diagnostic);
+        }
+    }
+
+    (diagnostics, readable)
+}
@@ -0,0 +1,83 @@
+use std::path::PathBuf;
+
+use crate::testing::diagnostics::{parse_cargo_messages, summarize, Diagnostic};
+
+const NEEDLESS_RETURN: &str = r#"{"reason":"compiler-message","package_id":"path+file:///workspace/api#0.1.0","manifest_path":"/workspace/api/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"api","src_path":"/workspace/api/src/lib.rs","edition":"2021","doc":true,"doctest":true,"test":true},"message":{"rendered":"warning: unneeded `return` statement\n --> api/src/lib.rs:2:5\n","$message_type":"diagnostic","children":[],"code":{"code":"clippy::needless_return","explanation":null},"level":"warning","message":"unneeded `return` statement","spans":[{"byte_end":30,"byte_start":21,"column_end":14,"column_start":5,"expansion":null,"file_name":"api/src/lib.rs","is_primary":true,"label":null,"line_end":2,"line_start":2,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}]}}"#;
+
+const MISMATCHED_TYPES: &str = r#"{"reason":"compiler-message","package_id":"path+file:///workspace/api#0.1.0","manifest_path":"/workspace/api/Cargo.toml","target":{"kind":["test"],"crate_types":["bin"],"name":"it","src_path":"/workspace/api/tests/it.rs","edition":"2021","doc":false,"doctest":false,"test":true},"message":{"rendered":"error[E0308]: mismatched types\n --> api/tests/it.rs:4:18\n","$message_type":"diagnostic","children":[],"code":{"code":"E0308","explanation":"..."},"level":"error","message":"mismatched types","spans":[{"byte_end":60,"byte_start":58,"column_end":20,"column_start":18,"expansion":null,"file_name":"api/tests/it.rs","is_primary":true,"label":"expected `u32`","line_end":4,"line_start":4,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}]}}"#;
+
+const ARTIFACT: &str = r#"{"reason":"compiler-artifact","package_id":"path+file:///workspace/api#0.1.0","fresh":false}"#;
+
+const BUILD_FINISHED: &str = r#"{"reason":"build-finished","success":false}"#;
+
+#[test]
+fn test_parse_diagnostics() {
+    let output = [
+        "    Checking api v0.1.0 (/workspace/api)",
+        NEEDLESS_RETURN,
+        ARTIFACT,
+        MISMATCHED_TYPES,
+        "error: could not compile `api` (test \"it\") due to 1 previous error",
+        BUILD_FINISHED,
+    ]
+    .join("\n");
+
+    let (diagnostics, readable) = parse_cargo_messages(&output);
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic {
+                level: "warning".to_string(),
+                code: Some("clippy::needless_return".to_string()),
+                message: "unneeded `return` statement".to_string(),
+                file: Some(PathBuf::from("api/src/lib.rs")),
+                line: Some(2),
+                column: Some(5),
+                rendered: "warning: unneeded `return` statement\n --> api/src/lib.rs:2:5\n"
+                    .to_string(),
+            },
+            Diagnostic {
+                level: "error".to_string(),
+                code: Some("E0308".to_string()),
+                message: "mismatched types".to_string(),
+                file: Some(PathBuf::from("api/tests/it.rs")),
+                line: Some(4),
+                column: Some(18),
+                rendered: "error[E0308]: mismatched types\n --> api/tests/it.rs:4:18\n".to_string(),
+            },
+        ]
+    );
+    assert_eq!(
+        readable,
+        "    Checking api v0.1.0 (/workspace/api)
+warning: unneeded `return` statement
+ --> api/src/lib.rs:2:5
+error[E0308]: mismatched types
+ --> api/tests/it.rs:4:18
+error: could not compile `api` (test \"it\") due to 1 previous error
+"
+    );
+}
+
+#[test]
+fn test_parse_deduplicates_diagnostics_across_targets() {
+    let output = [NEEDLESS_RETURN, NEEDLESS_RETURN].join("\n");
+
+    let (diagnostics, readable) = parse_cargo_messages(&output);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(readable.matches("unneeded `return`").count(), 1);
+}
+
+#[test]
+fn test_summarize() {
+    let (diagnostics, _) = parse_cargo_messages(&[NEEDLESS_RETURN, MISMATCHED_TYPES].join("\n"));
+
+    assert_eq!(summarize(&diagnostics), "1 error, 1 warning");
+    assert_eq!(summarize(&[]), "");
+    assert_eq!(
+        diagnostics[0].location().as_deref(),
+        Some("api/src/lib.rs:2:5")
+    );
+}
@@ -1,30 +1,38 @@
 use anyhow::Result;
+use indexmap::IndexMap;
 use std::io::{Read, Write};
-use std::process::Stdio;
+use std::process::{Command, Stdio};
 use std::time::Instant;
 
+use super::cancel::CancelToken;
+use super::cases::{self, TestCaseStatus, TestCounts};
+use super::filter::{TestFilter, TestTarget};
 use super::plan::TestPlan;
-use super::result::{TestResult, TestResults};
+use super::result::{SubResult, SubResultKind, TestOutcome, TestResult, TestResults};
 use crate::error::AppError;
 use crate::reporting::Reporter;
-use crate::test_runner::TestRunner;
+use crate::test_runner::{Capability, Runners, TestRunner};
 
 pub struct TestExecutor<'a> {
     test_plan: &'a TestPlan,
-    runner: &'a dyn TestRunner,
+    runners: &'a Runners,
     reporter: &'a mut dyn Reporter,
+    /// Set when the run can be cancelled from another thread
+    cancel: Option<&'a CancelToken>,
 }
 
 impl<'a> TestExecutor<'a> {
     pub fn new(
         plan: &'a TestPlan,
-        runner: &'a dyn TestRunner,
+        runners: &'a Runners,
         reporter: &'a mut dyn Reporter,
+        cancel: Option<&'a CancelToken>,
     ) -> Self {
         TestExecutor {
             test_plan: plan,
-            runner,
+            runners,
             reporter,
+            cancel,
         }
     }
 
@@ -32,18 +40,17 @@ impl<'a> TestExecutor<'a> {
         let mut results = TestResults::new();
         let start_time = Instant::now();
 
-        if !self.runner.is_installed() {
-            return Err(AppError::TestRunnerNotInstalled {
-                runner_name: self.runner.name().to_string(),
-                installation_tip: self.runner.installation_instructions(),
-            });
-        }
-
         let crates_to_test = &self.test_plan.get_crates_to_test();
+        self.check_runners(crates_to_test)?;
+
         for (index, test_crate) in crates_to_test.iter().enumerate() {
+            if self.cancel.is_some_and(CancelToken::is_cancelled) {
+                return Err(AppError::RunCancelled);
+            }
+
             let result = self.execute_single_test(test_crate, index + 1, crates_to_test.len())?;
 
-            let should_stop = !result.success && self.test_plan.fail_fast;
+            let should_stop = !result.success() && self.test_plan.fail_fast;
             results.add_result(result);
 
             if should_stop {
@@ -55,6 +62,80 @@ impl<'a> TestExecutor<'a> {
         Ok(results)
     }
 
+    /// Check that the runner of every crate is installed and recent enough for the features
+    /// the plan uses, before any crate is tested
+    fn check_runners(&self, crates: &[&String]) -> Result<(), AppError> {
+        let mut required: IndexMap<&str, (&dyn TestRunner, Vec<Capability>)> = IndexMap::new();
+        for crate_name in crates {
+            let runner = self.runners.runner(crate_name);
+            let (_, capabilities) = required
+                .entry(runner.name())
+                .or_insert((runner, Vec::new()));
+            for capability in self.required_capabilities(crate_name) {
+                if !capabilities.contains(&capability) {
+                    capabilities.push(capability);
+                }
+            }
+        }
+
+        for (runner, capabilities) in required.values() {
+            if !runner.is_installed() {
+                return Err(AppError::TestRunnerNotInstalled {
+                    runner_name: runner.name().to_string(),
+                    installation_tip: runner.installation_instructions(),
+                });
+            }
+
+            let minimum_versions: Vec<_> = capabilities
+                .iter()
+                .filter_map(|capability| Some((capability, runner.minimum_version(*capability)?)))
+                .collect();
+            if minimum_versions.is_empty() {
+                continue;
+            }
+
+            // Runners whose version cannot be read are assumed to be recent enough
+            let Some(version) = runner.version() else {
+                continue;
+            };
+            for (capability, minimum_version) in minimum_versions {
+                if version < minimum_version {
+                    return Err(AppError::TestRunnerTooOld {
+                        runner_name: runner.name().to_string(),
+                        version: version.to_string(),
+                        required_version: minimum_version.to_string(),
+                        feature: capability.description().to_string(),
+                        upgrade_tip: runner.upgrade_instructions(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Capabilities the plan needs from the runner of a crate
+    fn required_capabilities(&self, crate_name: &str) -> Vec<Capability> {
+        let mut capabilities = vec![Capability::NoTests, Capability::MachineReadableOutput];
+
+        let filtered = matches!(
+            self.test_plan.test_filters.get(crate_name),
+            Some(TestFilter::Targets { .. })
+        );
+        let quarantine = &self.test_plan.quarantine;
+        if filtered
+            || (!quarantine.tests(crate_name).is_empty() && !quarantine.contains_crate(crate_name))
+        {
+            capabilities.push(Capability::TestSelection);
+        }
+
+        if self.test_plan.retries > 0 {
+            capabilities.push(Capability::Retries);
+        }
+
+        capabilities
+    }
+
     fn execute_single_test(
         &mut self,
         crate_name: &str,
@@ -67,11 +148,242 @@ impl<'a> TestExecutor<'a> {
         let _ = std::io::stdout().flush();
 
         let crate_start = Instant::now();
-        let mut cmd = self.runner.command(crate_name);
-        cmd.args(&self.test_plan.test_runner_args);
+        let runner = self.runners.runner(crate_name);
+        let retries = self.test_plan.retries;
+        let native_retry_args = match retries {
+            0 => None,
+            retries => runner.retry_args(retries),
+        };
+
+        let quarantine = &self.test_plan.quarantine;
+        let filter_args = self.filter_args(crate_name);
+
+        // Individually quarantined tests are excluded from the run and tested in a separate
+        // pass, when the runner can select tests by name in a single invocation
+        let quarantined_tests = quarantine.tests(crate_name);
+        let passes = match filter_args.as_slice() {
+            [filter_args]
+                if !quarantined_tests.is_empty() && !quarantine.contains_crate(crate_name) =>
+            {
+                runner.quarantine_args(filter_args, &quarantined_tests)
+            }
+            _ => None,
+        };
+        let blocking_args = match &passes {
+            Some((blocking, _)) => std::slice::from_ref(blocking),
+            None => filter_args.as_slice(),
+        };
+
+        let (mut success, mut output) = self.run_attempt(
+            crate_name,
+            blocking_args,
+            native_retry_args.as_deref().unwrap_or_default(),
+        )?;
+        let mut test_cases = runner.parse_test_cases(&output);
+        let mut attempts = 1;
+
+        // Runners without native retries have the whole crate run again, unless only
+        // quarantined tests failed
+        if native_retry_args.is_none()
+            && !success
+            && retries > 0
+            && !quarantine.covers_failures(crate_name, &test_cases)
+        {
+            let mut earlier_cases = Vec::new();
+            let mut failed_output = String::new();
+
+            while !success && attempts <= retries {
+                attempts += 1;
+                if self.test_plan.verbose {
+                    self.reporter.note(&format!(
+                        "retrying {} (attempt {} of {})",
+                        crate_name,
+                        attempts,
+                        retries + 1
+                    ));
+                }
+
+                earlier_cases.append(&mut test_cases);
+                failed_output = output;
+                (success, output) = self.run_attempt(crate_name, blocking_args, &[])?;
+                test_cases = runner.parse_test_cases(&output);
+            }
+
+            if success {
+                test_cases = cases::mark_flaky(&earlier_cases, test_cases);
+                output = failed_output;
+            }
+        }
+
+        let outcome = if !success {
+            TestOutcome::Failed
+        } else if attempts > 1
+            || test_cases
+                .iter()
+                .any(|case| case.status == TestCaseStatus::Flaky)
+        {
+            TestOutcome::Flaky
+        } else {
+            TestOutcome::Passed
+        };
+
+        let mut quarantine_failed = false;
+        if let Some((_, non_blocking_args)) = &passes {
+            let (quarantine_success, quarantine_output) =
+                self.run_attempt(crate_name, std::slice::from_ref(non_blocking_args), &[])?;
+            test_cases.extend(runner.parse_test_cases(&quarantine_output));
+
+            if !quarantine_success {
+                quarantine_failed = true;
+                output.push_str(&quarantine_output);
+            }
+        }
+
+        // Doc-tests run once after the runner's own command, and their result is kept
+        // alongside it
+        let mut sub_results = Vec::new();
+        let mut doc_tests_failed = false;
+        if let Some(cmd) = self.doc_test_command(crate_name) {
+            let tests = SubResult {
+                kind: SubResultKind::Tests,
+                success: success && !quarantine_failed,
+                duration_ms: crate_start.elapsed().as_millis() as u64,
+                tests: TestCounts::from_parsed(&test_cases),
+            };
 
+            let doc_start = Instant::now();
+            let (doc_success, doc_output) = self.run_command(cmd)?;
+            let doc_cases = cases::parse_libtest_output(&doc_output);
+            sub_results = vec![
+                tests,
+                SubResult {
+                    kind: SubResultKind::DocTests,
+                    success: doc_success,
+                    duration_ms: doc_start.elapsed().as_millis() as u64,
+                    tests: TestCounts::from_parsed(&doc_cases),
+                },
+            ];
+
+            if !doc_success {
+                doc_tests_failed = true;
+                output.push_str(&doc_output);
+            }
+            test_cases.extend(doc_cases);
+        }
+
+        let (diagnostics, output) = match runner.parse_diagnostics(&output) {
+            Some((diagnostics, readable)) => (diagnostics, readable),
+            None => (Vec::new(), output),
+        };
+
+        let mut result = TestResult {
+            crate_name: crate_name.to_string(),
+            outcome,
+            output,
+            duration: crate_start.elapsed(),
+            test_cases,
+            attempts,
+            sub_results,
+            diagnostics,
+        };
+
+        if quarantine_failed || doc_tests_failed {
+            result.outcome = TestOutcome::Failed;
+        }
+        quarantine.apply(&mut result);
+
+        self.reporter.test_result(&result);
+
+        Ok(result)
+    }
+
+    /// Arguments restricting the crate's run to the tests selected by the plan, one set for
+    /// each invocation of the runner
+    fn filter_args(&self, crate_name: &str) -> Vec<Vec<String>> {
+        let runner = self.runners.runner(crate_name);
+        match self.test_plan.test_filters.get(crate_name) {
+            Some(TestFilter::Targets { targets }) => runner
+                .split_targets(targets)
+                .iter()
+                .map(|group| runner.filter_args(crate_name, group))
+                .collect(),
+            _ => vec![Vec::new()],
+        }
+    }
+
+    /// Command running the crate's doc-tests separately, when they are selected for the crate
+    /// and the runner's own command does not run them
+    ///
+    /// Runners that run doc-tests themselves skip them once the run is restricted to targets,
+    /// so they are run separately whenever the library's doc-tests are selected.
+    fn doc_test_command(&self, crate_name: &str) -> Option<Command> {
+        let runner = self.runners.runner(crate_name);
+        match self.test_plan.test_filters.get(crate_name) {
+            Some(TestFilter::Targets { targets }) => {
+                if !targets
+                    .iter()
+                    .any(|filter| filter.target == TestTarget::Doc)
+                {
+                    return None;
+                }
+                if !runner.runs_doc_tests() && !self.test_plan.doc_test_crates.contains(crate_name)
+                {
+                    return None;
+                }
+            }
+            _ => {
+                if runner.runs_doc_tests() || !self.test_plan.doc_test_crates.contains(crate_name) {
+                    return None;
+                }
+            }
+        }
+
+        let args = merge_args(
+            self.runners.args(crate_name),
+            &self.test_plan.test_runner_args,
+        );
+        let mut cmd = runner.doc_test_command(crate_name, &args)?;
+        cmd.envs(self.runners.env(crate_name));
+        Some(cmd)
+    }
+
+    /// Run the crate's tests once, with one invocation of the runner for each set of filter
+    /// arguments, returning whether they all passed and the captured output
+    fn run_attempt(
+        &mut self,
+        crate_name: &str,
+        filter_args: &[Vec<String>],
+        extra_args: &[String],
+    ) -> Result<(bool, String), AppError> {
+        let args = merge_args(
+            self.runners.args(crate_name),
+            &self.test_plan.test_runner_args,
+        );
+
+        let mut success = true;
+        let mut output = String::new();
+        for filter_args in filter_args {
+            let mut cmd = self.runners.runner(crate_name).command(crate_name);
+            let args = merge_args(filter_args, &args);
+            cmd.args(merge_args(extra_args, &args));
+            cmd.envs(self.runners.env(crate_name));
+
+            let (pass_success, pass_output) = self.run_command(cmd)?;
+            success &= pass_success;
+            output.push_str(&pass_output);
+        }
+
+        Ok((success, output))
+    }
+
+    /// Run a command from the workspace root, returning whether it succeeded and the captured
+    /// output
+    fn run_command(&mut self, mut cmd: Command) -> Result<(bool, String), AppError> {
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped());
+        if let Some(cancel) = self.cancel {
+            cancel.prepare(&mut cmd);
+        }
 
         let mut child = cmd
             .current_dir(&self.test_plan.workspace_root)
@@ -85,6 +397,9 @@ impl<'a> TestExecutor<'a> {
 
         let stdout = child.stdout.take();
         let stderr = child.stderr.take();
+        if let Some(cancel) = self.cancel {
+            cancel.start(child.id());
+        }
 
         if let (Some(stdout), Some(stderr)) = (stdout, stderr) {
             let mut merged_output = std::io::BufReader::new(stdout)
@@ -141,16 +456,39 @@ impl<'a> TestExecutor<'a> {
             reason: e.to_string(),
         })?;
 
-        let success = status.success();
-        let duration = crate_start.elapsed();
+        if let Some(cancel) = self.cancel {
+            cancel.finish();
+            if cancel.is_cancelled() {
+                return Err(AppError::RunCancelled);
+            }
+        }
 
-        self.reporter
-            .test_result(crate_name, success, duration.as_millis() as u64);
+        Ok((
+            status.success(),
+            String::from_utf8_lossy(&output_capture).into_owned(),
+        ))
+    }
+}
 
-        Ok(TestResult {
-            crate_name: crate_name.to_string(),
-            success,
-            output: String::from_utf8_lossy(&output_capture).into_owned(),
-        })
+/// Merge two argument lists, keeping a single `--` separator between the
+/// runner options and the arguments passed through to the test binary
+fn merge_args(first: &[String], second: &[String]) -> Vec<String> {
+    let split = |args: &'_ [String]| -> (Vec<String>, Vec<String>) {
+        match args.iter().position(|arg| arg == "--") {
+            Some(index) => (args[..index].to_vec(), args[index + 1..].to_vec()),
+            None => (args.to_vec(), Vec::new()),
+        }
+    };
+
+    let (mut options, mut passthrough) = split(first);
+    let (second_options, second_passthrough) = split(second);
+    options.extend(second_options);
+    passthrough.extend(second_passthrough);
+
+    if !passthrough.is_empty() {
+        options.push("--".to_string());
+        options.extend(passthrough);
     }
+
+    options
 }
@@ -0,0 +1,89 @@
+use std::{fmt, path::PathBuf};
+
+use indexmap::IndexSet;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// A compilation unit of a crate that contains tests
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, JsonSchema)]
+#[serde(tag = "kind", content = "name", rename_all = "snake_case")]
+pub enum TestTarget {
+    Lib,
+    Bin(String),
+    Test(String),
+    /// The library's doc-tests, which cannot be narrowed to modules
+    Doc,
+}
+
+/// Restricts a run to a single target, and optionally to modules within it
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, JsonSchema)]
+pub struct TargetFilter {
+    pub target: TestTarget,
+    /// Module paths to run tests for, or `None` to run every test in the target
+    pub modules: Option<IndexSet<String>>,
+}
+
+/// The set of tests to run for a single crate
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, JsonSchema)]
+#[serde(tag = "precision", rename_all = "snake_case")]
+pub enum TestFilter {
+    /// Run every test in the crate
+    Crate { reason: FallbackReason },
+    /// Only run tests in the given targets
+    Targets { targets: Vec<TargetFilter> },
+}
+
+/// Why module-level precision could not be used for a crate
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, JsonSchema)]
+#[serde(tag = "reason", content = "path", rename_all = "snake_case")]
+pub enum FallbackReason {
+    NoDepInfo,
+    CrateRootChanged(PathBuf),
+    ManifestChanged(PathBuf),
+    BuildScriptChanged(PathBuf),
+    NonSourceFile(PathBuf),
+    UnknownSourceFile(PathBuf),
+    UntestedTargetChanged(PathBuf),
+}
+
+impl fmt::Display for FallbackReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FallbackReason::NoDepInfo => {
+                write!(f, "no dep-info files found, build the workspace first")
+            }
+            FallbackReason::CrateRootChanged(path) => {
+                write!(f, "crate root '{}' changed", path.display())
+            }
+            FallbackReason::ManifestChanged(path) => {
+                write!(f, "manifest '{}' changed", path.display())
+            }
+            FallbackReason::BuildScriptChanged(path) => {
+                write!(f, "build script '{}' changed", path.display())
+            }
+            FallbackReason::NonSourceFile(path) => {
+                write!(f, "non-source file '{}' changed", path.display())
+            }
+            FallbackReason::UnknownSourceFile(path) => {
+                write!(f, "'{}' is not in any dep-info file", path.display())
+            }
+            FallbackReason::UntestedTargetChanged(path) => {
+                write!(
+                    f,
+                    "'{}' belongs to an example or bench target",
+                    path.display()
+                )
+            }
+        }
+    }
+}
+
+impl TargetFilter {
+    /// Merge another filter for the same target into this one
+    pub fn merge(&mut self, other: TargetFilter) {
+        match (&mut self.modules, other.modules) {
+            (Some(modules), Some(other)) => modules.extend(other),
+            (modules, _) => *modules = None,
+        }
+    }
+}
@@ -1,51 +1,80 @@
+pub mod cancel;
+pub mod cases;
+pub mod diagnostics;
 pub mod executor;
+pub mod filter;
 pub mod plan;
 pub mod result;
+pub mod schedule;
+pub mod shard;
+
+#[cfg(test)]
+mod cases_tests;
+#[cfg(test)]
+mod diagnostics_tests;
+#[cfg(test)]
+mod schedule_tests;
+#[cfg(test)]
+mod shard_tests;
 
 use anyhow::Result;
 
 use crate::error::AppError;
+use crate::quarantine;
 use crate::reporting::Reporter;
-use crate::test_runner::TestRunner;
+use crate::test_runner::Runners;
+use cancel::CancelToken;
 use executor::TestExecutor;
 use plan::TestPlan;
+use result::{TestOutcome, TestResults};
 
+/// Run the plan and report its results, returning them unless nothing was run
+///
+/// Failed crates are not an error here, so callers can record the results first, see
+/// `TestResults::ensure_success`.
 pub fn run_tests(
-    test_plan: TestPlan,
-    runner: &dyn TestRunner,
+    test_plan: &TestPlan,
+    runners: &Runners,
     dry_run: bool,
     reporter: &mut dyn Reporter,
-) -> Result<(), AppError> {
+    cancel: Option<&CancelToken>,
+) -> Result<Option<TestResults>, AppError> {
     if test_plan.is_empty() {
         reporter.no_tests();
-        return Ok(());
+        return Ok(None);
     }
 
-    reporter.plan_summary(&test_plan);
+    reporter.plan_summary(test_plan);
 
     if dry_run {
         reporter.dry_run();
-        return Ok(());
+        return Ok(None);
     }
 
-    let mut executor = TestExecutor::new(&test_plan, runner, reporter);
+    let mut executor = TestExecutor::new(test_plan, runners, reporter, cancel);
     let results = executor.execute()?;
 
     if !test_plan.verbose && results.has_failures() {
-        reporter.test_failures(&results.failed);
+        let failed: Vec<_> = results.with_outcome(TestOutcome::Failed).cloned().collect();
+        reporter.test_failures(&failed);
     }
 
     reporter.test_summary(
-        results.passed.len(),
-        results.failed.len(),
+        results.count(TestOutcome::Passed),
+        results.count(TestOutcome::Failed),
+        results.count(TestOutcome::Flaky),
+        results.count(TestOutcome::Quarantined),
         results.duration.as_secs_f64(),
     );
 
-    if results.has_failures() {
-        return Err(AppError::TestsFailed {
-            failed_crates: results.failed.into_iter().map(|c| c.crate_name).collect(),
-        });
+    let all_results: Vec<_> = results.results.iter().collect();
+    for entry in test_plan.quarantine.removable(&all_results) {
+        reporter.tip(&format!(
+            "`{}` passed in the last {} runs, consider removing it from the quarantine file",
+            entry,
+            quarantine::REMOVAL_STREAK
+        ));
     }
 
-    Ok(())
+    Ok(Some(results))
 }
@@ -1,29 +1,93 @@
-use indexmap::IndexSet;
-use serde::Serialize;
+use clap::ValueEnum;
+use indexmap::{IndexMap, IndexSet};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::filter::TestFilter;
+use super::schedule::{CrateStats, Schedule};
+use super::shard::ShardAssignment;
+use crate::quarantine::Quarantine;
+
+/// What is done with each selected crate
+#[derive(
+    ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum RunMode {
+    /// Run the crate's tests
+    #[default]
+    Test,
+    /// Type-check every target with `cargo check --all-targets`
+    Check,
+    /// Compile the test binaries with `cargo test --no-run` without running them
+    Build,
+    /// Lint every target with `cargo clippy --all-targets`
+    Clippy,
+    /// Check formatting with `cargo fmt --check`
+    Fmt,
+}
+
+impl RunMode {
+    /// The verb describing what is done with a crate
+    pub fn verb(&self) -> &'static str {
+        match self {
+            RunMode::Test => "test",
+            RunMode::Check => "check",
+            RunMode::Build => "build",
+            RunMode::Clippy => "clippy",
+            RunMode::Fmt => "fmt",
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct TestPlan {
     pub workspace_root: std::path::PathBuf,
     pub crates: TestCrates,
+    pub mode: RunMode,
     pub with_dependents: bool,
     pub fail_fast: bool,
+    /// Number of times a failing crate is retried before it is reported as failed
+    pub retries: usize,
     pub verbose: bool,
     pub test_runner_args: Vec<String>,
+    /// Tests to run for crates selected with module-level precision
+    pub test_filters: IndexMap<String, TestFilter>,
+    /// Crates skipped because they passed before with the same fingerprint
+    pub cached: IndexSet<String>,
+    /// Crates among `cached` whose passing result came from the shared result store
+    pub shared_cached: IndexSet<String>,
+    /// Content fingerprints of the crates to test, recorded in the cache when they pass
+    pub fingerprints: IndexMap<String, String>,
+    /// Known-flaky crates and tests whose failures do not fail the run
+    pub quarantine: Quarantine,
+    /// Order in which the crates to test are run
+    pub schedule: Schedule,
+    /// Results of previous runs for each crate, used by the schedule
+    pub crate_stats: IndexMap<String, CrateStats>,
+    /// Crates whose doc-tests are run separately, for runners that skip them
+    pub doc_test_crates: IndexSet<String>,
+    /// Crates assigned to this job when the plan is split across CI jobs
+    pub shard: Option<ShardAssignment>,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum DiscoveryType {
     Modified,
     Dependent,
+    /// Selected by a `triggered-by` pattern from the configuration
+    Mapped {
+        pattern: String,
+    },
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, JsonSchema)]
 pub struct ManualTestCrate {
     pub name: String,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, JsonSchema)]
 pub struct DiscoveredTestCrate {
     pub name: String,
     pub discovery_type: DiscoveryType,
@@ -36,7 +100,17 @@ pub enum TestCrates {
 }
 
 impl TestPlan {
-    pub fn get_crates_to_test(&self) -> Vec<&String> {
+    /// Crates selected by the plan, including those with cached results
+    pub fn get_selected_crates(&self) -> Vec<&String> {
+        let mut crates = self.get_all_selected_crates();
+        if let Some(shard) = &self.shard {
+            crates.retain(|name| shard.crates.contains(name));
+        }
+        crates
+    }
+
+    /// Crates selected by the plan across every shard
+    pub fn get_all_selected_crates(&self) -> Vec<&String> {
         match &self.crates {
             TestCrates::Manual(crates) => crates.iter().map(|c| &c.name).collect(),
             TestCrates::Discovered(crates) => {
@@ -45,7 +119,7 @@ impl TestPlan {
                 } else {
                     crates
                         .iter()
-                        .filter(|c| matches!(c.discovery_type, DiscoveryType::Modified))
+                        .filter(|c| !matches!(c.discovery_type, DiscoveryType::Dependent))
                         .map(|c| &c.name)
                         .collect()
                 }
@@ -53,7 +127,18 @@ impl TestPlan {
         }
     }
 
+    /// Crates to run tests for, in the order given by the schedule
+    pub fn get_crates_to_test(&self) -> Vec<&String> {
+        let mut crates: Vec<_> = self
+            .get_selected_crates()
+            .into_iter()
+            .filter(|name| !self.cached.contains(*name))
+            .collect();
+        self.schedule.order(&mut crates, &self.crate_stats);
+        crates
+    }
+
     pub fn is_empty(&self) -> bool {
-        self.get_crates_to_test().is_empty()
+        self.get_selected_crates().is_empty()
     }
 }
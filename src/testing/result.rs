@@ -1,37 +1,125 @@
 use std::time::Duration;
 
-#[derive(Debug)]
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use super::cases::{TestCase, TestCounts};
+use super::diagnostics::Diagnostic;
+use crate::error::AppError;
+
+#[derive(Debug, Default)]
 pub struct TestResults {
-    pub passed: Vec<TestResult>,
-    pub failed: Vec<TestResult>,
+    /// Results in the order the crates were tested
+    pub results: Vec<TestResult>,
     pub duration: Duration,
 }
 
-#[derive(Debug)]
+/// The outcome of testing a crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+    /// Failed at first, then passed when retried
+    Flaky,
+    /// Failed only in crates or tests listed in the quarantine file
+    Quarantined,
+}
+
+/// The command a part of a crate's result comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SubResultKind {
+    /// The test runner's own command
+    Tests,
+    /// `cargo test --doc`, for runners that do not run doc-tests
+    DocTests,
+}
+
+impl SubResultKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SubResultKind::Tests => "tests",
+            SubResultKind::DocTests => "doc-tests",
+        }
+    }
+}
+
+/// The result of one of several commands run for a crate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct SubResult {
+    pub kind: SubResultKind,
+    pub success: bool,
+    pub duration_ms: u64,
+    /// Number of tests by outcome, or null if the command's output could not be parsed
+    pub tests: Option<TestCounts>,
+}
+
+#[derive(Debug, Clone)]
 pub struct TestResult {
     pub crate_name: String,
-    pub success: bool,
+    pub outcome: TestOutcome,
+    /// Output of the final attempt, or of the last failed attempt when a retry passed
     pub output: String,
+    pub duration: Duration,
+    /// Individual tests, when the runner reports them in a form that can be parsed
+    pub test_cases: Vec<TestCase>,
+    /// Number of times the crate was run, including retries
+    pub attempts: usize,
+    /// Results of each command, when more than one was run for the crate
+    pub sub_results: Vec<SubResult>,
+    /// Compiler and lint diagnostics, for runners that report them
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl TestResult {
+    /// Whether the crate does not fail the run, because it passed, possibly after being
+    /// retried, or only failed in quarantined tests
+    pub fn success(&self) -> bool {
+        self.outcome != TestOutcome::Failed
+    }
+
+    /// Count the crate's tests by outcome, if the runner's output could be parsed
+    pub fn counts(&self) -> Option<TestCounts> {
+        TestCounts::from_parsed(&self.test_cases)
+    }
 }
 
 impl TestResults {
     pub fn new() -> Self {
-        TestResults {
-            passed: Vec::new(),
-            failed: Vec::new(),
-            duration: Duration::from_secs(0),
-        }
+        TestResults::default()
     }
 
     pub fn add_result(&mut self, result: TestResult) {
-        if result.success {
-            self.passed.push(result);
-        } else {
-            self.failed.push(result);
-        }
+        self.results.push(result);
+    }
+
+    /// The results with the given outcome, in the order the crates were tested
+    pub fn with_outcome(&self, outcome: TestOutcome) -> impl Iterator<Item = &TestResult> {
+        self.results
+            .iter()
+            .filter(move |result| result.outcome == outcome)
+    }
+
+    pub fn count(&self, outcome: TestOutcome) -> usize {
+        self.with_outcome(outcome).count()
     }
 
     pub fn has_failures(&self) -> bool {
-        !self.failed.is_empty()
+        self.count(TestOutcome::Failed) > 0
+    }
+
+    /// Turn failed crates into an error, for the exit status of the run
+    pub fn ensure_success(&self) -> Result<(), AppError> {
+        if !self.has_failures() {
+            return Ok(());
+        }
+
+        Err(AppError::TestsFailed {
+            failed_crates: self
+                .with_outcome(TestOutcome::Failed)
+                .map(|result| result.crate_name.clone())
+                .collect(),
+        })
     }
 }
@@ -0,0 +1,84 @@
+use std::cmp::Ordering;
+
+use clap::ValueEnum;
+use indexmap::IndexMap;
+
+use crate::history::RunRecord;
+
+/// Order in which the crates of a plan are tested
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Schedule {
+    /// Test crates in the order they were discovered (default)
+    #[default]
+    Discovery,
+    /// Test the crates most likely to fail first, for quicker feedback with fail-fast
+    FailuresFirst,
+    /// Test the crates that took longest in previous runs first
+    LongestFirst,
+}
+
+/// How a crate fared across the recorded runs
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CrateStats {
+    pub runs: usize,
+    pub failures: usize,
+    /// Average duration of the runs that tested the crate
+    pub duration_ms: f64,
+}
+
+impl CrateStats {
+    /// Estimated chance that the crate fails, smoothed so that crates with few recorded
+    /// runs are not ranked on a single result
+    pub fn failure_likelihood(&self) -> f64 {
+        (self.failures as f64 + 1.0) / (self.runs as f64 + 2.0)
+    }
+
+    /// Collect the statistics of each crate from the run history
+    pub fn from_history(runs: &[RunRecord]) -> IndexMap<String, CrateStats> {
+        let mut stats: IndexMap<String, CrateStats> = IndexMap::new();
+
+        for outcome in runs.iter().flat_map(|run| &run.crates) {
+            let crate_stats = stats.entry(outcome.crate_name.clone()).or_default();
+            let total_ms = crate_stats.duration_ms * crate_stats.runs as f64;
+
+            crate_stats.runs += 1;
+            crate_stats.failures += usize::from(!outcome.success);
+            crate_stats.duration_ms =
+                (total_ms + outcome.duration_ms as f64) / crate_stats.runs as f64;
+        }
+
+        stats
+    }
+}
+
+impl Schedule {
+    /// Sort crates according to the schedule, keeping the discovery order between crates
+    /// that rank the same
+    ///
+    /// Crates without recorded runs have an even chance of failing and are assumed to be
+    /// slower than any crate with a recorded duration.
+    pub fn order(&self, crates: &mut [&String], stats: &IndexMap<String, CrateStats>) {
+        let stats_of = |name: &String| stats.get(name).copied().unwrap_or_default();
+
+        match self {
+            Schedule::Discovery => {}
+            Schedule::FailuresFirst => crates.sort_by(|a, b| {
+                let (a, b) = (stats_of(a), stats_of(b));
+                descending(a.failure_likelihood(), b.failure_likelihood())
+                    // Among crates as likely to fail, quicker ones report sooner
+                    .then_with(|| a.duration_ms.total_cmp(&b.duration_ms))
+            }),
+            Schedule::LongestFirst => crates.sort_by(|a, b| {
+                let duration = |stats: CrateStats| match stats.runs {
+                    0 => f64::INFINITY,
+                    _ => stats.duration_ms,
+                };
+                descending(duration(stats_of(a)), duration(stats_of(b)))
+            }),
+        }
+    }
+}
+
+fn descending(a: f64, b: f64) -> Ordering {
+    b.total_cmp(&a)
+}
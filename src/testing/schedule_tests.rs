@@ -0,0 +1,98 @@
+use indexmap::IndexMap;
+
+use crate::history::{CrateOutcome, RunRecord};
+use crate::testing::plan::RunMode;
+use crate::testing::schedule::{CrateStats, Schedule};
+
+fn run(outcomes: &[(&str, bool, u64)]) -> RunRecord {
+    RunRecord {
+        started_at: 1,
+        head: None,
+        changed_files: Vec::new(),
+        mode: RunMode::Test,
+        planned: Vec::new(),
+        crates: outcomes
+            .iter()
+            .map(|(name, success, duration_ms)| CrateOutcome {
+                crate_name: name.to_string(),
+                success: *success,
+                flaky: false,
+                duration_ms: *duration_ms,
+                quarantined: Vec::new(),
+            })
+            .collect(),
+        duration_ms: 0,
+    }
+}
+
+/// `stable` always passes quickly, `broken` keeps failing and `slow` passes slowly
+fn stats() -> IndexMap<String, CrateStats> {
+    CrateStats::from_history(&[
+        run(&[
+            ("stable", true, 100),
+            ("broken", false, 300),
+            ("slow", true, 5000),
+        ]),
+        run(&[
+            ("stable", true, 300),
+            ("broken", false, 500),
+            ("slow", true, 3000),
+        ]),
+        run(&[("broken", true, 400)]),
+    ])
+}
+
+fn order(schedule: Schedule, crates: &[&str]) -> Vec<String> {
+    let names: Vec<String> = crates.iter().map(|name| name.to_string()).collect();
+    let mut crates: Vec<&String> = names.iter().collect();
+
+    schedule.order(&mut crates, &stats());
+
+    crates.into_iter().cloned().collect()
+}
+
+#[test]
+fn test_crate_stats_from_history() {
+    let stats = stats();
+
+    assert_eq!(
+        stats["broken"],
+        CrateStats {
+            runs: 3,
+            failures: 2,
+            duration_ms: 400.0,
+        }
+    );
+    assert_eq!(stats["stable"].duration_ms, 200.0);
+    assert!(stats["broken"].failure_likelihood() > stats["stable"].failure_likelihood());
+    assert_eq!(CrateStats::default().failure_likelihood(), 0.5);
+}
+
+#[test]
+fn test_discovery_order() {
+    assert_eq!(
+        order(Schedule::Discovery, &["stable", "new", "slow", "broken"]),
+        vec!["stable", "new", "slow", "broken"]
+    );
+}
+
+#[test]
+fn test_failures_first() {
+    // Crates without history rank between those that keep failing and those that pass,
+    // and the quicker of two crates that never failed runs first
+    assert_eq!(
+        order(
+            Schedule::FailuresFirst,
+            &["slow", "stable", "new", "broken"]
+        ),
+        vec!["broken", "new", "stable", "slow"]
+    );
+}
+
+#[test]
+fn test_longest_first() {
+    assert_eq!(
+        order(Schedule::LongestFirst, &["stable", "broken", "slow", "new"]),
+        vec!["new", "slow", "broken", "stable"]
+    );
+}
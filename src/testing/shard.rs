@@ -0,0 +1,162 @@
+use std::{fmt, fs, path::Path, str::FromStr};
+
+use clap::ValueEnum;
+use indexmap::IndexMap;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use super::schedule::CrateStats;
+use crate::{error::AppError, hash::fnv1a};
+
+/// One of several CI jobs splitting the test plan, given as `<index>/<count>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    /// Position of this job, starting at 1
+    pub index: usize,
+    pub count: usize,
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("expected <index>/<count>, such as 1/4, got '{}'", s);
+
+        let (index, count) = s.split_once('/').ok_or_else(invalid)?;
+        let index: usize = index.trim().parse().map_err(|_| invalid())?;
+        let count: usize = count.trim().parse().map_err(|_| invalid())?;
+
+        if count == 0 || index == 0 || index > count {
+            return Err(format!(
+                "shard index must be between 1 and the shard count, got '{}'",
+                s
+            ));
+        }
+
+        Ok(Shard { index, count })
+    }
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+/// How crates are assigned to shards
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ShardStrategy {
+    /// Assign each crate by a hash of its name (default)
+    #[default]
+    Hash,
+    /// Balance the shards by the crate durations given with `--shard-timings`
+    Duration,
+}
+
+/// The crates assigned to one shard
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct ShardAssignment {
+    /// Position of the shard, starting at 1
+    pub index: usize,
+    pub crates: Vec<String>,
+    /// Sum of the average durations of the crates, when balanced by duration
+    pub estimated_duration_ms: Option<u64>,
+}
+
+impl ShardStrategy {
+    /// Split crates across `count` shards
+    ///
+    /// The assignment only depends on the crate names and, when balancing by duration, the
+    /// statistics, so jobs given the same crates and timings agree on it.
+    pub fn assign(
+        &self,
+        crates: &[&String],
+        count: usize,
+        stats: &IndexMap<String, CrateStats>,
+    ) -> Vec<ShardAssignment> {
+        let mut shards: Vec<ShardAssignment> = (1..=count)
+            .map(|index| ShardAssignment {
+                index,
+                crates: Vec::new(),
+                estimated_duration_ms: None,
+            })
+            .collect();
+
+        match self {
+            ShardStrategy::Hash => {
+                for name in crates {
+                    let shard = (fnv1a(name.as_bytes()) % count as u64) as usize;
+                    shards[shard].crates.push(name.to_string());
+                }
+            }
+            ShardStrategy::Duration => {
+                let known: Vec<f64> = crates
+                    .iter()
+                    .filter_map(|name| stats.get(*name))
+                    .filter(|stats| stats.runs > 0)
+                    .map(|stats| stats.duration_ms)
+                    .collect();
+                // Crates without recorded runs are assumed to take as long as the average crate
+                let fallback = match known.len() {
+                    0 => 1.0,
+                    len => known.iter().sum::<f64>() / len as f64,
+                };
+                let duration = |name: &String| match stats.get(name) {
+                    Some(stats) if stats.runs > 0 => stats.duration_ms,
+                    _ => fallback,
+                };
+
+                let mut by_duration: Vec<(&String, f64)> =
+                    crates.iter().map(|name| (*name, duration(name))).collect();
+                by_duration.sort_by(|(a_name, a), (b_name, b)| {
+                    b.total_cmp(a).then_with(|| a_name.cmp(b_name))
+                });
+
+                // Longest crates first, each to the shard with the least work so far
+                let mut totals = vec![0.0_f64; count];
+                for (name, duration) in by_duration {
+                    let shard = (0..count)
+                        .min_by(|a, b| totals[*a].total_cmp(&totals[*b]))
+                        .unwrap_or_default();
+                    totals[shard] += duration;
+                    shards[shard].crates.push(name.to_string());
+                }
+
+                for (shard, total) in shards.iter_mut().zip(totals) {
+                    shard.estimated_duration_ms = Some(total.round() as u64);
+                }
+            }
+        }
+
+        shards
+    }
+}
+
+/// Load the crate durations shared by every shard job, a JSON object mapping crate names to
+/// milliseconds
+///
+/// Each job's own run history differs, so balancing by it would give each job a different
+/// assignment.
+pub fn load_timings(path: &Path) -> Result<IndexMap<String, CrateStats>, AppError> {
+    let invalid = |reason: String| AppError::InvalidArguments {
+        reason: format!("cannot read shard timings '{}': {}", path.display(), reason),
+    };
+
+    let contents = fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+    let durations: IndexMap<String, u64> =
+        serde_json::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+
+    Ok(durations
+        .into_iter()
+        .map(|(name, duration_ms)| {
+            (
+                name,
+                CrateStats {
+                    runs: 1,
+                    failures: 0,
+                    duration_ms: duration_ms as f64,
+                },
+            )
+        })
+        .collect())
+}
@@ -0,0 +1,125 @@
+use indexmap::IndexMap;
+
+use crate::error::AppError;
+use crate::testing::schedule::CrateStats;
+use crate::testing::shard::{self, Shard, ShardStrategy};
+
+fn names(count: usize) -> Vec<String> {
+    (0..count).map(|i| format!("crate_{}", i)).collect()
+}
+
+fn stats(durations: &[(&str, f64)]) -> IndexMap<String, CrateStats> {
+    durations
+        .iter()
+        .map(|(name, duration_ms)| {
+            (
+                name.to_string(),
+                CrateStats {
+                    runs: 1,
+                    failures: 0,
+                    duration_ms: *duration_ms,
+                },
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_parse_shard() {
+    assert_eq!("2/4".parse::<Shard>(), Ok(Shard { index: 2, count: 4 }));
+    assert_eq!("1/1".parse::<Shard>().unwrap().to_string(), "1/1");
+    assert!("0/4".parse::<Shard>().is_err());
+    assert!("5/4".parse::<Shard>().is_err());
+    assert!("1/0".parse::<Shard>().is_err());
+    assert!("2".parse::<Shard>().is_err());
+    assert!("a/b".parse::<Shard>().is_err());
+}
+
+#[test]
+fn test_hash_assigns_every_crate_once() {
+    let names = names(20);
+    let crates: Vec<&String> = names.iter().collect();
+
+    let shards = ShardStrategy::Hash.assign(&crates, 3, &IndexMap::new());
+
+    assert_eq!(shards.len(), 3);
+    assert_eq!(
+        shards.iter().map(|shard| shard.index).collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+    let mut assigned: Vec<&String> = shards.iter().flat_map(|shard| &shard.crates).collect();
+    assigned.sort();
+    let mut expected = crates.clone();
+    expected.sort();
+    assert_eq!(assigned, expected);
+    assert!(shards.iter().all(|shard| !shard.crates.is_empty()));
+    assert!(shards
+        .iter()
+        .all(|shard| shard.estimated_duration_ms.is_none()));
+}
+
+#[test]
+fn test_hash_does_not_depend_on_other_crates() {
+    let names = names(20);
+    let all: Vec<&String> = names.iter().collect();
+    let some: Vec<&String> = names.iter().step_by(3).collect();
+
+    let all_shards = ShardStrategy::Hash.assign(&all, 4, &IndexMap::new());
+    let some_shards = ShardStrategy::Hash.assign(&some, 4, &IndexMap::new());
+
+    for (all_shard, some_shard) in all_shards.iter().zip(&some_shards) {
+        assert!(some_shard
+            .crates
+            .iter()
+            .all(|name| all_shard.crates.contains(name)));
+    }
+}
+
+#[test]
+fn test_duration_balances_shards() {
+    let names: Vec<String> = ["a", "b", "c", "d", "e"]
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    let crates: Vec<&String> = names.iter().collect();
+    // `e` has no recorded runs and counts as the average of the others, 3500ms
+    let stats = stats(&[("a", 8000.0), ("b", 3000.0), ("c", 2000.0), ("d", 1000.0)]);
+
+    let shards = ShardStrategy::Duration.assign(&crates, 2, &stats);
+
+    assert_eq!(shards[0].crates, vec!["a", "d"]);
+    assert_eq!(shards[0].estimated_duration_ms, Some(9000));
+    assert_eq!(shards[1].crates, vec!["e", "b", "c"]);
+    assert_eq!(shards[1].estimated_duration_ms, Some(8500));
+
+    // Ordering of the input does not change the assignment
+    let reversed: Vec<&String> = names.iter().rev().collect();
+    assert_eq!(ShardStrategy::Duration.assign(&reversed, 2, &stats), shards);
+}
+
+#[test]
+fn test_load_timings() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("timings.json");
+    std::fs::write(&path, r#"{ "a": 8000, "b": 3000 }"#).unwrap();
+
+    let timings = shard::load_timings(&path).unwrap();
+
+    assert_eq!(timings, stats(&[("a", 8000.0), ("b", 3000.0)]));
+}
+
+#[test]
+fn test_load_timings_rejects_invalid_file() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("timings.json");
+    std::fs::write(&path, r#"{ "a": "slow" }"#).unwrap();
+
+    assert!(matches!(
+        shard::load_timings(&path),
+        Err(AppError::InvalidArguments { .. })
+    ));
+    assert!(matches!(
+        shard::load_timings(&dir.path().join("missing.json")),
+        Err(AppError::InvalidArguments { .. })
+    ));
+}
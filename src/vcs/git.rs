@@ -92,6 +92,85 @@ impl Vcs for GitVcs {
 
         Ok(changed_files)
     }
+
+    fn get_head_commit(&self, workspace_root: &Path) -> Result<Option<String>, AppError> {
+        let repo = discover_repo(workspace_root)?;
+        let head = repo.head().map_err(|e| AppError::GitOperationFailed {
+            operation: "head".to_string(),
+            reason: e.to_string(),
+        })?;
+
+        Ok(head.id().map(|id| id.to_string()))
+    }
+
+    fn get_tree_id(&self, workspace_root: &Path, path: &Path) -> Result<Option<String>, AppError> {
+        let repo = discover_repo(workspace_root)?;
+        let tree_failed = |e: &dyn std::fmt::Display| AppError::GitOperationFailed {
+            operation: "tree lookup".to_string(),
+            reason: e.to_string(),
+        };
+
+        let Ok(commit) = repo.head_commit() else {
+            return Ok(None);
+        };
+        let tree = get_commit_tree(&commit)?;
+
+        if path.as_os_str().is_empty() {
+            return Ok(Some(tree.id.to_string()));
+        }
+
+        let entry = tree
+            .lookup_entry_by_path(path)
+            .map_err(|e| tree_failed(&e))?;
+
+        Ok(entry
+            .filter(|entry| entry.mode().is_tree())
+            .map(|entry| entry.oid().to_string()))
+    }
+
+    fn filter_ignored(
+        &self,
+        workspace_root: &Path,
+        paths: Vec<PathBuf>,
+    ) -> Result<Vec<PathBuf>, AppError> {
+        let repo = discover_repo(workspace_root)?;
+        let excludes_failed = |e: &dyn std::fmt::Display| AppError::GitOperationFailed {
+            operation: "ignore lookup".to_string(),
+            reason: e.to_string(),
+        };
+
+        let Some(workdir) = repo.workdir().map(Path::to_path_buf) else {
+            return Ok(paths);
+        };
+        let workdir = workdir.canonicalize().unwrap_or(workdir);
+        let index = repo.index_or_empty().map_err(|e| excludes_failed(&e))?;
+        let mut excludes = repo
+            .excludes(
+                &index,
+                None,
+                gix::worktree::stack::state::ignore::Source::WorktreeThenIdMappingIfNotSkipped,
+            )
+            .map_err(|e| excludes_failed(&e))?;
+
+        let mut kept = Vec::with_capacity(paths.len());
+        for path in paths {
+            let excluded = match path.strip_prefix(&workdir) {
+                Ok(relative) => {
+                    let mode = path.is_dir().then_some(gix::index::entry::Mode::DIR);
+                    excludes
+                        .at_path(relative, mode)
+                        .map_err(|e| excludes_failed(&e))?
+                        .is_excluded()
+                }
+                Err(_) => false,
+            };
+            if !excluded {
+                kept.push(path);
+            }
+        }
+
+        Ok(kept)
+    }
 }
 
 fn discover_repo(workspace_root: &Path) -> Result<Repository, AppError> {
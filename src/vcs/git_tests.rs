@@ -1,4 +1,5 @@
 use std::fs;
+use std::path::Path;
 use std::process::Command;
 use tempfile::TempDir;
 
@@ -64,6 +65,77 @@ mod workspace_root_tests {
     }
 }
 
+mod head_commit_tests {
+    use super::*;
+
+    #[test]
+    fn test_get_head_commit() -> Result<(), Box<dyn std::error::Error>> {
+        let test_repo = test_utils::TestRepo::new()?;
+        let git_vcs = GitVcs;
+
+        // Nothing has been committed yet
+        assert_eq!(git_vcs.get_head_commit(&test_repo.repo_path)?, None);
+
+        let commit = test_repo.create_and_commit_file("file.txt", "content")?;
+        assert_eq!(git_vcs.get_head_commit(&test_repo.repo_path)?, Some(commit));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_tree_id() -> Result<(), Box<dyn std::error::Error>> {
+        let test_repo = test_utils::TestRepo::new()?;
+        let git_vcs = GitVcs;
+
+        assert_eq!(
+            git_vcs.get_tree_id(&test_repo.repo_path, Path::new("a"))?,
+            None
+        );
+
+        fs::create_dir_all(test_repo.repo_path.join("a/src"))?;
+        fs::create_dir_all(test_repo.repo_path.join("b"))?;
+        test_repo.create_file("a/src/lib.rs", "fn a() {}")?;
+        test_repo.create_file("b/lib.rs", "fn b() {}")?;
+        test_repo.stage_all()?;
+        test_repo.commit("Add crates")?;
+
+        let rev_parse = |spec: &str| -> Result<String, Box<dyn std::error::Error>> {
+            let output = Command::new("git")
+                .args(["rev-parse", spec])
+                .current_dir(&test_repo.repo_path)
+                .output()?;
+            Ok(String::from_utf8(output.stdout)?.trim().to_string())
+        };
+
+        let tree_a = git_vcs.get_tree_id(&test_repo.repo_path, Path::new("a"))?;
+        assert_eq!(tree_a, Some(rev_parse("HEAD:a")?));
+        assert_eq!(
+            git_vcs.get_tree_id(&test_repo.repo_path, Path::new(""))?,
+            Some(rev_parse("HEAD^{tree}")?)
+        );
+        // Files and missing paths have no tree
+        assert_eq!(
+            git_vcs.get_tree_id(&test_repo.repo_path, Path::new("b/lib.rs"))?,
+            None
+        );
+        assert_eq!(
+            git_vcs.get_tree_id(&test_repo.repo_path, Path::new("c"))?,
+            None
+        );
+
+        // Changing a file in b leaves the tree of a untouched
+        test_repo.modify_file("b/lib.rs", "fn b() { todo!() }")?;
+        test_repo.stage_all()?;
+        test_repo.commit("Change b")?;
+        assert_eq!(
+            git_vcs.get_tree_id(&test_repo.repo_path, Path::new("a"))?,
+            tree_a
+        );
+
+        Ok(())
+    }
+}
+
 mod uncommitted_changes_tests {
     use super::*;
 
@@ -1030,3 +1102,32 @@ mod commit_diff_tests {
         Ok(())
     }
 }
+
+mod ignore_tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_ignored() -> Result<(), Box<dyn std::error::Error>> {
+        let test_repo = test_utils::TestRepo::new()?;
+        let root = test_repo.repo_path.canonicalize()?;
+        test_repo.create_file(".gitignore", "*.log\nbuild/\n")?;
+        fs::create_dir_all(root.join("build"))?;
+        fs::create_dir_all(root.join("src"))?;
+
+        let git_vcs = GitVcs;
+        let kept = git_vcs.filter_ignored(
+            &root,
+            vec![
+                root.join("src/lib.rs"),
+                root.join("debug.log"),
+                root.join("build/output.rs"),
+                root.join("build"),
+                root.join(".gitignore"),
+            ],
+        )?;
+
+        assert_eq!(kept, vec![root.join("src/lib.rs"), root.join(".gitignore")]);
+
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use clap::ValueEnum;
+use schemars::JsonSchema;
 use serde::Serialize;
 use std::path::{Path, PathBuf};
 
@@ -12,7 +13,7 @@ mod git_tests;
 #[cfg(test)]
 mod test_utils;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ChangeType {
     Added,
@@ -20,7 +21,7 @@ pub enum ChangeType {
     Removed,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum FileType {
     File,
@@ -29,7 +30,7 @@ pub enum FileType {
     Other,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, JsonSchema)]
 pub struct ChangedFile {
     pub current_path: PathBuf,
     pub old_path: Option<PathBuf>,
@@ -54,9 +55,24 @@ pub trait Vcs {
         from_ref: &str,
         to_ref: Option<&str>,
     ) -> Result<Vec<ChangedFile>, AppError>;
+
+    /// Get the commit currently checked out, or `None` if nothing has been committed yet
+    fn get_head_commit(&self, workspace_root: &Path) -> Result<Option<String>, AppError>;
+
+    /// Get the ID of a directory's tree at the current commit, relative to the workspace root
+    ///
+    /// Returns `None` if nothing has been committed yet or the directory is not tracked.
+    fn get_tree_id(&self, workspace_root: &Path, path: &Path) -> Result<Option<String>, AppError>;
+
+    /// Drop the paths the VCS ignores, such as those matched by `.gitignore`
+    fn filter_ignored(
+        &self,
+        workspace_root: &Path,
+        paths: Vec<PathBuf>,
+    ) -> Result<Vec<PathBuf>, AppError>;
 }
 
-#[derive(Debug, Clone, ValueEnum)]
+#[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum VcsType {
     Git,
 }
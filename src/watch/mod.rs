@@ -0,0 +1,234 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use indexmap::IndexSet;
+use notify_debouncer_full::{
+    new_debouncer_opt,
+    notify::{
+        self,
+        event::{EventKind, ModifyKind},
+        RecommendedWatcher, RecursiveMode,
+    },
+    DebounceEventResult, Debouncer, NoCache,
+};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    error::AppError,
+    metadata::IgnorePatterns,
+    vcs::{ChangeType, ChangedFile, FileType, VcsType},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Default time to wait for edits to settle before starting a run
+pub const DEFAULT_DEBOUNCE_MS: u64 = 300;
+
+/// What watch mode is doing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum WatchStatus {
+    /// Waiting for files to change
+    Watching {
+        /// Whether the previous run passed, if there was one
+        last_run_passed: Option<bool>,
+    },
+    /// Files changed since the last completed run and the crates they affect are tested
+    Changed {
+        files: Vec<PathBuf>,
+        crates: Vec<String>,
+    },
+    /// The run in progress was stopped because more files changed
+    Cancelled,
+}
+
+/// Decides which changed paths are worth a run: build output, VCS metadata, files the VCS
+/// ignores and files matching the configured `ignore` patterns are left out
+///
+/// Clones share the `ignore` patterns, so the watch loop can update them when the manifest
+/// changes.
+#[derive(Clone)]
+pub struct WatchFilter {
+    workspace_root: PathBuf,
+    ignored_dirs: Vec<PathBuf>,
+    vcs: VcsType,
+    ignore_patterns: Arc<Mutex<IgnorePatterns>>,
+}
+
+impl WatchFilter {
+    pub fn new(
+        workspace_root: &Path,
+        ignored_dirs: Vec<PathBuf>,
+        vcs: VcsType,
+        ignore_patterns: IgnorePatterns,
+    ) -> Self {
+        WatchFilter {
+            workspace_root: workspace_root.to_path_buf(),
+            ignored_dirs,
+            vcs,
+            ignore_patterns: Arc::new(Mutex::new(ignore_patterns)),
+        }
+    }
+
+    /// Replace the configured `ignore` patterns after the configuration is reloaded
+    pub fn set_ignore_patterns(&self, ignore_patterns: IgnorePatterns) {
+        if let Ok(mut current) = self.ignore_patterns.lock() {
+            *current = ignore_patterns;
+        }
+    }
+
+    /// Keep the paths whose changes can affect the tests
+    ///
+    /// If the VCS cannot be queried, paths are kept rather than missing a change.
+    pub fn retain(&self, paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        let paths: Vec<PathBuf> = paths
+            .into_iter()
+            .filter(|path| is_watched(path, &self.ignored_dirs))
+            .collect();
+        let paths = match self.ignore_patterns.lock() {
+            Ok(patterns) => paths
+                .into_iter()
+                .filter(|path| !patterns.is_ignored(path))
+                .collect(),
+            Err(_) => paths,
+        };
+        if paths.is_empty() {
+            return paths;
+        }
+
+        let vcs = self.vcs.create();
+        match vcs.filter_ignored(&self.workspace_root, paths.clone()) {
+            Ok(kept) => kept,
+            Err(_) => paths,
+        }
+    }
+}
+
+/// Watches the workspace for file changes, ignoring those the filter leaves out
+pub struct FileWatcher {
+    _debouncer: Debouncer<RecommendedWatcher, NoCache>,
+    changes: Receiver<Result<Vec<PathBuf>, AppError>>,
+}
+
+impl FileWatcher {
+    /// Watch the workspace, calling `on_change` from the watcher's thread as soon as a batch of
+    /// changes the filter keeps has settled, before it is returned by `wait`
+    pub fn new(
+        workspace_root: &Path,
+        filter: WatchFilter,
+        debounce: Duration,
+        on_change: impl Fn() + Send + 'static,
+    ) -> Result<Self, AppError> {
+        let (sender, changes) = mpsc::channel();
+
+        let handler = move |result: DebounceEventResult| {
+            let batch = match result {
+                Ok(events) => {
+                    let paths: Vec<PathBuf> = events
+                        .into_iter()
+                        .filter(|event| is_content_change(&event.kind))
+                        .flat_map(|event| event.event.paths)
+                        .collect();
+                    let paths = filter.retain(paths);
+                    if paths.is_empty() {
+                        return;
+                    }
+                    Ok(paths)
+                }
+                Err(errors) => Err(AppError::WatchFailed {
+                    reason: errors
+                        .iter()
+                        .map(|e| e.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                }),
+            };
+
+            on_change();
+            let _ = sender.send(batch);
+        };
+
+        let watch_failed = |e: notify::Error| AppError::WatchFailed {
+            reason: e.to_string(),
+        };
+        // Rename tracking is not needed, and its cache would scan the target directory
+        let mut debouncer =
+            new_debouncer_opt(debounce, None, handler, NoCache, notify::Config::default())
+                .map_err(watch_failed)?;
+        debouncer
+            .watch(workspace_root, RecursiveMode::Recursive)
+            .map_err(watch_failed)?;
+
+        Ok(FileWatcher {
+            _debouncer: debouncer,
+            changes,
+        })
+    }
+
+    /// Block until files change, returning every path changed since the previous call
+    pub fn wait(&self) -> Result<IndexSet<PathBuf>, AppError> {
+        let disconnected = || AppError::WatchFailed {
+            reason: "the file watcher stopped".to_string(),
+        };
+
+        let mut paths: IndexSet<PathBuf> = self
+            .changes
+            .recv()
+            .map_err(|_| disconnected())??
+            .into_iter()
+            .collect();
+        while let Ok(batch) = self.changes.try_recv() {
+            paths.extend(batch?);
+        }
+
+        Ok(paths)
+    }
+}
+
+/// Whether an event changed a file, rather than only reading it or touching its metadata,
+/// as the runner does while building and testing
+fn is_content_change(kind: &EventKind) -> bool {
+    !matches!(
+        kind,
+        EventKind::Access(_) | EventKind::Modify(ModifyKind::Metadata(_))
+    )
+}
+
+/// Whether a change to the path can affect the tests, which excludes build output and VCS
+/// metadata
+pub fn is_watched(path: &Path, ignored_dirs: &[PathBuf]) -> bool {
+    !ignored_dirs.iter().any(|dir| path.starts_with(dir))
+}
+
+/// Describe changed paths the way the VCS reports uncommitted changes
+///
+/// Directories are left out, since watchers also report a directory when an entry in it is
+/// added or removed.
+pub fn changed_files(paths: &IndexSet<PathBuf>) -> Vec<ChangedFile> {
+    paths
+        .iter()
+        .filter_map(|path| {
+            let (file_type, change_type) = match path.symlink_metadata() {
+                Ok(metadata) if metadata.is_dir() => return None,
+                Ok(metadata) if metadata.is_symlink() => (FileType::Symlink, ChangeType::Modified),
+                Ok(_) => (FileType::File, ChangeType::Modified),
+                Err(_) => (FileType::File, ChangeType::Removed),
+            };
+
+            Some(ChangedFile {
+                current_path: path.clone(),
+                old_path: None,
+                file_type,
+                change_type,
+            })
+        })
+        .collect()
+}
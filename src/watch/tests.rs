@@ -0,0 +1,101 @@
+use std::{fs, path::PathBuf, process::Command};
+
+use indexmap::IndexSet;
+use tempfile::TempDir;
+
+use super::*;
+use crate::config::Config;
+
+#[test]
+fn test_is_watched() {
+    let ignored_dirs = vec![
+        PathBuf::from("/workspace/target"),
+        PathBuf::from("/workspace/.git"),
+    ];
+
+    assert!(is_watched(
+        Path::new("/workspace/crates/a/src/lib.rs"),
+        &ignored_dirs
+    ));
+    assert!(is_watched(
+        Path::new("/workspace/targets.md"),
+        &ignored_dirs
+    ));
+    assert!(!is_watched(
+        Path::new("/workspace/target/debug/build/a/output"),
+        &ignored_dirs
+    ));
+    assert!(!is_watched(
+        Path::new("/workspace/.git/index"),
+        &ignored_dirs
+    ));
+}
+
+#[test]
+fn test_watch_filter_retain() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    Command::new("git")
+        .arg("init")
+        .current_dir(&root)
+        .output()
+        .unwrap();
+    fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+
+    let config = Config {
+        ignore: vec!["**/*.md".to_string()],
+        ..Default::default()
+    };
+    let filter = WatchFilter::new(
+        &root,
+        vec![root.join("target"), root.join(".git")],
+        VcsType::Git,
+        IgnorePatterns::from_config(&config, &root).unwrap(),
+    );
+    let paths = vec![
+        root.join("src/lib.rs"),
+        root.join("target/debug/output"),
+        root.join(".git/index"),
+        root.join("server.log"),
+        root.join("README.md"),
+    ];
+
+    assert_eq!(filter.retain(paths.clone()), vec![root.join("src/lib.rs")]);
+
+    // Clones share the patterns, so a reloaded configuration applies to the running watcher
+    filter
+        .clone()
+        .set_ignore_patterns(IgnorePatterns::from_config(&Config::default(), &root).unwrap());
+    assert_eq!(
+        filter.retain(paths),
+        vec![root.join("src/lib.rs"), root.join("README.md")]
+    );
+}
+
+#[test]
+fn test_changed_files() {
+    let dir = TempDir::new().unwrap();
+    let modified = dir.path().join("lib.rs");
+    let removed = dir.path().join("old.rs");
+    fs::write(&modified, "").unwrap();
+
+    let paths = IndexSet::from([modified.clone(), removed.clone(), dir.path().to_path_buf()]);
+
+    assert_eq!(
+        changed_files(&paths),
+        vec![
+            ChangedFile {
+                current_path: modified,
+                old_path: None,
+                file_type: FileType::File,
+                change_type: ChangeType::Modified,
+            },
+            ChangedFile {
+                current_path: removed,
+                old_path: None,
+                file_type: FileType::File,
+                change_type: ChangeType::Removed,
+            },
+        ]
+    );
+}